@@ -0,0 +1,93 @@
+extern crate libgc;
+extern crate getopts;
+
+use std::fs;
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use libgc::bristol::{Circuit,Converter,export};
+use libgc::parser;
+use getopts::{Options,Matches};
+
+macro_rules! fail_on_error {
+    ($exp:expr, $msg:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{} - {}", $msg, why);
+                return;
+            },
+        }
+    };
+    ($exp:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{}", why);
+                return;
+            },
+        }
+    };
+}
+
+macro_rules! must {
+    ($exp:expr, $fail:expr) => {
+        match $exp {
+            Some(val) => val,
+            None => {
+                $fail;
+                return;
+            },
+        }
+    };
+}
+
+fn help(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options]", program);
+    print!("{}", opts.usage(&brief));
+}
+
+fn short_help(program: &str, opts: Options) {
+    println!("{}", opts.short_usage(program));
+}
+
+// cargo build --release
+// ./target/release/gc-bristol --import adder64.txt --dst DST
+// ./target/release/gc-bristol --export SRC --dst adder64.txt
+
+pub fn main(){
+    let mut opts = Options::new();
+    opts.optopt("", "import", "path to a Bristol (Fashion) circuit file.", "FILE");
+    opts.optopt("", "export", "path to a directory containing the libgc files.", "SRC");
+    opts.optopt("", "dst", "path to the libgc directory (import) or the Bristol file (export).", "DST");
+    opts.optflag("", "keep-NOT", "disable INV gate replacement - a binary circuit containing NOT gates cannot turned into a garbled circuit");
+    opts.optflag("h", "help", "print this help menu");
+
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+    let matches: Matches = fail_on_error!(opts.parse(&args[1..]));
+
+    if matches.opt_present("h") {
+        help(&program, opts);
+        return;
+    }
+
+    let dst = must!(matches.opt_str("dst"), short_help(&program, opts));
+    match (matches.opt_str("import"), matches.opt_str("export")) {
+        (Some(src), None) => {
+            let circuit = fail_on_error!(Circuit::open(Path::new(src.as_str())), src);
+            let converter = fail_on_error!(Converter::new(Path::new(dst.as_str())), dst);
+            fail_on_error!(converter.convert(&circuit, matches.opt_present("keep-NOT")));
+        },
+        (None, Some(src)) => {
+            let src_path = fail_on_error!(fs::canonicalize(Path::new(src.as_str())), src);
+            let info = fail_on_error!(parser::parse_meta_info(src_path.as_path()));
+            let circuit = fail_on_error!(export(&info));
+            let mut file = fail_on_error!(File::create(Path::new(dst.as_str())), dst);
+            fail_on_error!(file.write_fmt(format_args!("{}", circuit)));
+        },
+        _ => short_help(&program, opts),
+    };
+}
@@ -5,7 +5,8 @@ use std::fs;
 use std::env;
 use std::path::Path;
 
-use libgc::cbmc::{Parser,Converter,sort_gates};
+use libgc::cbmc::{Parser,Converter,Party,sort_gates};
+use libgc::parser::types::Interface;
 use getopts::{Options,Matches};
 
 macro_rules! fail_on_error {
@@ -86,6 +87,10 @@ pub fn main(){
     let sorted_gates = fail_on_error!(sort_gates(&gates));
     let inputs = fail_on_error!(parser.parse_inputs());
     let constant = fail_on_error!(parser.parse_constant());
+    let mut interface = Interface::default();
+    interface.inputs = fail_on_error!(parser.parse_party_inputs(Party::A));
+    interface.inputs.extend(fail_on_error!(parser.parse_party_inputs(Party::B)));
+    interface.outputs = fail_on_error!(parser.parse_outputs());
 
     let converter = fail_on_error!(Converter::with_capacity(cap, dst_path.as_path()));    
     let (sorted_gates, constant) = if !matches.opt_present("keep-NOT") { 
@@ -96,4 +101,5 @@ pub fn main(){
 
     fail_on_error!(converter.convert_circuit(&inputs, &sorted_gates));
     fail_on_error!(converter.create_meta_info(&inputs, &sorted_gates, constant));
+    fail_on_error!(converter.create_interface(&interface));
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+extern crate libgc;
+extern crate rand;
+
+use std::fs;
+use std::env;
+use std::path::Path;
+use std::process;
+use std::net::TcpStream;
+use std::collections::HashMap;
+
+use libgc::parser;
+use libgc::channel::BufStream;
+use libgc::protocol::{Assignment, EvaluatorParty};
+
+macro_rules! fail_on_error {
+    ($exp:expr, $msg:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{} - {}", $msg, why);
+                process::exit(1);
+            },
+        }
+    };
+    ($exp:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{}", why);
+                process::exit(1);
+            },
+        }
+    };
+}
+
+fn show_help(){
+    println!("gc-eval is the evaluator of a two-party computation of a libgc circuit\n");
+    println!("Usage:");
+    println!("\t gc-eval -c [circuit_path] -a [address] [input bits]\n");
+    println!("\t c\n \t\t path to a directory containing the libgc circuit files.");
+    println!("\t a\n \t\t address of the garbler - e.g. 127.0.0.1:7766");
+    println!("");
+    println!("The input bits of the evaluator are the last inputs of the circuit.");
+    println!("");
+    println!("Example:");
+    println!("\t gc-eval -c /home/foo/sum32 -a 127.0.0.1:7766 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0");
+    process::exit(0);
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() >= 4 && args[0].starts_with("-c") && args[2].starts_with("-a") {
+        let path = fail_on_error!(fs::canonicalize(Path::new(args[1].as_str())), args[1]);
+        let info = fail_on_error!(parser::parse_meta_info(path.as_path()));
+
+        let bits = args.len() as u64 - 4;
+        if bits > info.inputs {
+            println!("too many input bits - the circuit has {} inputs", info.inputs);
+            process::exit(1);
+        }
+        let mut inputs = HashMap::new();
+        let mut i = info.inputs - bits + 1;
+        for arg in args.iter().skip(4) {
+            if arg.trim() == "1" {
+                inputs.insert(i, 1);
+            } else {
+                inputs.insert(i, 0);
+            }
+            i += 1
+        }
+        let assignment = Assignment::split(info.inputs, info.inputs - bits);
+        let mut party = fail_on_error!(EvaluatorParty::new(info, assignment));
+
+        let stream = fail_on_error!(TcpStream::connect(args[3].as_str()), args[3]);
+        let mut ch = BufStream::new(fail_on_error!(stream.try_clone()), stream);
+
+        let out_bits = fail_on_error!(party.run(&mut ch, &mut rand::thread_rng(), &inputs));
+        for bit in out_bits {
+            print!("{} ", bit);
+        }
+        println!("");
+    }else{ 
+        show_help();
+    }
+}
@@ -0,0 +1,81 @@
+extern crate libgc;
+extern crate rand;
+
+use std::fs;
+use std::env;
+use std::path::Path;
+use std::process;
+use std::net::TcpListener;
+use std::collections::HashMap;
+
+use libgc::parser;
+use libgc::channel::BufStream;
+use libgc::protocol::{Assignment, GarblerParty};
+
+macro_rules! fail_on_error {
+    ($exp:expr, $msg:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{} - {}", $msg, why);
+                process::exit(1);
+            },
+        }
+    };
+    ($exp:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{}", why);
+                process::exit(1);
+            },
+        }
+    };
+}
+
+fn show_help(){
+    println!("gc-garble is the garbler of a two-party computation of a libgc circuit\n");
+    println!("Usage:");
+    println!("\t gc-garble -c [circuit_path] -a [address] [input bits]\n");
+    println!("\t c\n \t\t path to a directory containing the libgc circuit files.");
+    println!("\t a\n \t\t address to listen on for the evaluator - e.g. 127.0.0.1:7766");
+    println!("");
+    println!("The input bits of the garbler are the first inputs of the circuit.");
+    println!("");
+    println!("Example:");
+    println!("\t gc-garble -c /home/foo/sum32 -a 127.0.0.1:7766 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0");
+    process::exit(0);
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() >= 4 && args[0].starts_with("-c") && args[2].starts_with("-a") {
+        let path = fail_on_error!(fs::canonicalize(Path::new(args[1].as_str())), args[1]);
+        let info = fail_on_error!(parser::parse_meta_info(path.as_path()));
+
+        let mut inputs = HashMap::new();
+        let mut i = 1;
+        for arg in args.iter().skip(4) {
+            if arg.trim() == "1" {
+                inputs.insert(i, 1);
+            } else {
+                inputs.insert(i, 0);
+            }
+            i += 1
+        }
+        let assignment = Assignment::split(info.inputs, inputs.len() as u64);
+        let mut party = fail_on_error!(GarblerParty::new(info, assignment));
+
+        let listener = fail_on_error!(TcpListener::bind(args[3].as_str()), args[3]);
+        let (stream, _) = fail_on_error!(listener.accept());
+        let mut ch = BufStream::new(fail_on_error!(stream.try_clone()), stream);
+
+        let out_bits = fail_on_error!(party.run(&mut ch, &mut rand::thread_rng(), &inputs));
+        for bit in out_bits {
+            print!("{} ", bit);
+        }
+        println!("");
+    }else{ 
+        show_help();
+    }
+}
@@ -0,0 +1,94 @@
+extern crate libgc;
+extern crate getopts;
+
+use std::fs;
+use std::env;
+use std::path::Path;
+
+use libgc::parser;
+use libgc::parser::error::ParseError;
+use getopts::{Options,Matches};
+
+macro_rules! fail_on_error {
+    ($exp:expr, $msg:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{} - {}", $msg, why);
+                return;
+            },
+        }
+    };
+    ($exp:expr) => {
+        match $exp {
+            Ok(val) => val,
+            Err(why) => {
+                println!("{}", why);
+                return;
+            },
+        }
+    };
+}
+
+macro_rules! must {
+    ($exp:expr, $fail:expr) => {
+        match $exp {
+            Some(val) => val,
+            None => {
+                $fail;
+                return;
+            },
+        }
+    };
+}
+
+fn help(program: &str, opts: Options) {
+    let brief = format!("Usage: {} [options]", program);
+    print!("{}", opts.usage(&brief));
+}
+
+fn short_help(program: &str, opts: Options) {
+    println!("{}", opts.short_usage(program));
+}
+
+// converts the circuit and all its sub circuits
+fn convert(path: &Path, unpack: bool, keep: bool) -> Result<(), ParseError> {
+    let info = try!(parser::parse_meta_info(path));
+    let (src, result) = if unpack {
+        ("circuit.bin", parser::unpack_circuit(path))
+    } else {
+        ("circuit.txt", parser::pack_circuit(path))
+    };
+    try!(result);
+    if !keep {
+        try!(fs::remove_file(path.join(src)));
+    }
+    for sub in info.sub_circuits.values() {
+        try!(convert(Path::new(sub), unpack, keep));
+    }
+    Ok(())
+}
+
+// cargo build --release
+// ./target/release/gc-pack --src SRC [--unpack] [--keep]
+
+pub fn main(){
+    let mut opts = Options::new();
+    opts.optopt("", "src", "path to a directory containing the libgc files.", "SRC");
+    opts.optflag("", "unpack", "convert the compact circuit.bin back into circuit.txt");
+    opts.optflag("", "keep", "keep the source circuit file - circuit.txt is preferred if both exist");
+    opts.optflag("h", "help", "print this help menu");
+
+    let args: Vec<String> = env::args().collect();
+    let program = args[0].clone();
+    let matches: Matches = fail_on_error!(opts.parse(&args[1..]));
+
+    if matches.opt_present("h") {
+        help(&program, opts);
+        return;
+    }
+
+    let src = must!(matches.opt_str("src"), short_help(&program, opts));
+    let src_path = fail_on_error!(fs::canonicalize(Path::new(src.as_str())), src);
+    fail_on_error!(convert(src_path.as_path(), matches.opt_present("unpack"), matches.opt_present("keep")));
+}
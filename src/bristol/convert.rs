@@ -0,0 +1,267 @@
+use std::fs;
+use std::path::Path;
+use std::collections::HashMap;
+use std::io::{Error as IOError, ErrorKind, Result as IOResult};
+
+use super::error::Error;
+use super::types::{Circuit, Gate};
+use super::super::circuit::binary::Graph;
+use super::super::parser;
+use super::super::parser::MetaInfo;
+use super::super::parser::types::{ID, Pin, Edge, Node, GateType, Variable};
+
+/// Source is the libgc node which drives a Bristol wire.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Source {
+    Input(u64),
+    Gate(u64),
+    One,
+}
+
+/// A Converter turns Bristol circuits into libgc circuits - it writes the
+/// `circuit.txt`, `meta_info.txt` and `interface.txt` files into a directory.
+///
+pub struct Converter<'a> {
+    path: &'a Path,
+}
+
+impl<'a> Converter<'a> {
+    pub fn new(path: &'a Path) -> IOResult<Converter<'a>> {
+        if !path.is_dir() {
+            if !path.exists() {
+                try!(fs::create_dir(path));
+            } else {
+                return Err(IOError::new(ErrorKind::NotFound,
+                                        format!("{} isn't a folder", path.display())));
+            }
+        }
+        Ok(Converter { path: path })
+    }
+
+    /// Converts the Bristol `circuit`. The input values become the input
+    /// variables `INPUT_1, INPUT_2, ...` and the output values the output
+    /// variables `OUTPUT_1, ...` of the interface. INV gates are replaced by
+    /// XOR gates with the ONE constant unless `keep_not` is set. Gates which
+    /// do not contribute to an output are removed.
+    pub fn convert(&self, circuit: &Circuit, keep_not: bool) -> IOResult<()> {
+        let wires = circuit.wires() as usize;
+        let inputs = circuit.input_wires();
+        let outputs = circuit.output_wires();
+
+        let mut live = vec![false; wires];
+        for wire in wires - outputs as usize..wires {
+            live[wire] = true;
+        }
+        for gate in circuit.gates().iter().rev() {
+            if live[gate.output() as usize] {
+                for wire in gate.inputs() {
+                    live[wire as usize] = true;
+                }
+            }
+        }
+
+        let mut source: Vec<Option<Source>> = vec![None; wires];
+        for i in 0..inputs {
+            source[i as usize] = Some(Source::Input(i + 1));
+        }
+        let mut graph = Wiring {
+            inputs: vec![Vec::new(); inputs as usize],
+            gates: Vec::new(),
+            one: Vec::new(),
+        };
+        for gate in circuit.gates() {
+            let out = gate.output() as usize;
+            if !live[out] {
+                continue;
+            }
+            source[out] = match *gate {
+                Gate::XOR(a, b, _) => {
+                    Some(graph.gate(GateType::XOR, source[a as usize], source[b as usize]))
+                }
+                Gate::AND(a, b, _) => {
+                    Some(graph.gate(GateType::AND, source[a as usize], source[b as usize]))
+                }
+                Gate::INV(a, _) if keep_not => {
+                    Some(graph.gate(GateType::NOT, source[a as usize], None))
+                }
+                Gate::INV(a, _) => {
+                    Some(graph.gate(GateType::XOR, source[a as usize], Some(Source::One)))
+                }
+                Gate::EQ(1, _) => Some(Source::One),
+                Gate::EQ(_, _) => {
+                    Some(graph.gate(GateType::XOR, Some(Source::One), Some(Source::One)))
+                }
+                Gate::EQW(a, _) => source[a as usize],
+            };
+        }
+        for (k, wire) in (wires - outputs as usize..wires).enumerate() {
+            graph.connect(source[wire], Edge::new(ID::Output(k as u64 + 1), None, None));
+        }
+
+        let mut info = MetaInfo::default();
+        info.inputs = inputs;
+        info.gates = graph.gates.len() as u64;
+        info.outputs = outputs;
+        if !graph.one.is_empty() {
+            info.one = Some(Node::new(ID::Const, None, None, graph.one));
+        }
+        let mut nodes = Vec::with_capacity(inputs as usize + graph.gates.len());
+        for (i, edges) in graph.inputs.into_iter().enumerate() {
+            if !edges.is_empty() {
+                nodes.push(Node::new(ID::Input(i as u64 + 1), None, None, edges));
+            }
+        }
+        for (i, (gate_type, edges)) in graph.gates.into_iter().enumerate() {
+            nodes.push(Node::new(ID::Gate(i as u64 + 1), Some(gate_type), None, edges));
+        }
+
+        let mut first = 1;
+        for (i, len) in circuit.inputs().iter().enumerate() {
+            info.interface.inputs.push(Variable::input(&format!("INPUT_{}", i + 1), first, *len));
+            first += *len;
+        }
+        let mut first = 1;
+        for (i, len) in circuit.outputs().iter().enumerate() {
+            let ids: Vec<u64> = (first..first + *len).collect();
+            info.interface.outputs.push(Variable::output(&format!("OUTPUT_{}", i + 1),
+                                                         &format!("UINT{}", len),
+                                                         &ids));
+            first += *len;
+        }
+        match parser::write_libgc(self.path, &info, &nodes) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(IOError::new(ErrorKind::InvalidData, format!("{}", err))),
+        }
+    }
+}
+
+/// Wiring collects the edges of all libgc nodes while a Bristol circuit is
+/// converted.
+struct Wiring {
+    inputs: Vec<Vec<Edge>>,
+    gates: Vec<(GateType, Vec<Edge>)>,
+    one: Vec<Edge>,
+}
+
+impl Wiring {
+    fn gate(&mut self, gate_type: GateType, a: Option<Source>, b: Option<Source>) -> Source {
+        self.gates.push((gate_type, Vec::new()));
+        let id = self.gates.len() as u64;
+        self.connect(a, Edge::new(ID::Gate(id), Some(Pin::Left), None));
+        if gate_type != GateType::NOT {
+            self.connect(b, Edge::new(ID::Gate(id), Some(Pin::Right), None));
+        }
+        Source::Gate(id)
+    }
+
+    fn connect(&mut self, source: Option<Source>, edge: Edge) {
+        // verified circuits assign every wire before it is read
+        match source.expect("wire is not assigned") {
+            Source::Input(id) => self.inputs[id as usize - 1].push(edge),
+            Source::Gate(id) => self.gates[id as usize - 1].1.push(edge),
+            Source::One => self.one.push(edge),
+        }
+    }
+}
+
+/// Exports the libgc circuit described by `info` as Bristol Fashion circuit.
+/// All sub circuits are inlined and OR gates are expressed by XOR and AND
+/// gates. The input and output values are taken from the circuit interface
+/// if it covers all inputs / outputs in order - otherwise all inputs and all
+/// outputs form one value each.
+pub fn export(info: &MetaInfo) -> Result<Circuit, Error> {
+    let graph = match Graph::compile(info) {
+        Ok(graph) => graph,
+        Err(err) => return Err(Error::from(format!("{}", err))),
+    };
+    let inputs = graph.inputs() as u64;
+    let outputs = graph.outputs();
+
+    // output wires, which are computed by the circuit, are written directly
+    // into their output slot - all others are copied by EQW gates
+    let mut slots: HashMap<usize, u64> = HashMap::new();
+    for (k, wire) in outputs.iter().enumerate() {
+        if *wire >= graph.inputs() && !slots.contains_key(wire) {
+            slots.insert(*wire, k as u64);
+        }
+    }
+    let mut internal = inputs;
+    for constant in graph.constants() {
+        if !slots.contains_key(&constant.wire) {
+            internal += 1;
+        }
+    }
+    for op in graph.operations() {
+        if op.gate_type == GateType::OR {
+            internal += 2;
+        }
+        if !slots.contains_key(&op.out) {
+            internal += 1;
+        }
+    }
+    let wires = internal + outputs.len() as u64;
+
+    let mut map: Vec<u64> = (0..graph.wires() as u64).collect();
+    let mut next = inputs;
+    let mut gates = Vec::new();
+    let mut constants = graph.constants().iter().peekable();
+    for (i, op) in graph.operations().iter().enumerate() {
+        while let Some(constant) = constants.peek().map(|c| **c).filter(|c| c.position == i) {
+            map[constant.wire] = allocate(constant.wire, &slots, internal, &mut next);
+            gates.push(Gate::EQ(1, map[constant.wire]));
+            constants.next();
+        }
+        let (a, b) = (map[op.a], map[op.b]);
+        let out = allocate(op.out, &slots, internal, &mut next);
+        match op.gate_type {
+            GateType::XOR => gates.push(Gate::XOR(a, b, out)),
+            GateType::AND => gates.push(Gate::AND(a, b, out)),
+            GateType::NOT => gates.push(Gate::INV(a, out)),
+            GateType::OR => {
+                // a | b = (a ^ b) ^ (a & b)
+                gates.push(Gate::XOR(a, b, next));
+                gates.push(Gate::AND(a, b, next + 1));
+                gates.push(Gate::XOR(next, next + 1, out));
+                next += 2;
+            }
+        };
+        map[op.out] = out;
+    }
+    for constant in constants {
+        map[constant.wire] = allocate(constant.wire, &slots, internal, &mut next);
+        gates.push(Gate::EQ(1, map[constant.wire]));
+    }
+    for (k, wire) in outputs.iter().enumerate() {
+        if slots.get(wire) != Some(&(k as u64)) {
+            gates.push(Gate::EQW(map[*wire], internal + k as u64));
+        }
+    }
+
+    Circuit::new(wires,
+                 value_lengths(&info.interface.inputs, inputs),
+                 value_lengths(&info.interface.outputs, outputs.len() as u64),
+                 gates)
+}
+
+fn allocate(wire: usize, slots: &HashMap<usize, u64>, internal: u64, next: &mut u64) -> u64 {
+    match slots.get(&wire) {
+        Some(k) => internal + k,
+        None => {
+            *next += 1;
+            *next - 1
+        }
+    }
+}
+
+fn value_lengths(variables: &[Variable], bits: u64) -> Vec<u64> {
+    let ids: Vec<u64> = variables.iter()
+        .flat_map(|var| var.ids().iter().map(|id| (*id).into()))
+        .collect();
+    if !variables.is_empty() && ids == (1..bits + 1).collect::<Vec<u64>>() {
+        variables.iter().map(|var| var.len() as u64).collect()
+    } else if bits > 0 {
+        vec![bits]
+    } else {
+        Vec::new()
+    }
+}
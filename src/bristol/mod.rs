@@ -0,0 +1,7 @@
+mod convert;
+mod error;
+mod types;
+
+pub use self::convert::{Converter, export};
+pub use self::error::Error;
+pub use self::types::{Circuit, Gate};
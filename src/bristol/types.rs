@@ -0,0 +1,329 @@
+use std::cmp;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::error::Error;
+
+macro_rules! must {
+    ($exp:expr, $line:expr, $tok:expr, $msg:expr) => {
+        try!(match $exp {
+            Ok(val) => Ok(val),
+            Err(_) => Err(Error::new($line, format!("{} {}", $tok, $msg))),
+        })
+    };
+}
+
+macro_rules! fail {
+    ($line:expr, $tok:expr, $msg:expr) => {
+        return Err(Error::new($line, format!("{} {}", $tok, $msg)))
+    };
+}
+
+/// Gate is one line of a Bristol (Fashion) circuit. The wires are listed
+/// like in the file - the input wire(s) first and the output wire last.
+///
+///   - `2 1 a b out XOR`
+///   - `2 1 a b out AND`
+///   - `1 1 a out INV`
+///   - `1 1 bit out EQ` (assigns the constant bit to out)
+///   - `1 1 a out EQW` (copies the wire a to out)
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Gate {
+    XOR(u64, u64, u64),
+    AND(u64, u64, u64),
+    INV(u64, u64),
+    EQ(u8, u64),
+    EQW(u64, u64),
+}
+
+impl Display for Gate {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Gate::XOR(a, b, out) => write!(f, "2 1 {} {} {} XOR", a, b, out),
+            Gate::AND(a, b, out) => write!(f, "2 1 {} {} {} AND", a, b, out),
+            Gate::INV(a, out) => write!(f, "1 1 {} {} INV", a, out),
+            Gate::EQ(bit, out) => write!(f, "1 1 {} {} EQ", bit, out),
+            Gate::EQW(a, out) => write!(f, "1 1 {} {} EQW", a, out),
+        }
+    }
+}
+
+impl Gate {
+    pub fn parse(expr: &str, line: u64) -> Result<Gate, Error> {
+        let tokens: Vec<&str> = expr.split_whitespace().collect();
+        if tokens.len() < 5 {
+            fail!(line, expr, "doesn't match 'inputs' 'outputs' 'wire(s)' 'gate type'");
+        }
+        let (ins, outs) = (tokens[0], tokens[1]);
+        let gate_type = tokens[tokens.len() - 1];
+        let n = match (ins, outs, gate_type) {
+            ("2", "1", "XOR") | ("2", "1", "AND") => 2,
+            ("1", "1", "INV") | ("1", "1", "EQ") | ("1", "1", "EQW") => 1,
+            (_, _, "XOR") | (_, _, "AND") | (_, _, "INV") | (_, _, "EQ") | (_, _, "EQW") => {
+                fail!(line, expr, "has an invalid number of wires")
+            }
+            _ => fail!(line, gate_type, "is an unsupported gate type"),
+        };
+        if tokens.len() != n + 4 {
+            fail!(line, expr, "has an invalid number of wires");
+        }
+
+        let mut wires = [0u64; 3];
+        for (i, token) in tokens[2..n + 3].iter().enumerate() {
+            wires[i] = must!(token.parse::<u64>(), line, token, "is not a valid wire");
+        }
+        Ok(match gate_type {
+            "XOR" => Gate::XOR(wires[0], wires[1], wires[2]),
+            "AND" => Gate::AND(wires[0], wires[1], wires[2]),
+            "INV" => Gate::INV(wires[0], wires[1]),
+            "EQW" => Gate::EQW(wires[0], wires[1]),
+            _ => {
+                if wires[0] > 1 {
+                    fail!(line, wires[0], "is not a constant - expected '0' or '1'");
+                }
+                Gate::EQ(wires[0] as u8, wires[1])
+            }
+        })
+    }
+
+    /// Returns the wires read by the gate.
+    pub fn inputs(&self) -> Vec<u64> {
+        match *self {
+            Gate::XOR(a, b, _) | Gate::AND(a, b, _) => vec![a, b],
+            Gate::INV(a, _) | Gate::EQW(a, _) => vec![a],
+            Gate::EQ(_, _) => Vec::new(),
+        }
+    }
+
+    /// Returns the wire written by the gate.
+    pub fn output(&self) -> u64 {
+        match *self {
+            Gate::XOR(_, _, out) | Gate::AND(_, _, out) => out,
+            Gate::INV(_, out) | Gate::EQ(_, out) | Gate::EQW(_, out) => out,
+        }
+    }
+}
+
+/// Circuit is a Bristol Fashion circuit. The input values occupy the first
+/// wires (`inputs` holds the bit length of every value) and the output values
+/// the last wires of the circuit. The gates are in topological order and
+/// every wire is written exactly once.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Circuit {
+    wires: u64,
+    inputs: Vec<u64>,
+    outputs: Vec<u64>,
+    gates: Vec<Gate>,
+}
+
+impl Display for Circuit {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        try!(write!(f, "{} {}\n{}", self.gates.len(), self.wires, self.inputs.len()));
+        for len in &self.inputs {
+            try!(write!(f, " {}", len));
+        }
+        try!(write!(f, "\n{}", self.outputs.len()));
+        for len in &self.outputs {
+            try!(write!(f, " {}", len));
+        }
+        try!(write!(f, "\n\n"));
+        for gate in &self.gates {
+            try!(write!(f, "{}\n", gate));
+        }
+        Ok(())
+    }
+}
+
+impl Circuit {
+    /// Returns a new circuit after checking that the gates are in
+    /// topological order and write every wire, which is no input, once.
+    pub fn new(wires: u64,
+               inputs: Vec<u64>,
+               outputs: Vec<u64>,
+               gates: Vec<Gate>)
+               -> Result<Circuit, Error> {
+        let circuit = Circuit {
+            wires: wires,
+            inputs: inputs,
+            outputs: outputs,
+            gates: gates,
+        };
+        try!(circuit.verify());
+        Ok(circuit)
+    }
+
+    /// Reads the circuit file at `path`.
+    pub fn open(path: &Path) -> Result<Circuit, Error> {
+        Circuit::parse(BufReader::new(try!(File::open(path))))
+    }
+
+    /// Parses a circuit in Bristol Fashion or in the old Bristol format
+    /// (`inputs_A inputs_B outputs` on the second line).
+    pub fn parse<R: BufRead>(reader: R) -> Result<Circuit, Error> {
+        let mut lines = Vec::new();
+        let mut line_nr: u64 = 1;
+        for line in reader.lines() {
+            let line = try!(line);
+            if !line.trim().is_empty() {
+                lines.push((line_nr, line));
+            }
+            line_nr += 1;
+        }
+        if lines.len() < 2 {
+            return Err(Error::from("missing circuit header"));
+        }
+
+        let header = try!(parse_numbers(&lines[0].1, lines[0].0));
+        if header.len() != 2 {
+            fail!(lines[0].0, lines[0].1, "doesn't match 'gates' 'wires'");
+        }
+        let (num_of_gates, wires) = (header[0], header[1]);
+
+        // Bristol Fashion lists the output values on the third line - the old
+        // format continues with the first gate
+        let is_fashion = match lines.get(2) {
+            Some(&(_, ref line)) => {
+                line.split_whitespace().last().map_or(false, |t| t.parse::<u64>().is_ok())
+            }
+            None => false,
+        };
+        let (inputs, outputs, first_gate) = if is_fashion {
+            let inputs = try!(parse_values(&lines[1].1, lines[1].0));
+            let outputs = try!(parse_values(&lines[2].1, lines[2].0));
+            (inputs, outputs, 3)
+        } else {
+            let values = try!(parse_numbers(&lines[1].1, lines[1].0));
+            if values.len() != 3 {
+                fail!(lines[1].0,
+                      lines[1].1,
+                      "doesn't match 'inputs A' 'inputs B' 'outputs'");
+            }
+            (values[..2].iter().cloned().filter(|n| *n > 0).collect(), vec![values[2]], 2)
+        };
+
+        // the header is not trusted - only the gate lines actually read are
+        // preallocated
+        let mut gates = Vec::with_capacity(lines.len() - first_gate);
+        for &(line_nr, ref line) in &lines[first_gate..] {
+            gates.push(try!(Gate::parse(line, line_nr)));
+        }
+        if gates.len() as u64 != num_of_gates {
+            return Err(Error::from(format!("expected {} gates - found {}",
+                                           num_of_gates,
+                                           gates.len())));
+        }
+        Circuit::new(wires, inputs, outputs, gates)
+    }
+
+    #[inline]
+    pub fn wires(&self) -> u64 {
+        self.wires
+    }
+
+    /// Returns the bit length of every input value.
+    #[inline]
+    pub fn inputs(&self) -> &[u64] {
+        self.inputs.as_slice()
+    }
+
+    /// Returns the bit length of every output value.
+    #[inline]
+    pub fn outputs(&self) -> &[u64] {
+        self.outputs.as_slice()
+    }
+
+    #[inline]
+    pub fn gates(&self) -> &[Gate] {
+        self.gates.as_slice()
+    }
+
+    /// Returns the number of input wires - the wires `0..input_wires()`.
+    pub fn input_wires(&self) -> u64 {
+        self.inputs.iter().sum()
+    }
+
+    /// Returns the number of output wires - the last wires of the circuit.
+    pub fn output_wires(&self) -> u64 {
+        self.outputs.iter().sum()
+    }
+
+    // The wire counts of the header are untrusted - the wires must not
+    // exceed the inputs and the wires referenced by the gates.
+    fn verify(&self) -> Result<(), Error> {
+        let inputs = try!(sum(&self.inputs, "input"));
+        let outputs = try!(sum(&self.outputs, "output"));
+        match inputs.checked_add(outputs) {
+            Some(n) if n <= self.wires => (),
+            _ => {
+                return Err(Error::from(format!("{} input and {} output wires exceed {} wires",
+                                               inputs,
+                                               outputs,
+                                               self.wires)))
+            }
+        }
+        let mut referenced = inputs;
+        for gate in &self.gates {
+            for wire in gate.inputs().into_iter().chain(Some(gate.output())) {
+                referenced = cmp::max(referenced, wire.saturating_add(1));
+            }
+        }
+        if self.wires > referenced {
+            return Err(Error::from(format!("{} wires exceed the {} wires referenced by the gates",
+                                           self.wires,
+                                           referenced)));
+        }
+        let mut assigned = vec![false; self.wires as usize];
+        for wire in 0..inputs {
+            assigned[wire as usize] = true;
+        }
+        for gate in &self.gates {
+            for wire in gate.inputs() {
+                if wire >= self.wires || !assigned[wire as usize] {
+                    return Err(Error::from(format!("gate '{}' reads unassigned wire {}",
+                                                   gate,
+                                                   wire)));
+                }
+            }
+            let out = gate.output();
+            if out >= self.wires || assigned[out as usize] {
+                return Err(Error::from(format!("gate '{}' cannot write wire {}", gate, out)));
+            }
+            assigned[out as usize] = true;
+        }
+        for wire in self.wires - outputs..self.wires {
+            if !assigned[wire as usize] {
+                return Err(Error::from(format!("output wire {} is not assigned", wire)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn sum(values: &[u64], kind: &str) -> Result<u64, Error> {
+    let mut sum: u64 = 0;
+    for n in values {
+        sum = try!(sum.checked_add(*n)
+            .ok_or_else(|| Error::from(format!("too many {} wires", kind))));
+    }
+    Ok(sum)
+}
+
+fn parse_numbers(expr: &str, line: u64) -> Result<Vec<u64>, Error> {
+    let mut numbers = Vec::new();
+    for token in expr.split_whitespace() {
+        numbers.push(must!(token.parse::<u64>(), line, token, "is not a number"));
+    }
+    Ok(numbers)
+}
+
+fn parse_values(expr: &str, line: u64) -> Result<Vec<u64>, Error> {
+    let numbers = try!(parse_numbers(expr, line));
+    if numbers.is_empty() || numbers[0] as usize != numbers.len() - 1 {
+        fail!(line, expr, "doesn't match 'number of values' 'bits'...");
+    }
+    Ok(numbers[1..].to_vec())
+}
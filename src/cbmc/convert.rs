@@ -5,9 +5,11 @@ use std::path::{Path, PathBuf};
 use std::io::{Error, ErrorKind, Result as IOResult, LineWriter, Write};
 
 use super::types::{Gate, IOPin, Constant, GateType, Pin};
+use super::super::parser::types::Interface;
 
 const CIRCUIT: &'static str = "circuit.txt";
 const META_INFO: &'static str = "meta_info.txt";
+const INTERFACE: &'static str = "interface.txt";
 const NEW_LINE: &'static [u8] = &['\n' as u8];
 
 pub struct Converter<'a> {
@@ -109,6 +111,14 @@ impl<'a> Converter<'a> {
         writer.flush()
     }
 
+    /// Writes the named input variables of both parties (`+name first len`)
+    /// and the typed output variables (`-name type id...`) of the circuit.
+    pub fn create_interface(&self, interface: &Interface) -> IOResult<()> {
+        let mut writer: LineWriter<File> = try!(self.open_file(INTERFACE));
+        try!(writer.write_fmt(format_args!("{}", interface)));
+        writer.flush()
+    }
+
     pub fn replace_not_gates(&self,
                              gates: &Vec<Gate>,
                              cons: Option<Constant>)
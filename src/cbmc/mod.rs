@@ -9,4 +9,4 @@ pub use self::convert::Converter;
 pub use self::parser::Parser;
 pub use self::types::*;
 pub use self::error::Error as ParseError;
-pub use self::sort::{sort_gates, Error as SortError};
+pub use self::sort::{sort_gates, sort_levels, levels, Error as SortError};
@@ -5,12 +5,16 @@ use std::fs::File;
 
 use super::types::*;
 use super::error::Error;
+use super::super::parser::types::Variable;
 
 const GATES: &'static str = "output.gate.txt";
 const INPUTS: &'static str = "output.inputs.txt";
 const CONSTS: &'static str = "output.constants.txt";
 const NUM_OF_GATES: &'static str = "output.numberofgates.txt";
 const NUM_OUT_BITS: &'static str = "output.noob.txt";
+const PARTY_A: &'static str = "output.inputs.partyA.txt";
+const PARTY_B: &'static str = "output.inputs.partyB.txt";
+const MAPPING: &'static str = "output.mapping.txt";
 
 pub struct Parser<'a> {
     path: &'a Path,
@@ -87,6 +91,44 @@ impl<'a> Parser<'a> {
         Ok(num_of_gates)
     }
 
+    /// Parses the named input variables of the `party` (`name first len`).
+    /// Every variable must lie within the input pins of the circuit.
+    pub fn parse_party_inputs(&self, party: Party) -> Result<Vec<Variable>, Error> {
+        let filename = match party {
+            Party::A => PARTY_A,
+            Party::B => PARTY_B,
+        };
+        let pins = try!(self.parse_inputs()).len() as u64;
+        let mut inputs = Vec::new();
+        let mut line_nr: u64 = 1;
+
+        let reader = BufReader::new(try!(File::open(self.join_path(filename).as_path())));
+        for line in reader.lines() {
+            let line = try!(line);
+            if !line.trim().is_empty() {
+                inputs.push(try!(parse_input_variable(line.as_str(), line_nr, pins)));
+            }
+            line_nr += 1;
+        }
+        Ok(inputs)
+    }
+
+    /// Parses the typed output variables of the circuit (`name type id...`).
+    pub fn parse_outputs(&self) -> Result<Vec<Variable>, Error> {
+        let mut outputs = Vec::new();
+        let mut line_nr: u64 = 1;
+
+        let reader = BufReader::new(try!(File::open(self.join_path(MAPPING).as_path())));
+        for line in reader.lines() {
+            let line = try!(line);
+            if !line.trim().is_empty() {
+                outputs.push(try!(parse_output_variable(line.as_str(), line_nr)));
+            }
+            line_nr += 1;
+        }
+        Ok(outputs)
+    }
+
     pub fn parse_constant(&self) -> Result<Option<Constant>, Error> {
         let mut reader = BufReader::new(try!(File::open(self.join_path(CONSTS).as_path())));
         let mut buf = String::default();
@@ -98,3 +140,44 @@ impl<'a> Parser<'a> {
         Ok(Some(try!(Constant::parse(buf.as_str()))))
     }
 }
+
+// e.g. `INPUT_A_x 1 32` - the `len` input pins starting at `first`
+fn parse_input_variable(expr: &str, line: u64, pins: u64) -> Result<Variable, Error> {
+    let tokens: Vec<&str> = expr.split_whitespace().collect();
+    if tokens.len() != 3 {
+        return Err(Error::new(line,
+                              format!("{} doesn't match 'name' 'first_pin' 'number_of_pins'",
+                                      expr)));
+    }
+    let first = match tokens[1].parse::<u64>() {
+        Ok(val) if val > 0 => val,
+        _ => return Err(Error::new(line, format!("{} is not a valid IO pin id", tokens[1]))),
+    };
+    let len = match tokens[2].parse::<u64>() {
+        Ok(val) => val,
+        Err(_) => return Err(Error::new(line, format!("{} is not a number", tokens[2]))),
+    };
+    match first.checked_add(len) {
+        Some(end) if end - 1 <= pins => Ok(Variable::input(tokens[0], first, len)),
+        _ => {
+            Err(Error::new(line,
+                           format!("{} exceeds the {} input pins of the circuit", expr, pins)))
+        }
+    }
+}
+
+// e.g. `OUTPUT_sum INT32 1 2 3 ... 32` - least significant bit first
+fn parse_output_variable(expr: &str, line: u64) -> Result<Variable, Error> {
+    let tokens: Vec<&str> = expr.split_whitespace().collect();
+    if tokens.len() < 2 {
+        return Err(Error::new(line, format!("{} doesn't match 'name' 'type' 'pin'...", expr)));
+    }
+    let mut ids = Vec::with_capacity(tokens.len() - 2);
+    for token in &tokens[2..] {
+        match token.parse::<u64>() {
+            Ok(id) if id > 0 => ids.push(id),
+            _ => return Err(Error::new(line, format!("{} is not a valid IO pin id", token))),
+        }
+    }
+    Ok(Variable::output(tokens[0], tokens[1], &ids))
+}
@@ -27,75 +27,64 @@ impl ErrorTrait for Error {
 }
 
 pub fn sort_gates(gates: &Vec<Gate>) -> Result<Vec<Gate>, Error> {
-    let mut nodes = new_nodes(gates);
+    let levels = try!(sort_levels(gates));
     let mut sorted: Vec<Gate> = Vec::with_capacity(gates.len());
-    let mut marked = Vec::new();
-    loop {
-        if mark_nodes(&mut marked, &mut nodes) {
-            break;
-        }
-
-        if marked.is_empty() {
-            return Err(Error::new());
-        }
-
-        sweep_nodes(&mut marked, &mut nodes, gates, &mut sorted);
+    for level in levels {
+        sorted.extend(level.into_iter().map(|i| gates[i].clone()));
     }
     // Ok(normalize(sorted))
     Ok(sorted)
 }
 
-fn mark_nodes(marked: &mut Vec<usize>, nodes: &mut Vec<i64>) -> bool {
-    let (mut n, mut cnt, mut done) = (0 as usize, 0 as usize, true);
-    marked.clear();
-    for node in nodes {
-        match *node {
-            0 => {
-                marked.push(n);
-                done = false;
-            }
-            -1 => cnt += 1,
-            _ => done = false,
-        };
-        n += 1;
-    }
-    if cnt == n {
-        done = true
-    }
-    done
+/// Groups the gates into topological levels: the gates of the first level
+/// only depend on circuit inputs and the gates of every other level only
+/// on gates of lower levels. Returns the gate indices of every level.
+pub fn sort_levels(gates: &Vec<Gate>) -> Result<Vec<Vec<usize>>, Error> {
+    levels(gates.len(), |i| {
+        (&gates[i])
+            .into_iter()
+            .filter(|wire| !wire.is_output())
+            .map(|wire| wire.destination().as_index())
+    })
 }
 
-fn sweep_nodes(marked: &mut Vec<usize>,
-               nodes: &mut Vec<i64>,
-               from: &Vec<Gate>,
-               to: &mut Vec<Gate>) {
-    for node in marked {
-        let ref gate = from[*node];
-        for wire in gate {
-            if !wire.is_output() {
-                nodes[wire.destination().as_index()] -= 1;
-            }
+/// Groups the nodes `0..n` of a dependency graph into topological levels -
+/// `successors(i)` returns the nodes which consume the result of node `i`
+/// (once per consumed operand). The nodes of every level are sorted and
+/// the level of a node is the length of the longest path leading to it.
+pub fn levels<F, I>(n: usize, successors: F) -> Result<Vec<Vec<usize>>, Error>
+    where F: Fn(usize) -> I,
+          I: Iterator<Item = usize>
+{
+    let mut pending: Vec<usize> = vec![0; n];
+    for node in 0..n {
+        for succ in successors(node) {
+            pending[succ] += 1;
         }
-        to.push(gate.clone());
-        nodes[*node] = -1;
     }
-}
-
-fn new_nodes(gates: &Vec<Gate>) -> Vec<i64> {
-    let mut nodes: Vec<i64> = Vec::with_capacity(gates.len());
-    unsafe { nodes.set_len(gates.len()) };
-
-    for gate in gates {
-        for wire in gate {
-            if !wire.is_output() {
-                (&mut nodes)[wire.destination().as_index()] += 1;
+    let mut level: Vec<usize> = (0..n).filter(|node| pending[*node] == 0).collect();
+    let (mut levels, mut sorted) = (Vec::new(), 0);
+    while !level.is_empty() {
+        let mut next = Vec::new();
+        for node in &level {
+            for succ in successors(*node) {
+                pending[succ] -= 1;
+                if pending[succ] == 0 {
+                    next.push(succ);
+                }
             }
         }
+        next.sort();
+        sorted += level.len();
+        levels.push(level);
+        level = next;
+    }
+    if sorted != n {
+        return Err(Error::new());
     }
-    nodes
+    Ok(levels)
 }
 
-
 // # See: github.com/aead/issues/5
 // pub fn normalize(gates: Vec<Gate>) -> Vec<Gate>{
 // let mut normed: Vec<Gate> = Vec::with_capacity(gates.len());
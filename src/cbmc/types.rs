@@ -197,6 +197,25 @@ impl IOPin {
     }
 }
 
+/// Party represents the two input parties of a cbmc-gc circuit.
+/// The inputs of party A are listed in `output.inputs.partyA.txt` and
+/// the inputs of party B in `output.inputs.partyB.txt`.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Party {
+    A,
+    B,
+}
+
+impl Display for Party {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Party::A => write!(f, "A"),
+            Party::B => write!(f, "B"),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Pin {
     Left,
@@ -0,0 +1,255 @@
+use std::io::{self, Read, Write, BufReader, BufWriter, ErrorKind};
+use std::sync::mpsc::{self, Sender, Receiver};
+
+use super::circuit::garbled::{Label, LABEL_SIZE};
+
+/// Channel is a reliable, ordered byte channel between two parties.
+/// Every `Read + Write` type - e.g. a TCP stream or an in-memory `Pipe` -
+/// is a channel. The provided methods encode all values in big-endian
+/// byte order. Buffered channels must be flushed before waiting for data
+/// of the other party.
+///
+pub trait Channel: Read + Write {
+    fn send_u8(&mut self, val: u8) -> io::Result<()> {
+        self.write_all(&[val])
+    }
+
+    fn recv_u8(&mut self) -> io::Result<u8> {
+        let mut buf = [0u8; 1];
+        try!(self.read_exact(&mut buf));
+        Ok(buf[0])
+    }
+
+    fn send_u64(&mut self, val: u64) -> io::Result<()> {
+        let mut buf = [0u8; 8];
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = (val >> (56 - 8 * i)) as u8;
+        }
+        self.write_all(&buf)
+    }
+
+    fn recv_u64(&mut self) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        try!(self.read_exact(&mut buf));
+        let mut val = 0;
+        for b in buf.iter() {
+            val = (val << 8) | (*b as u64);
+        }
+        Ok(val)
+    }
+
+    fn send_label(&mut self, label: Label) -> io::Result<()> {
+        self.write_all(&label.to_bytes())
+    }
+
+    fn recv_label(&mut self) -> io::Result<Label> {
+        let mut buf = [0u8; LABEL_SIZE];
+        try!(self.read_exact(&mut buf));
+        Ok(Label::from_bytes(&buf))
+    }
+
+    fn send_bytes(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.write_all(buf)
+    }
+
+    fn recv_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut buf = vec![0u8; len];
+        try!(self.read_exact(&mut buf));
+        Ok(buf)
+    }
+}
+
+impl<T: Read + Write> Channel for T {}
+
+/// Pipe is one end of an in-memory, bidirectional channel. It is intended
+/// for running several parties as threads of one process - e.g. in tests.
+/// Reading from a pipe blocks until the other end writes some data and
+/// returns EOF when the other end was dropped.
+///
+pub struct Pipe {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+/// Returns the two connected ends of a new pipe.
+pub fn pipe() -> (Pipe, Pipe) {
+    let (tx0, rx0) = mpsc::channel();
+    let (tx1, rx1) = mpsc::channel();
+    (Pipe {
+         tx: tx0,
+         rx: rx1,
+         buf: Vec::new(),
+         pos: 0,
+     },
+     Pipe {
+         tx: tx1,
+         rx: rx0,
+         buf: Vec::new(),
+         pos: 0,
+     })
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.pos == self.buf.len() {
+            match self.rx.recv() {
+                Ok(data) => {
+                    self.buf = data;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = ::std::cmp::min(buf.len(), self.buf.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        match self.tx.send(buf.to_vec()) {
+            Ok(_) => Ok(buf.len()),
+            Err(_) => Err(io::Error::new(ErrorKind::BrokenPipe, "pipe is closed")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// BufStream adds buffering to both directions of a stream - e.g. the two
+/// handles of a cloned TCP stream. Written data is only sent on flush
+/// or when the buffer is full.
+///
+pub struct BufStream<R: Read, W: Write> {
+    reader: BufReader<R>,
+    writer: BufWriter<W>,
+}
+
+impl<R: Read, W: Write> BufStream<R, W> {
+    pub fn new(reader: R, writer: W) -> BufStream<R, W> {
+        BufStream {
+            reader: BufReader::new(reader),
+            writer: BufWriter::new(writer),
+        }
+    }
+}
+
+impl<R: Read, W: Write> Read for BufStream<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl<R: Read, W: Write> Write for BufStream<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Network connects one party of a multi-party computation with all other
+/// parties - one channel per pair of parties. The parties are numbered
+/// from 0 to n - 1.
+///
+pub struct Network<C: Channel> {
+    id: usize,
+    channels: Vec<Option<C>>,
+}
+
+impl<C: Channel> Network<C> {
+    /// Returns the network of the party `id`. The channel `channels[j]`
+    /// must connect the party with party `j` and `channels[id]` must be None.
+    pub fn new(id: usize, channels: Vec<Option<C>>) -> io::Result<Network<C>> {
+        for (j, ch) in channels.iter().enumerate() {
+            if (j == id) != ch.is_none() {
+                return Err(io::Error::new(ErrorKind::InvalidInput,
+                                          format!("invalid channel for party {}", j)));
+            }
+        }
+        Ok(Network {
+            id: id,
+            channels: channels,
+        })
+    }
+
+    /// Returns the number of the own party.
+    #[inline]
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the number of parties.
+    #[inline]
+    pub fn parties(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Returns the channel to party `j`.
+    pub fn channel(&mut self, j: usize) -> Option<&mut C> {
+        match self.channels.get_mut(j) {
+            Some(ch) => ch.as_mut(),
+            None => None,
+        }
+    }
+
+    /// Sends `data` to all other parties.
+    pub fn broadcast(&mut self, data: &[u8]) -> io::Result<()> {
+        for ch in self.channels.iter_mut().filter_map(|ch| ch.as_mut()) {
+            try!(ch.send_bytes(data));
+            try!(ch.flush());
+        }
+        Ok(())
+    }
+
+    /// Receives `len` bytes from every other party. The own entry of the
+    /// result is empty.
+    pub fn gather(&mut self, len: usize) -> io::Result<Vec<Vec<u8>>> {
+        let mut data = Vec::with_capacity(self.channels.len());
+        for ch in self.channels.iter_mut() {
+            match ch.as_mut() {
+                Some(ch) => data.push(try!(ch.recv_bytes(len))),
+                None => data.push(Vec::new()),
+            }
+        }
+        Ok(data)
+    }
+
+    /// Sends `data` to all other parties and receives as many bytes from
+    /// every other party.
+    pub fn exchange(&mut self, data: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        try!(self.broadcast(data));
+        self.gather(data.len())
+    }
+}
+
+/// Returns the networks of `n` parties connected by pipes.
+pub fn network(n: usize) -> Vec<Network<Pipe>> {
+    let mut channels: Vec<Vec<Option<Pipe>>> = (0..n).map(|_| (0..n).map(|_| None).collect())
+        .collect();
+    for i in 0..n {
+        for j in i + 1..n {
+            let (a, b) = pipe();
+            channels[i][j] = Some(a);
+            channels[j][i] = Some(b);
+        }
+    }
+    channels.into_iter()
+        .enumerate()
+        .map(|(i, c)| Network::new(i, c).unwrap())
+        .collect()
+}
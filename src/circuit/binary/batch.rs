@@ -0,0 +1,211 @@
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+use super::error::ExecError;
+use super::graph::Graph;
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::GateType;
+
+/// Lanes is a machine word which holds one bit of `LANES` independent
+/// circuit instances - bit `i` belongs to instance `i`.
+pub trait Lanes: Copy + BitAnd<Output = Self> + BitOr<Output = Self> + BitXor<Output = Self> +
+                 Not<Output = Self> {
+    /// The number of instances per word.
+    const LANES: usize;
+
+    /// Returns the word with all bits set to 0.
+    fn zero() -> Self;
+
+    /// Returns the bit of instance `i`.
+    fn bit(&self, i: usize) -> u8;
+
+    /// Returns the word with the bit of instance `i` set to `bit`.
+    fn with_bit(self, i: usize, bit: u8) -> Self;
+}
+
+impl Lanes for u64 {
+    const LANES: usize = 64;
+
+    #[inline]
+    fn zero() -> u64 {
+        0
+    }
+
+    #[inline]
+    fn bit(&self, i: usize) -> u8 {
+        ((self >> i) & 1) as u8
+    }
+
+    #[inline]
+    fn with_bit(self, i: usize, bit: u8) -> u64 {
+        (self & !(1 << i)) | (((bit & 0x01) as u64) << i)
+    }
+}
+
+impl Lanes for u128 {
+    const LANES: usize = 128;
+
+    #[inline]
+    fn zero() -> u128 {
+        0
+    }
+
+    #[inline]
+    fn bit(&self, i: usize) -> u8 {
+        ((self >> i) & 1) as u8
+    }
+
+    #[inline]
+    fn with_bit(self, i: usize, bit: u8) -> u128 {
+        (self & !(1 << i)) | (((bit & 0x01) as u128) << i)
+    }
+}
+
+/// U256 is a 256 bit word made of two u128 - `0` holds the instances
+/// 0 to 127 and `1` the instances 128 to 255.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct U256(pub u128, pub u128);
+
+impl BitAnd for U256 {
+    type Output = U256;
+
+    #[inline]
+    fn bitand(self, rhs: U256) -> U256 {
+        U256(self.0 & rhs.0, self.1 & rhs.1)
+    }
+}
+
+impl BitOr for U256 {
+    type Output = U256;
+
+    #[inline]
+    fn bitor(self, rhs: U256) -> U256 {
+        U256(self.0 | rhs.0, self.1 | rhs.1)
+    }
+}
+
+impl BitXor for U256 {
+    type Output = U256;
+
+    #[inline]
+    fn bitxor(self, rhs: U256) -> U256 {
+        U256(self.0 ^ rhs.0, self.1 ^ rhs.1)
+    }
+}
+
+impl Not for U256 {
+    type Output = U256;
+
+    #[inline]
+    fn not(self) -> U256 {
+        U256(!self.0, !self.1)
+    }
+}
+
+impl Lanes for U256 {
+    const LANES: usize = 256;
+
+    #[inline]
+    fn zero() -> U256 {
+        U256(0, 0)
+    }
+
+    #[inline]
+    fn bit(&self, i: usize) -> u8 {
+        if i < 128 {
+            self.0.bit(i)
+        } else {
+            self.1.bit(i - 128)
+        }
+    }
+
+    #[inline]
+    fn with_bit(self, i: usize, bit: u8) -> U256 {
+        if i < 128 {
+            U256(self.0.with_bit(i, bit), self.1)
+        } else {
+            U256(self.0, self.1.with_bit(i - 128, bit))
+        }
+    }
+}
+
+/// A Batch evaluates a compiled circuit on many inputs at once. The inputs
+/// of `W::LANES` instances are packed (bit-sliced) into one word per wire,
+/// so that every gate is a single bitwise operation for all of them.
+///
+pub struct Batch<W: Lanes = u64> {
+    graph: Graph,
+    values: Vec<W>,
+}
+
+impl<W: Lanes> Batch<W> {
+    pub fn new(info: MetaInfo) -> Result<Batch<W>, ExecError> {
+        Ok(Batch::from_graph(try!(Graph::compile(&info))))
+    }
+
+    pub fn from_graph(graph: Graph) -> Batch<W> {
+        Batch {
+            graph: graph,
+            values: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Evaluates `W::LANES` instances at once: `inputs[id - 1]` holds the
+    /// bits of the input `id` of all instances. Returns one word per output
+    /// in the order of the output IDs.
+    pub fn evaluate_lanes(&mut self, inputs: &[W]) -> Result<Vec<W>, ExecError> {
+        let graph = &self.graph;
+        if inputs.len() != graph.inputs() {
+            return Err(ExecError::from(format!("expected {} input words - got {}",
+                                               graph.inputs(),
+                                               inputs.len())));
+        }
+        let values = &mut self.values;
+        values.clear();
+        values.resize(graph.wires(), W::zero());
+        values[..inputs.len()].copy_from_slice(inputs);
+        for constant in graph.constants() {
+            values[constant.wire] = !W::zero();
+        }
+        for op in graph.operations() {
+            let (a, b) = (values[op.a], values[op.b]);
+            values[op.out] = match op.gate_type {
+                GateType::AND => a & b,
+                GateType::XOR => a ^ b,
+                GateType::OR => a | b,
+                GateType::NOT => !a,
+            };
+        }
+        Ok(graph.outputs().iter().map(|w| values[*w]).collect())
+    }
+
+    /// Evaluates the circuit for every input assignment - `inputs[k][id - 1]`
+    /// is the value of the input `id` of instance `k` - and returns the
+    /// output bits of every instance.
+    pub fn evaluate(&mut self, inputs: &[Vec<u8>]) -> Result<Vec<Vec<u8>>, ExecError> {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for chunk in inputs.chunks(W::LANES) {
+            let mut words = vec![W::zero(); self.graph.inputs()];
+            for (i, bits) in chunk.iter().enumerate() {
+                if bits.len() != words.len() {
+                    return Err(ExecError::from(format!("expected {} input bits - got {}",
+                                                       words.len(),
+                                                       bits.len())));
+                }
+                for (word, bit) in words.iter_mut().zip(bits.iter()) {
+                    *word = word.with_bit(i, *bit);
+                }
+            }
+            let words = try!(self.evaluate_lanes(&words));
+            for i in 0..chunk.len() {
+                outputs.push(words.iter().map(|word| word.bit(i)).collect());
+            }
+        }
+        Ok(outputs)
+    }
+}
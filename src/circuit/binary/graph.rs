@@ -0,0 +1,373 @@
+use std::path::Path;
+use std::collections::HashMap;
+
+use super::error::ExecError;
+use super::super::super::cbmc;
+use super::super::super::parser;
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::*;
+
+/// An Operation computes the wire `out` from the wires `a` and `b`.
+/// The right operand `b` of a NOT gate is the same wire as `a`.
+/// The gate `id` belongs to the (sub) circuit instance `instance` -
+/// instances are numbered like the garbler numbers them.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Operation {
+    pub gate_type: GateType,
+    pub a: usize,
+    pub b: usize,
+    pub out: usize,
+    pub instance: u64,
+    pub id: u64,
+}
+
+/// A Constant is the ONE wire of one execution of a (sub) circuit. It is
+/// processed right before the operation with the index `position`.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Constant {
+    pub wire: usize,
+    pub position: usize,
+}
+
+/// A Graph is the compiled, in-memory form of a libgc circuit. All sub
+/// circuits are inlined and every wire is resolved to an index into one
+/// flat array of wire values: the inputs are the wires `0..inputs` and
+/// every constant and operation writes a new wire. The operations are
+/// stored in evaluation order.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Graph {
+    inputs: usize,
+    wires: usize,
+    operations: Vec<Operation>,
+    constants: Vec<Constant>,
+    outputs: Vec<usize>,
+}
+
+impl Graph {
+    /// Compiles the circuit described by `info` and all its sub circuits.
+    /// The circuit files are read only once.
+    pub fn compile(info: &MetaInfo) -> Result<Graph, ExecError> {
+        Graph::compile_nodes(info, try!(parser::parse_circuit(info.path.as_path())))
+    }
+
+    /// Compiles the circuit `nodes` described by `info` - e.g. a circuit
+    /// which was never written to a file. The sub circuits are read from
+    /// their files.
+    pub fn compile_nodes(info: &MetaInfo, nodes: Vec<Node>) -> Result<Graph, ExecError> {
+        let inputs = info.inputs as usize;
+        let mut builder = Builder {
+            wires: inputs,
+            operations: Vec::new(),
+            constants: Vec::new(),
+        };
+        let mut instance = 0;
+        let mut scope = try!(Scope::with_nodes(info, nodes, &mut instance));
+        for i in 0..inputs {
+            scope.input.insert(ID::Input(i as u64 + 1), i);
+        }
+        try!(scope.execute(&mut builder));
+
+        let mut outputs = Vec::with_capacity(scope.output.len());
+        for id in 1..scope.output.len() as u64 + 1 {
+            outputs.push(*try!(expect_some(scope.output.get(&ID::Output(id)),
+                                           format!("no output for id {}", id))));
+        }
+        Ok(Graph {
+            inputs: inputs,
+            wires: builder.wires,
+            operations: builder.operations,
+            constants: builder.constants,
+            outputs: outputs,
+        })
+    }
+
+    #[inline]
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    /// Returns the number of wires including inputs and constants.
+    #[inline]
+    pub fn wires(&self) -> usize {
+        self.wires
+    }
+
+    #[inline]
+    pub fn operations(&self) -> &[Operation] {
+        self.operations.as_slice()
+    }
+
+    #[inline]
+    pub fn constants(&self) -> &[Constant] {
+        self.constants.as_slice()
+    }
+
+    /// Returns the wires of the outputs in the order of the output IDs.
+    #[inline]
+    pub fn outputs(&self) -> &[usize] {
+        self.outputs.as_slice()
+    }
+
+    /// Groups the operations into topological levels - the same levels
+    /// `cbmc::sort_gates` processes. An operation only depends on operations
+    /// of lower levels, so all operations of one level can be evaluated in
+    /// parallel. Returns the operation indices of every level.
+    pub fn levels(&self) -> Result<Vec<Vec<usize>>, ExecError> {
+        let mut consumers: Vec<Vec<usize>> = vec![Vec::new(); self.wires];
+        for (i, op) in self.operations.iter().enumerate() {
+            consumers[op.a].push(i);
+            consumers[op.b].push(i);
+        }
+        let ops = &self.operations;
+        match cbmc::levels(ops.len(), |i| consumers[ops[i].out].iter().cloned()) {
+            Ok(levels) => Ok(levels),
+            Err(err) => Err(ExecError::from(format!("{}", err))),
+        }
+    }
+
+    /// Evaluates the graph on the input bits `inputs[id - 1]`. The buffer
+    /// `values` holds all wire values afterwards and can be reused by
+    /// repeated evaluations.
+    pub fn evaluate(&self, inputs: &[u8], values: &mut Vec<u8>) -> Result<Vec<u8>, ExecError> {
+        try!(self.reset(inputs, values));
+        for op in &self.operations {
+            values[op.out] = eval(op.gate_type, values[op.a], values[op.b]);
+        }
+        Ok(self.outputs.iter().map(|w| values[*w]).collect())
+    }
+
+    /// Resets `values` to the input bits and constants of the graph.
+    pub fn reset(&self, inputs: &[u8], values: &mut Vec<u8>) -> Result<(), ExecError> {
+        if inputs.len() != self.inputs {
+            return Err(ExecError::from(format!("expected {} input bits - got {}",
+                                               self.inputs,
+                                               inputs.len())));
+        }
+        values.clear();
+        values.resize(self.wires, 0);
+        for (v, bit) in values.iter_mut().zip(inputs.iter()) {
+            *v = bit & 0x01;
+        }
+        for constant in &self.constants {
+            values[constant.wire] = 1;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the result of the gate for the bits `a` and `b`.
+#[inline]
+pub fn eval(gate_type: GateType, a: u8, b: u8) -> u8 {
+    match gate_type {
+        GateType::AND => a & b,
+        GateType::XOR => a ^ b,
+        GateType::OR => a | b,
+        GateType::NOT => !a & 0x01,
+    }
+}
+
+fn expect_some<O, T>(expr: Option<O>, msg: T) -> Result<O, ExecError>
+    where ExecError: From<T>
+{
+    match expr {
+        Some(val) => Ok(val),
+        None => Err(ExecError::from(msg)),
+    }
+}
+
+struct Builder {
+    wires: usize,
+    operations: Vec<Operation>,
+    constants: Vec<Constant>,
+}
+
+impl Builder {
+    fn constant(&mut self) -> usize {
+        let wire = self.wires;
+        self.wires += 1;
+        self.constants.push(Constant {
+            wire: wire,
+            position: self.operations.len(),
+        });
+        wire
+    }
+
+    fn push(&mut self, gate_type: GateType, a: usize, b: usize, instance: u64, id: u64) -> usize {
+        let out = self.wires;
+        self.wires += 1;
+        self.operations.push(Operation {
+            gate_type: gate_type,
+            a: a,
+            b: b,
+            out: out,
+            instance: instance,
+            id: id,
+        });
+        out
+    }
+}
+
+/// A Scope is one instance of a (sub) circuit during compilation. It is
+/// processed like the circuit would be executed, but maps IDs to wires
+/// instead of values.
+struct Scope {
+    instance: u64,
+    inputs: u64,
+    one: Option<Vec<Edge>>,
+    nodes: Vec<Node>,
+    input: HashMap<ID, usize>,
+    output: HashMap<ID, usize>,
+    lookup: HashMap<(ID, Pin), usize>,
+    sub: HashMap<String, Scope>,
+}
+
+impl Scope {
+    fn new(info: &MetaInfo, instance: &mut u64) -> Result<Scope, ExecError> {
+        Scope::with_nodes(info, try!(parser::parse_circuit(info.path.as_path())), instance)
+    }
+
+    fn with_nodes(info: &MetaInfo,
+                  nodes: Vec<Node>,
+                  instance: &mut u64)
+                  -> Result<Scope, ExecError> {
+        let mut scope = Scope {
+            instance: *instance,
+            inputs: info.inputs,
+            one: info.one.as_ref().map(|node| node.edges().to_vec()),
+            nodes: nodes,
+            input: HashMap::new(),
+            output: HashMap::new(),
+            lookup: HashMap::new(),
+            sub: HashMap::new(),
+        };
+        // sub circuits are numbered in the order of their names
+        let mut keys: Vec<&String> = info.sub_circuits.keys().collect();
+        keys.sort();
+        for key in keys {
+            let inf = try!(parser::parse_meta_info(Path::new(&info.sub_circuits[key])));
+            *instance += 1;
+            scope.sub.insert(key.clone(), try!(Scope::new(&inf, instance)));
+        }
+        Ok(scope)
+    }
+
+    fn is_executable(&self) -> bool {
+        self.inputs == self.input.len() as u64
+    }
+
+    fn execute(&mut self, builder: &mut Builder) -> Result<(), ExecError> {
+        if !self.is_executable() {
+            return Err(ExecError::from("circuit is not executable"));
+        }
+        let one = self.one.take();
+        let nodes = ::std::mem::replace(&mut self.nodes, Vec::new());
+        let result = match one {
+            Some(ref edges) => {
+                let wire = builder.constant();
+                self.propagate(edges, wire, builder)
+            }
+            None => Ok(()),
+        };
+        let result = match result {
+            Ok(_) => self.process(&nodes, builder),
+            Err(err) => Err(err),
+        };
+        self.one = one;
+        self.nodes = nodes;
+        result
+    }
+
+    fn process(&mut self, nodes: &[Node], builder: &mut Builder) -> Result<(), ExecError> {
+        for node in nodes {
+            let wire = match node.id() {
+                ID::Input(_) => {
+                    for edge in node.edges() {
+                        if edge.circuit().is_none() && edge.id().is_input() {
+                            return Err(ExecError::from(format!("invalid edge: {}", edge)));
+                        }
+                    }
+                    *try!(expect_some(self.input.get(&node.id()),
+                                      format!("missing input value {}", node.id())))
+                }
+                ID::Output(_) => {
+                    match node.circuit() {
+                        Some(key) => {
+                            let sub = try!(expect_some(self.sub.get(&key),
+                                                       format!("unknown sub circuit: {}", key)));
+                            *try!(expect_some(sub.output.get(&node.id()),
+                                              format!("no output for id {}", node.id())))
+                        }
+                        None => {
+                            *try!(expect_some(self.output.get(&node.id()),
+                                              format!("no output for id {}", node.id())))
+                        }
+                    }
+                }
+                ID::Gate(_) => try!(self.process_gate(node, builder)),
+                _ => return Err(ExecError::from(format!("invalid node id: {}", node.id()))),
+            };
+            try!(self.propagate(node.edges(), wire, builder));
+        }
+        Ok(())
+    }
+
+    fn process_gate(&mut self, node: &Node, builder: &mut Builder) -> Result<usize, ExecError> {
+        if node.circuit().is_some() {
+            return Err(ExecError::from(format!("node with id: {} cannot reference sub circuit",
+                                               node.id())));
+        }
+        let gate_type = try!(expect_some(node.gate_type(),
+                                         format!("node with id: {} must have a gate type",
+                                                 node.id())));
+        let a = try!(expect_some(self.lookup.remove(&(node.id(), Pin::Left)),
+                                 format!("cannot find value for left pin of node: {}",
+                                         node.id())));
+        let b = match gate_type.operands() {
+            1 => a,
+            _ => {
+                try!(expect_some(self.lookup.remove(&(node.id(), Pin::Right)),
+                                 format!("cannot find value for right pin of node: {}",
+                                         node.id())))
+            }
+        };
+        Ok(builder.push(gate_type, a, b, self.instance, node.id().into()))
+    }
+
+    fn propagate(&mut self,
+                 edges: &[Edge],
+                 wire: usize,
+                 builder: &mut Builder)
+                 -> Result<(), ExecError> {
+        for edge in edges {
+            match edge.id() {
+                ID::Output(id) => {
+                    self.output.insert(ID::Output(id), wire);
+                }
+                ID::Gate(id) => {
+                    let pin = try!(expect_some(edge.pin(),
+                                               format!("pin is required for edge: {}", edge)));
+                    self.lookup.insert((ID::Gate(id), pin), wire);
+                }
+                ID::Input(id) => {
+                    let key = try!(expect_some(edge.circuit(),
+                                               format!("invalid edge: expected sub circuit - {}",
+                                                       edge)));
+                    let sub = try!(expect_some(self.sub.get_mut(&key),
+                                               format!("unknown sub circuit: {}", key)));
+                    sub.input.insert(ID::Input(id), wire);
+                    if sub.is_executable() {
+                        try!(sub.execute(builder));
+                        sub.input.clear();
+                    }
+                }
+                _ => {
+                    return Err(ExecError::from(format!("invalid edge: {}", edge)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::cmp;
+use std::panic;
+use std::thread;
+
+use super::error::ExecError;
+use super::graph::{self, Graph};
+use super::super::super::parser::MetaInfo;
+
+/// A Pool evaluates the topological levels of a circuit on a fixed number
+/// of worker threads. The workers of a level borrow the wire values, so
+/// they are scoped threads (`std::thread::scope`) which are spawned for
+/// every level and joined before the results are applied. A panic of a
+/// worker is passed on to the caller once all workers of the level are
+/// done - there is no shared state which could be left locked.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Pool {
+    threads: usize,
+}
+
+impl Pool {
+    /// Returns a pool of `threads` workers - at least one.
+    pub fn new(threads: usize) -> Pool {
+        Pool { threads: if threads == 0 { 1 } else { threads } }
+    }
+
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Processes the operations `levels` level by level. The workers call
+    /// `compute(values, op)` for the operations of a level and the results
+    /// are passed to `apply(values, op, result)` on the calling thread once
+    /// the level is complete. Levels with a single operation are computed
+    /// on the calling thread.
+    pub fn run<T, R, F, A>(&self,
+                           levels: &[Vec<usize>],
+                           values: &mut Vec<T>,
+                           compute: F,
+                           mut apply: A)
+        where T: Sync,
+              R: Send,
+              F: Fn(&[T], usize) -> R + Sync,
+              A: FnMut(&mut [T], usize, R)
+    {
+        for level in levels {
+            let n = cmp::min(self.threads, level.len());
+            let results: Vec<Vec<(usize, R)>> = if n <= 1 {
+                vec![level.iter().map(|op| (*op, compute(values, *op))).collect()]
+            } else {
+                let (values, compute) = (values.as_slice(), &compute);
+                thread::scope(|s| {
+                    let workers: Vec<_> = (0..n)
+                        .map(|w| {
+                            s.spawn(move || {
+                                level.iter()
+                                    .skip(w)
+                                    .step_by(n)
+                                    .map(|op| (*op, compute(values, *op)))
+                                    .collect()
+                            })
+                        })
+                        .collect();
+                    workers.into_iter()
+                        .map(|worker| match worker.join() {
+                            Ok(results) => results,
+                            Err(err) => panic::resume_unwind(err),
+                        })
+                        .collect()
+                })
+            };
+            for (op, result) in results.into_iter().flatten() {
+                apply(values, op, result);
+            }
+        }
+    }
+}
+
+/// A ParallelCircuit evaluates a compiled circuit level by level on a
+/// pool of worker threads. It pays off for circuits with wide levels of
+/// independent gates.
+///
+pub struct ParallelCircuit {
+    graph: Graph,
+    levels: Vec<Vec<usize>>,
+    pool: Pool,
+}
+
+impl ParallelCircuit {
+    pub fn new(info: MetaInfo, threads: usize) -> Result<ParallelCircuit, ExecError> {
+        ParallelCircuit::from_graph(try!(Graph::compile(&info)), threads)
+    }
+
+    pub fn from_graph(graph: Graph, threads: usize) -> Result<ParallelCircuit, ExecError> {
+        Ok(ParallelCircuit {
+            levels: try!(graph.levels()),
+            graph: graph,
+            pool: Pool::new(threads),
+        })
+    }
+
+    #[inline]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    #[inline]
+    pub fn levels(&self) -> &[Vec<usize>] {
+        self.levels.as_slice()
+    }
+
+    /// Evaluates the circuit on the input bits `inputs[id - 1]` and returns
+    /// the output bits in the order of the output IDs.
+    pub fn evaluate(&self, inputs: &[u8]) -> Result<Vec<u8>, ExecError> {
+        let mut values = Vec::new();
+        try!(self.graph.reset(inputs, &mut values));
+        let ops = self.graph.operations();
+        self.pool.run(&self.levels,
+                      &mut values,
+                      |values, i| {
+                          let op = &ops[i];
+                          graph::eval(op.gate_type, values[op.a], values[op.b])
+                      },
+                      |values, i, val| values[ops[i].out] = val);
+        Ok(self.graph.outputs().iter().map(|w| values[*w]).collect())
+    }
+}
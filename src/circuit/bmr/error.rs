@@ -0,0 +1,61 @@
+use std::io;
+use super::super::super::parser::error::ParseError;
+use super::super::super::ot::OTError;
+use super::super::gmw::GmwError;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BmrError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for BmrError {
+    fn from(s: &'a str) -> BmrError {
+        BmrError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for BmrError {
+    fn from(s: String) -> BmrError {
+        BmrError { msg: s }
+    }
+}
+
+impl error::Error for BmrError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+
+impl From<io::Error> for BmrError {
+    fn from(err: io::Error) -> BmrError {
+        BmrError::from(format!("{}", err))
+    }
+}
+
+impl From<ParseError> for BmrError {
+    fn from(err: ParseError) -> BmrError {
+        BmrError::from(format!("{}", err))
+    }
+}
+
+impl From<OTError> for BmrError {
+    fn from(err: OTError) -> BmrError {
+        BmrError::from(format!("{}", err))
+    }
+}
+
+impl From<GmwError> for BmrError {
+    fn from(err: GmwError) -> BmrError {
+        BmrError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for BmrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on BMR execution: {}", self.msg)
+    }
+}
@@ -0,0 +1,464 @@
+mod error;
+
+pub use self::error::BmrError;
+
+use std::ops::Range;
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::gmw;
+use super::walker::{Pass, Walker};
+use super::garbled::{Label, LABEL_SIZE, TweakableHash, FixedKeyAes};
+use super::super::channel::{Channel, Network};
+use super::super::ot::{Sender, Receiver, IknpSender, IknpReceiver};
+use super::super::parser;
+use super::super::parser::MetaInfo;
+use super::super::parser::types::GateType;
+use super::super::parser::types::GateType::*;
+
+/// The own share of a wire while garbling: the own 0-label of the wire and
+/// the own share of its (secret) mask bit.
+#[derive(Debug, Copy, Clone)]
+struct Share {
+    key: Label,
+    mask: u8,
+}
+
+/// An AND or OR gate which requires a garbled table.
+struct Gate {
+    gate_type: GateType,
+    a: Share,
+    b: Share,
+    c: Share,
+}
+
+/// A wire while evaluating: the public masked value and the label of
+/// every party.
+#[derive(Debug, Clone)]
+struct Wire {
+    mask: u8,
+    keys: Vec<Label>,
+}
+
+/// Garbling chooses the own labels and mask shares of all wires. XOR gates
+/// are free, AND and OR gates are collected and garbled at once.
+struct Garbling<'a, R: 'a + Rng> {
+    rng: &'a mut R,
+    one: u8,
+    gates: Vec<Gate>,
+    constants: Vec<Label>,
+}
+
+impl<'a, R: Rng> Pass for Garbling<'a, R> {
+    type Wire = Share;
+    type Error = BmrError;
+
+    fn constant(&mut self) -> Result<Share, BmrError> {
+        let key = Label::random(self.rng);
+        self.constants.push(key);
+        Ok(Share {
+            key: key,
+            mask: 0,
+        })
+    }
+
+    fn gate(&mut self,
+            gate_type: GateType,
+            _: u64,
+            _: u64,
+            a: Share,
+            b: Option<Share>)
+            -> Result<Share, BmrError> {
+        match (gate_type, b) {
+            (NOT, _) => {
+                Ok(Share {
+                    key: a.key,
+                    mask: a.mask ^ self.one,
+                })
+            }
+            (XOR, Some(b)) => {
+                Ok(Share {
+                    key: a.key ^ b.key,
+                    mask: a.mask ^ b.mask,
+                })
+            }
+            (_, Some(b)) => {
+                let c = Share {
+                    key: Label::random(self.rng),
+                    mask: self.rng.gen::<u8>() & 0x01,
+                };
+                self.gates.push(Gate {
+                    gate_type: gate_type,
+                    a: a,
+                    b: b,
+                    c: c,
+                });
+                Ok(c)
+            }
+            (_, None) => Err(BmrError::from(format!("missing right wire of {} gate", gate_type))),
+        }
+    }
+}
+
+/// Evaluation decrypts the labels of all parties gate by gate. The own
+/// label of an output wire reveals its masked value.
+struct Evaluation<'a> {
+    me: usize,
+    offset: Label,
+    hash: &'a FixedKeyAes,
+    gates: &'a [Gate],
+    tables: &'a [Label],
+    constants: ::std::vec::IntoIter<Wire>,
+    next: usize,
+}
+
+impl<'a> Pass for Evaluation<'a> {
+    type Wire = Wire;
+    type Error = BmrError;
+
+    fn constant(&mut self) -> Result<Wire, BmrError> {
+        match self.constants.next() {
+            Some(wire) => Ok(wire),
+            None => Err(BmrError::from("missing labels of constant")),
+        }
+    }
+
+    fn gate(&mut self,
+            gate_type: GateType,
+            _: u64,
+            _: u64,
+            a: Wire,
+            b: Option<Wire>)
+            -> Result<Wire, BmrError> {
+        let b = match (gate_type, b) {
+            (NOT, _) => return Ok(a),
+            (_, Some(b)) => b,
+            (_, None) => {
+                return Err(BmrError::from(format!("missing right wire of {} gate", gate_type)))
+            }
+        };
+        if gate_type == XOR {
+            return Ok(Wire {
+                mask: a.mask ^ b.mask,
+                keys: a.keys.iter().zip(b.keys.iter()).map(|(x, y)| *x ^ *y).collect(),
+            });
+        }
+
+        let (g, n) = (self.next, a.keys.len());
+        let gate = match self.gates.get(g) {
+            Some(gate) => gate,
+            None => return Err(BmrError::from(format!("missing garbled table {}", g))),
+        };
+        self.next += 1;
+        let row = (a.mask << 1) | b.mask;
+        let table = &self.tables[(4 * g + row as usize) * n..(4 * g + row as usize + 1) * n];
+        let mut keys = table.to_vec();
+        for (j, key) in keys.iter_mut().enumerate() {
+            for (x, y) in a.keys.iter().zip(b.keys.iter()) {
+                *key = *key ^ self.hash.hash2(*x, *y, tweak(g, row, j));
+            }
+        }
+        let mask = if keys[self.me] == gate.c.key {
+            0
+        } else if keys[self.me] == gate.c.key ^ self.offset {
+            1
+        } else {
+            return Err(BmrError::from(format!("invalid garbled table {}", g)));
+        };
+        Ok(Wire {
+            mask: mask,
+            keys: keys,
+        })
+    }
+}
+
+/// Returns the tweak of the label of party `j` in row `row` of table `g`.
+#[inline]
+fn tweak(g: usize, row: u8, j: usize) -> u128 {
+    ((g as u128) << 64) | ((row as u128) << 32) | j as u128
+}
+
+#[inline]
+fn select(bit: u8, key: Label) -> Label {
+    if bit & 0x01 == 1 { key } else { Label::default() }
+}
+
+fn to_bytes(labels: &[Label]) -> Vec<u8> {
+    labels.iter().flat_map(|l| l.to_bytes().to_vec()).collect()
+}
+
+fn from_bytes(buf: &[u8]) -> Vec<Label> {
+    buf.chunks(LABEL_SIZE)
+        .map(|c| {
+            let mut b = [0u8; LABEL_SIZE];
+            b.copy_from_slice(c);
+            Label::from_bytes(&b)
+        })
+        .collect()
+}
+
+/// Sends `labels` to all other parties and receives as many labels from
+/// every other party. The own entry of the result is empty.
+fn exchange_labels<C: Channel>(net: &mut Network<C>,
+                               labels: &[Label])
+                               -> Result<Vec<Vec<Label>>, BmrError> {
+    let data = try!(net.exchange(&to_bytes(labels)));
+    Ok(data.iter().map(|buf| from_bytes(buf)).collect())
+}
+
+/// Returns the owner of every input - party `j` owns the inputs `ranges[j]`.
+fn owners(ranges: &[Range<u64>], inputs: u64, parties: usize) -> Result<Vec<usize>, BmrError> {
+    if ranges.len() != parties {
+        return Err(BmrError::from(format!("expected {} input ranges - got {}",
+                                          parties,
+                                          ranges.len())));
+    }
+    let mut owners: Vec<Option<usize>> = vec![None; inputs as usize];
+    for (j, range) in ranges.iter().enumerate() {
+        if range.start < range.end && (range.start == 0 || range.end > inputs + 1) {
+            return Err(BmrError::from(format!("invalid input range {:?} of party {}", range, j)));
+        }
+        for id in range.clone() {
+            let owner = &mut owners[id as usize - 1];
+            if owner.is_some() {
+                return Err(BmrError::from(format!("input {} is assigned twice", id)));
+            }
+            *owner = Some(j);
+        }
+    }
+    let mut result = Vec::with_capacity(owners.len());
+    for (i, owner) in owners.into_iter().enumerate() {
+        match owner {
+            Some(j) => result.push(j),
+            None => return Err(BmrError::from(format!("input {} is not assigned", i + 1))),
+        }
+    }
+    Ok(result)
+}
+
+/// Returns the own shares of `chi[k] * R_j` for the offset `R_j` of every
+/// party `j`. Every pair of parties runs one oblivious transfer per bit in
+/// each direction: party j sends `(r, r XOR R_j)` and party i chooses with
+/// its share of `chi[k]`.
+fn multiply_offsets<C: Channel, R: Rng>(net: &mut Network<C>,
+                                        rng: &mut R,
+                                        offset: Label,
+                                        chi: &[u8])
+                                        -> Result<Vec<Vec<Label>>, BmrError> {
+    let me = net.id();
+    let mut shares = vec![Vec::new(); net.parties()];
+    shares[me] = chi.iter().map(|bit| select(*bit, offset)).collect();
+    if chi.is_empty() {
+        return Ok(shares);
+    }
+    for j in 0..net.parties() {
+        if j == me {
+            continue;
+        }
+        let ch = net.channel(j).unwrap();
+        // the party with the lower number sends first
+        let order = if me < j { [true, false] } else { [false, true] };
+        for sender in order.iter() {
+            if *sender {
+                let mut messages = Vec::with_capacity(chi.len());
+                for share in shares[me].iter_mut() {
+                    let r = Label::random(rng);
+                    *share = *share ^ r;
+                    messages.push((r, r ^ offset));
+                }
+                try!(IknpSender::new().send(ch, rng, &messages));
+            } else {
+                shares[j] = try!(IknpReceiver::new().receive(ch, rng, chi));
+            }
+        }
+    }
+    Ok(shares)
+}
+
+/// A Party runs the BMR protocol on a libgc circuit together with any
+/// number of other parties. All parties garble the circuit jointly - every
+/// wire has one label pair and one share of a secret mask bit per party -
+/// and afterwards every party evaluates it on its own. The garbled tables
+/// of the AND and OR gates are computed with GMW triples and oblivious
+/// transfer, XOR and NOT gates are free.
+///
+pub struct Party {
+    garbler: Walker<Share>,
+    evaluator: Walker<Wire>,
+}
+
+impl Party {
+    pub fn new(info: MetaInfo) -> Result<Party, BmrError> {
+        let inf = try!(parser::parse_meta_info(info.path.as_path()));
+        Ok(Party {
+            garbler: try!(Walker::new(info)),
+            evaluator: try!(Walker::new(inf)),
+        })
+    }
+
+    #[inline]
+    pub fn info(&self) -> &MetaInfo {
+        self.garbler.info()
+    }
+
+    /// Runs the protocol together with all other parties of the network.
+    /// Party `j` owns the inputs with IDs in `ranges[j]` and `inputs`
+    /// contains the values (input ID -> value) of the inputs of the own
+    /// party. The output bits are returned in the order of the output IDs.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   net: &mut Network<C>,
+                                   rng: &mut R,
+                                   ranges: &[Range<u64>],
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, BmrError> {
+        let (me, n) = (net.id(), net.parties());
+        let owners = try!(owners(ranges, self.info().inputs, n));
+        let own: Vec<u64> = ranges[me].clone().collect();
+        if own.len() != inputs.len() {
+            return Err(BmrError::from(format!("expected {} input bits - got {}",
+                                              own.len(),
+                                              inputs.len())));
+        }
+        let mut values = Vec::with_capacity(own.len());
+        for id in &own {
+            match inputs.get(id) {
+                Some(val) => values.push(val & 0x01),
+                None => return Err(BmrError::from(format!("missing value for input {}", id))),
+            }
+        }
+
+        let one = if me == 0 { 1 } else { 0 };
+        let offset = Label::random(rng);
+        let hash = FixedKeyAes::default();
+
+        // garble the own labels and masks of all wires
+        let mut shares = Vec::with_capacity(owners.len());
+        for owner in &owners {
+            let mask = if *owner == me { rng.gen::<u8>() & 0x01 } else { 0 };
+            shares.push(Share {
+                key: Label::random(rng),
+                mask: mask,
+            });
+        }
+        let (gates, constants, masks) = {
+            let mut pass = Garbling {
+                rng: rng,
+                one: one,
+                gates: Vec::new(),
+                constants: Vec::new(),
+            };
+            let masks = try!(self.garbler.walk(&mut pass, shares.clone()));
+            (pass.gates, pass.constants, masks)
+        };
+
+        // compute the shares of the permuted output masks of every row
+        let count = gates.len();
+        let triples = if count > 0 {
+            try!(gmw::generate_triples(net, rng, count as u64))
+        } else {
+            Vec::new()
+        };
+        let x: Vec<u8> = gates.iter().map(|g| g.a.mask).collect();
+        let y: Vec<u8> = gates.iter().map(|g| g.b.mask).collect();
+        let products = try!(gmw::multiply(net, &triples, &x, &y));
+        let mut chi = Vec::with_capacity(4 * count);
+        for (gate, p) in gates.iter().zip(products.iter()) {
+            for row in 0..4u8 {
+                let (alpha, beta) = (row >> 1, row & 0x01);
+                let mut val = p ^ (alpha & gate.b.mask) ^ (beta & gate.a.mask) ^
+                              (alpha & beta & one);
+                if gate.gate_type == OR {
+                    val ^= gate.a.mask ^ (alpha & one) ^ gate.b.mask ^ (beta & one);
+                }
+                chi.push(val ^ gate.c.mask);
+            }
+        }
+        let offsets = try!(multiply_offsets(net, rng, offset, &chi));
+
+        // garble and open the tables
+        let mut tables = Vec::with_capacity(4 * count * n);
+        for (g, gate) in gates.iter().enumerate() {
+            for row in 0..4u8 {
+                let a = gate.a.key ^ select(row >> 1, offset);
+                let b = gate.b.key ^ select(row & 0x01, offset);
+                for j in 0..n {
+                    let mut label = hash.hash2(a, b, tweak(g, row, j)) ^
+                                    offsets[j][4 * g + row as usize];
+                    if j == me {
+                        label = label ^ gate.c.key;
+                    }
+                    tables.push(label);
+                }
+            }
+        }
+        for other in try!(exchange_labels(net, &tables)) {
+            for (label, share) in tables.iter_mut().zip(other.iter()) {
+                *label = *label ^ *share;
+            }
+        }
+
+        // open the masked inputs and the matching labels
+        let masked: Vec<u8> = own.iter()
+            .zip(values.iter())
+            .map(|(id, val)| val ^ shares[*id as usize - 1].mask)
+            .collect();
+        try!(net.broadcast(&masked));
+        let mut public = vec![0u8; owners.len()];
+        for j in 0..n {
+            let ids: Vec<usize> = ranges[j].clone().map(|id| id as usize - 1).collect();
+            let bits = if j == me {
+                masked.clone()
+            } else {
+                try!(net.channel(j).unwrap().recv_bytes(ids.len()))
+            };
+            for (i, bit) in ids.iter().zip(bits.iter()) {
+                public[*i] = bit & 0x01;
+            }
+        }
+        let mut keys: Vec<Label> = shares.iter()
+            .zip(public.iter())
+            .map(|(share, bit)| share.key ^ select(*bit, offset))
+            .collect();
+        keys.extend(constants.iter().map(|key| *key ^ offset));
+        let others = try!(exchange_labels(net, &keys));
+        let wire = |k: usize, mask: u8| {
+            Wire {
+                mask: mask,
+                keys: (0..n).map(|j| if j == me { keys[k] } else { others[j][k] }).collect(),
+            }
+        };
+        let wires: Vec<Wire> = public.iter().enumerate().map(|(k, bit)| wire(k, *bit)).collect();
+        let ones: Vec<Wire> = (owners.len()..keys.len()).map(|k| wire(k, 1)).collect();
+
+        // evaluate and unmask the outputs
+        let outputs = {
+            let mut pass = Evaluation {
+                me: me,
+                offset: offset,
+                hash: &hash,
+                gates: &gates,
+                tables: &tables,
+                constants: ones.into_iter(),
+                next: 0,
+            };
+            try!(self.evaluator.walk(&mut pass, wires))
+        };
+        if outputs.len() != masks.len() {
+            return Err(BmrError::from(format!("expected {} outputs - got {}",
+                                              masks.len(),
+                                              outputs.len())));
+        }
+        let shares: Vec<u8> = masks.iter().map(|s| s.mask).collect();
+        let mut output: Vec<u8> = outputs.iter()
+            .zip(shares.iter())
+            .map(|(w, share)| w.mask ^ share)
+            .collect();
+        for other in try!(net.exchange(&shares)) {
+            for (bit, share) in output.iter_mut().zip(other.iter()) {
+                *bit ^= *share;
+            }
+        }
+        Ok(output)
+    }
+}
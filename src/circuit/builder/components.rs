@@ -0,0 +1,419 @@
+use super::{CircuitBuilder, Wire, BuildError};
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::Node;
+
+// The components are built for a low number of AND (and OR) gates - XOR and
+// NOT gates are free when the circuit is garbled. All numbers are vectors of
+// wires - least significant bit first. Both operands must have the same
+// number of bits.
+
+/// Returns `x + y + carry` and the carry out. One AND gate per bit.
+pub fn add_with_carry(b: &mut CircuitBuilder,
+                      x: &[Wire],
+                      y: &[Wire],
+                      carry: Option<Wire>)
+                      -> (Vec<Wire>, Wire) {
+    assert_eq!(x.len(), y.len());
+    let mut sum = Vec::with_capacity(x.len());
+    let mut carry = carry;
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        carry = Some(match carry {
+            // c' = c ^ ((x ^ c) & (y ^ c))
+            Some(c) => {
+                let xc = b.xor(*xi, c);
+                let yc = b.xor(*yi, c);
+                sum.push(b.xor(xc, *yi));
+                let t = b.and(xc, yc);
+                b.xor(c, t)
+            }
+            None => {
+                sum.push(b.xor(*xi, *yi));
+                b.and(*xi, *yi)
+            }
+        });
+    }
+    match carry {
+        Some(c) => (sum, c),
+        None => (sum, b.zero()),
+    }
+}
+
+/// Returns `x + y` (mod 2^n) computed by a ripple carry adder - `n - 1`
+/// AND gates, but a depth of `n`.
+pub fn add(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    add_with_carry(b, x, y, None).0
+}
+
+/// Returns `x + y` (mod 2^n) computed by a (Sklansky) carry lookahead
+/// adder - a depth of `log n` at the cost of about `n/2 log n` more AND
+/// gates than the ripple carry adder.
+pub fn add_lookahead(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    assert_eq!(x.len(), y.len());
+    let n = x.len();
+    let p: Vec<Wire> = x.iter().zip(y.iter()).map(|(xi, yi)| b.xor(*xi, *yi)).collect();
+    let mut g: Vec<Wire> = x.iter().zip(y.iter()).map(|(xi, yi)| b.and(*xi, *yi)).collect();
+    let mut q = p.clone();
+
+    // after the loop g[i] is the carry out of bit i. Generate and propagate
+    // of a group are never both 1, so their OR is a XOR
+    let mut d = 1;
+    while d < n {
+        for i in 0..n {
+            if (i / d) % 2 == 1 {
+                let j = (i / d) * d - 1;
+                let t = b.and(q[i], g[j]);
+                g[i] = b.xor(g[i], t);
+                q[i] = b.and(q[i], q[j]);
+            }
+        }
+        d *= 2;
+    }
+
+    let mut sum = Vec::with_capacity(n);
+    for i in 0..n {
+        sum.push(match i {
+            0 => p[0],
+            _ => b.xor(p[i], g[i - 1]),
+        });
+    }
+    sum
+}
+
+/// Returns `x - y` (mod 2^n) - `n - 1` AND gates.
+pub fn sub(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    let not_y: Vec<Wire> = y.iter().map(|yi| b.not(*yi)).collect();
+    let one = b.one();
+    add_with_carry(b, x, &not_y, Some(one)).0
+}
+
+/// Returns `x + bit` and the carry out - one AND gate per bit.
+pub fn increment(b: &mut CircuitBuilder, x: &[Wire], bit: Wire) -> (Vec<Wire>, Wire) {
+    let mut carry = bit;
+    let mut sum = Vec::with_capacity(x.len());
+    for xi in x {
+        sum.push(b.xor(*xi, carry));
+        carry = b.and(*xi, carry);
+    }
+    (sum, carry)
+}
+
+/// Returns 1 if `x < y` for unsigned numbers - `n` AND gates.
+pub fn less_than(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Wire {
+    assert_eq!(x.len(), y.len());
+    // x < y if x - y = x + !y + 1 does not carry out
+    let mut carry = b.one();
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        let xc = b.xor(*xi, carry);
+        let not_y = b.not(*yi);
+        let yc = b.xor(not_y, carry);
+        let t = b.and(xc, yc);
+        carry = b.xor(carry, t);
+    }
+    b.not(carry)
+}
+
+/// Returns 1 if `x < y` for two's complement numbers - `n` AND gates.
+pub fn less_than_signed(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Wire {
+    assert_eq!(x.len(), y.len());
+    if x.is_empty() {
+        return b.zero();
+    }
+    // flipping the sign bits maps the signed order onto the unsigned one
+    let (mut x, mut y) = (x.to_vec(), y.to_vec());
+    let msb = x.len() - 1;
+    x[msb] = b.not(x[msb]);
+    y[msb] = b.not(y[msb]);
+    less_than(b, &x, &y)
+}
+
+/// Returns 1 if `x == y` - `n - 1` AND gates.
+pub fn equal(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Wire {
+    assert_eq!(x.len(), y.len());
+    let mut bits: Vec<Wire> = Vec::with_capacity(x.len());
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        let t = b.xor(*xi, *yi);
+        bits.push(b.not(t));
+    }
+    and_all(b, &bits)
+}
+
+/// Returns the AND of all `bits` as balanced tree - `n - 1` AND gates.
+pub fn and_all(b: &mut CircuitBuilder, bits: &[Wire]) -> Wire {
+    match bits.len() {
+        0 => b.one(),
+        1 => bits[0],
+        n => {
+            let left = and_all(b, &bits[..n / 2]);
+            let right = and_all(b, &bits[n / 2..]);
+            b.and(left, right)
+        }
+    }
+}
+
+/// Returns the OR of all `bits` as balanced tree - `n - 1` OR gates.
+pub fn or_all(b: &mut CircuitBuilder, bits: &[Wire]) -> Wire {
+    match bits.len() {
+        0 => b.zero(),
+        1 => bits[0],
+        n => {
+            let left = or_all(b, &bits[..n / 2]);
+            let right = or_all(b, &bits[n / 2..]);
+            b.or(left, right)
+        }
+    }
+}
+
+/// Returns `x` if `s` is 0 and `y` if `s` is 1 - one AND gate per bit.
+pub fn mux(b: &mut CircuitBuilder, s: Wire, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    assert_eq!(x.len(), y.len());
+    let mut z = Vec::with_capacity(x.len());
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        // x ^ (s & (x ^ y))
+        let t = b.xor(*xi, *yi);
+        let t = b.and(s, t);
+        z.push(b.xor(*xi, t));
+    }
+    z
+}
+
+/// Returns `x * y` (mod 2^n) by shifting and adding the partial products -
+/// `n(n+1)/2` AND gates for the partial products and `(n-1)(n-2)/2` for
+/// the additions.
+pub fn mul(b: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    assert_eq!(x.len(), y.len());
+    let n = x.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut acc: Vec<Wire> = x.iter().map(|xj| b.and(*xj, y[0])).collect();
+    for i in 1..n {
+        let row: Vec<Wire> = x[..n - i].iter().map(|xj| b.and(*xj, y[i])).collect();
+        let sum = add(b, &acc[i..], &row);
+        acc.truncate(i);
+        acc.extend(sum);
+    }
+    acc
+}
+
+/// Returns `x << k` - no AND gates.
+pub fn shift_left(b: &mut CircuitBuilder, x: &[Wire], k: usize) -> Vec<Wire> {
+    let n = x.len();
+    let zero = if k > 0 && n > 0 { Some(b.zero()) } else { None };
+    (0..n).map(|i| if i < k { zero.unwrap() } else { x[i - k] }).collect()
+}
+
+/// Returns `x >> k` - arithmetic shifts fill in the sign bit. No AND
+/// gates.
+pub fn shift_right(b: &mut CircuitBuilder, x: &[Wire], k: usize, arithmetic: bool) -> Vec<Wire> {
+    let n = x.len();
+    let fill = match (k > 0 && n > 0, arithmetic) {
+        (false, _) => None,
+        (true, true) => Some(x[n - 1]),
+        (true, false) => Some(b.zero()),
+    };
+    (0..n).map(|i| if i + k < n { x[i + k] } else { fill.unwrap() }).collect()
+}
+
+/// Returns `x << amount` for the unsigned shift amount `amount` computed by
+/// a barrel shifter - one AND gate per bit and stage. The amount bits, which
+/// shift out all bits, cost one OR gate each and `n` AND gates together.
+pub fn shift_left_by(b: &mut CircuitBuilder, x: &[Wire], amount: &[Wire]) -> Vec<Wire> {
+    barrel_shift(b, x, amount, false, false)
+}
+
+/// Returns `x >> amount` for the unsigned shift amount `amount` computed by
+/// a barrel shifter - like `shift_left_by`.
+pub fn shift_right_by(b: &mut CircuitBuilder,
+                      x: &[Wire],
+                      amount: &[Wire],
+                      arithmetic: bool)
+                      -> Vec<Wire> {
+    barrel_shift(b, x, amount, true, arithmetic)
+}
+
+fn barrel_shift(b: &mut CircuitBuilder,
+                x: &[Wire],
+                amount: &[Wire],
+                right: bool,
+                arithmetic: bool)
+                -> Vec<Wire> {
+    let n = x.len();
+    let mut x = x.to_vec();
+    if n == 0 {
+        return x;
+    }
+    let sign = x[n - 1];
+    let fill = if right && arithmetic { Some(sign) } else { None };
+
+    let mut stage = 0;
+    while stage < amount.len() && (1usize << stage) < n {
+        let k = 1 << stage;
+        let s = amount[stage];
+        let mut shifted = Vec::with_capacity(n);
+        for i in 0..n {
+            let from = if right { i + k } else { i.wrapping_sub(k) };
+            let y = if from < n { Some(x[from]) } else { fill };
+            shifted.push(select(b, s, x[i], y));
+        }
+        x = shifted;
+        stage += 1;
+    }
+
+    // any higher amount bit shifts out all bits
+    if stage < amount.len() {
+        let over = or_all(b, &amount[stage..]);
+        x = x.iter().map(|xi| select(b, over, *xi, fill)).collect();
+    }
+    x
+}
+
+// returns s ? y : x - y is 0 if it is None
+fn select(b: &mut CircuitBuilder, s: Wire, x: Wire, y: Option<Wire>) -> Wire {
+    match y {
+        Some(y) => mux(b, s, &[x], &[y])[0],
+        None => {
+            let not_s = b.not(s);
+            b.and(x, not_s)
+        }
+    }
+}
+
+/// Returns the number of 1 bits of `x` - `floor(log2 n) + 1` bits. It needs
+/// `n - popcount(n)` AND gates.
+pub fn hamming_weight(b: &mut CircuitBuilder, x: &[Wire]) -> Vec<Wire> {
+    let mut weight = count(b, x);
+    let mut bits = 0;
+    while (x.len() >> bits) > 0 {
+        bits += 1;
+    }
+    weight.truncate(bits);
+    weight
+}
+
+// counts a block of 2^k - 1 bits and the rest of less than 2^k bits and
+// adds both with one more bit as carry in - that needs k AND gates, which
+// sums up to n - popcount(n) AND gates
+fn count(b: &mut CircuitBuilder, x: &[Wire]) -> Vec<Wire> {
+    let n = x.len();
+    if n <= 1 {
+        return x.to_vec();
+    }
+    let mut block = 1;
+    while block * 2 <= n {
+        block *= 2;
+    }
+    let left = count(b, &x[..block - 1]);
+    let right = count(b, &x[block..]);
+    let w = right.len();
+    let (mut sum, carry) = add_with_carry(b, &left[..w], &right, Some(x[block - 1]));
+    let (high, carry) = increment(b, &left[w..], carry);
+    sum.extend(high);
+    sum.push(carry);
+    sum
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// output `z = x + y` (mod 2^bits) built by a ripple carry adder.
+pub fn ripple_carry_adder(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    binary_operation(bits, bits, add)
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// output `z = x + y` (mod 2^bits) built by a carry lookahead adder.
+pub fn carry_lookahead_adder(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    binary_operation(bits, bits, add_lookahead)
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// output `z = x - y` (mod 2^bits).
+pub fn subtractor(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    binary_operation(bits, bits, sub)
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// output `z = x * y` (mod 2^bits).
+pub fn multiplier(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    binary_operation(bits, bits, mul)
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// outputs `lt = x < y` and `eq = x == y`.
+pub fn comparator(bits: usize, signed: bool) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    let mut b = CircuitBuilder::new();
+    let x = try!(b.input_variable("x", bits));
+    let y = try!(b.input_variable("y", bits));
+    let lt = if signed {
+        less_than_signed(&mut b, &x, &y)
+    } else {
+        less_than(&mut b, &x, &y)
+    };
+    let eq = equal(&mut b, &x, &y);
+    try!(b.output_variable("lt", "BOOL", &[lt]));
+    try!(b.output_variable("eq", "BOOL", &[eq]));
+    Ok(b.build())
+}
+
+/// Returns a circuit with the inputs `x` and `y` of `bits` bits and the
+/// output `z = x == y`.
+pub fn equality(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    binary_operation(bits, 1, |b, x, y| vec![equal(b, x, y)])
+}
+
+/// Returns a circuit with the inputs `s` (1 bit), `x` and `y` of `bits`
+/// bits and the output `z = s ? y : x`.
+pub fn multiplexer(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    let mut b = CircuitBuilder::new();
+    let s = try!(b.input_variable("s", 1));
+    let x = try!(b.input_variable("x", bits));
+    let y = try!(b.input_variable("y", bits));
+    let z = mux(&mut b, s[0], &x, &y);
+    try!(b.output_variable("z", &format!("UINT{}", bits), &z));
+    Ok(b.build())
+}
+
+/// Returns a circuit with the inputs `x` of `bits` bits and the shift
+/// amount `k` of `floor(log2 bits) + 1` bits and the output `z = x << k` or
+/// `z = x >> k`. Right shifts are arithmetic if `arithmetic` is set.
+pub fn barrel_shifter(bits: usize,
+                      right: bool,
+                      arithmetic: bool)
+                      -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    let mut amount = 0;
+    while (bits >> amount) > 0 {
+        amount += 1;
+    }
+    let mut b = CircuitBuilder::new();
+    let x = try!(b.input_variable("x", bits));
+    let k = try!(b.input_variable("k", amount));
+    let z = barrel_shift(&mut b, &x, &k, right, arithmetic);
+    try!(b.output_variable("z", &format!("UINT{}", bits), &z));
+    Ok(b.build())
+}
+
+/// Returns a circuit with the input `x` of `bits` bits and the output `z`,
+/// the number of 1 bits of `x`.
+pub fn hamming_weight_counter(bits: usize) -> Result<(MetaInfo, Vec<Node>), BuildError> {
+    let mut b = CircuitBuilder::new();
+    let x = try!(b.input_variable("x", bits));
+    let z = hamming_weight(&mut b, &x);
+    try!(b.output_variable("z", &format!("UINT{}", z.len()), &z));
+    Ok(b.build())
+}
+
+fn binary_operation<F>(bits: usize,
+                       out: usize,
+                       op: F)
+                       -> Result<(MetaInfo, Vec<Node>), BuildError>
+    where F: FnOnce(&mut CircuitBuilder, &[Wire], &[Wire]) -> Vec<Wire>
+{
+    if bits == 0 {
+        return Err(BuildError::from("a circuit needs at least 1 input bit"));
+    }
+    let mut b = CircuitBuilder::new();
+    let x = try!(b.input_variable("x", bits));
+    let y = try!(b.input_variable("y", bits));
+    let z = op(&mut b, &x, &y);
+    let var_type = if out == 1 { String::from("BOOL") } else { format!("UINT{}", out) };
+    try!(b.output_variable("z", &var_type, &z));
+    Ok(b.build())
+}
@@ -0,0 +1,47 @@
+use std::io;
+use super::super::super::parser::error::ParseError;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct BuildError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for BuildError {
+    fn from(s: &'a str) -> BuildError {
+        BuildError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for BuildError {
+    fn from(s: String) -> BuildError {
+        BuildError { msg: s }
+    }
+}
+
+impl error::Error for BuildError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+
+impl From<io::Error> for BuildError {
+    fn from(err: io::Error) -> BuildError {
+        BuildError::from(format!("{}", err))
+    }
+}
+
+impl From<ParseError> for BuildError {
+    fn from(err: ParseError) -> BuildError {
+        BuildError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on circuit construction: {}", self.msg)
+    }
+}
@@ -0,0 +1,324 @@
+mod error;
+pub mod components;
+
+pub use self::error::BuildError;
+
+use std::fs;
+use std::path::Path;
+
+use super::binary::Graph;
+use super::super::parser;
+use super::super::parser::MetaInfo;
+use super::super::parser::types::*;
+
+/// A Wire is a handle to a bit of the circuit under construction - an
+/// input, a constant, the result of a gate or an output of a sub circuit.
+/// Wires are only valid for the builder which created them.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Wire(usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Source {
+    Input(u64),
+    Gate(usize),
+    One,
+    Sub(usize, u64),
+}
+
+struct Gate {
+    gate_type: GateType,
+    a: Wire,
+    b: Wire,
+}
+
+struct Sub {
+    key: String,
+    path: String,
+    inputs: Vec<Wire>,
+}
+
+/// A CircuitBuilder constructs a libgc circuit gate by gate. The gates get
+/// their IDs in the order they are created - which is a topological order,
+/// since a gate can only use wires which exist already. NOT gates are built
+/// as XOR with the ONE constant, so that the circuit can be garbled.
+///
+/// `build` returns the meta info and the nodes of the circuit, which can
+/// be executed by `binary::Circuit::from_nodes` or written by
+/// `parser::write_libgc`. Gates which do not contribute to an output are
+/// removed.
+///
+pub struct CircuitBuilder {
+    inputs: u64,
+    wires: Vec<Source>,
+    gates: Vec<Gate>,
+    subs: Vec<Sub>,
+    order: Vec<Wire>,
+    outputs: Vec<Wire>,
+    one: Option<Wire>,
+    zero: Option<Wire>,
+    interface: Interface,
+}
+
+impl CircuitBuilder {
+    pub fn new() -> CircuitBuilder {
+        CircuitBuilder {
+            inputs: 0,
+            wires: Vec::new(),
+            gates: Vec::new(),
+            subs: Vec::new(),
+            order: Vec::new(),
+            outputs: Vec::new(),
+            one: None,
+            zero: None,
+            interface: Interface::default(),
+        }
+    }
+
+    fn wire(&mut self, source: Source) -> Wire {
+        self.wires.push(source);
+        Wire(self.wires.len() - 1)
+    }
+
+    /// Returns a new input - the inputs get the IDs 1, 2, ... in the order
+    /// they are created.
+    pub fn input(&mut self) -> Wire {
+        self.inputs += 1;
+        let id = self.inputs;
+        self.wire(Source::Input(id))
+    }
+
+    /// Returns `n` new inputs.
+    pub fn inputs(&mut self, n: usize) -> Vec<Wire> {
+        (0..n).map(|_| self.input()).collect()
+    }
+
+    /// Returns the `bits` new inputs (least significant bit first) of the
+    /// input variable `name`.
+    pub fn input_variable(&mut self, name: &str, bits: usize) -> Result<Vec<Wire>, BuildError> {
+        if self.interface.input(name).is_some() || self.interface.output(name).is_some() {
+            return Err(BuildError::from(format!("variable {} exists already", name)));
+        }
+        let first = self.inputs + 1;
+        self.interface.inputs.push(Variable::input(name, first, bits as u64));
+        Ok(self.inputs(bits))
+    }
+
+    /// Returns the ONE constant.
+    pub fn one(&mut self) -> Wire {
+        match self.one {
+            Some(wire) => wire,
+            None => {
+                let wire = self.wire(Source::One);
+                self.one = Some(wire);
+                wire
+            }
+        }
+    }
+
+    /// Returns the ZERO constant - the XOR of ONE with itself.
+    pub fn zero(&mut self) -> Wire {
+        match self.zero {
+            Some(wire) => wire,
+            None => {
+                let one = self.one();
+                let wire = self.xor(one, one);
+                self.zero = Some(wire);
+                wire
+            }
+        }
+    }
+
+    pub fn constant(&mut self, bit: bool) -> Wire {
+        if bit { self.one() } else { self.zero() }
+    }
+
+    fn gate(&mut self, gate_type: GateType, a: Wire, b: Wire) -> Wire {
+        self.gates.push(Gate {
+            gate_type: gate_type,
+            a: a,
+            b: b,
+        });
+        let wire = self.wire(Source::Gate(self.gates.len() - 1));
+        self.order.push(wire);
+        wire
+    }
+
+    pub fn and(&mut self, a: Wire, b: Wire) -> Wire {
+        self.gate(GateType::AND, a, b)
+    }
+
+    pub fn xor(&mut self, a: Wire, b: Wire) -> Wire {
+        self.gate(GateType::XOR, a, b)
+    }
+
+    pub fn or(&mut self, a: Wire, b: Wire) -> Wire {
+        self.gate(GateType::OR, a, b)
+    }
+
+    pub fn not(&mut self, a: Wire) -> Wire {
+        let one = self.one();
+        self.xor(a, one)
+    }
+
+    /// Makes `wire` the next output of the circuit and returns its ID.
+    pub fn output(&mut self, wire: Wire) -> u64 {
+        self.outputs.push(wire);
+        self.outputs.len() as u64
+    }
+
+    /// Makes the `wires` (least significant bit first) the next outputs of
+    /// the circuit and adds them as output variable `name` of the type
+    /// `var_type` - e.g. `INT32`.
+    pub fn output_variable(&mut self,
+                           name: &str,
+                           var_type: &str,
+                           wires: &[Wire])
+                           -> Result<(), BuildError> {
+        if self.interface.input(name).is_some() || self.interface.output(name).is_some() {
+            return Err(BuildError::from(format!("variable {} exists already", name)));
+        }
+        let ids: Vec<u64> = wires.iter().map(|wire| self.output(*wire)).collect();
+        self.interface.outputs.push(Variable::output(name, var_type, &ids));
+        Ok(())
+    }
+
+    /// Instantiates the libgc circuit at `path` as sub circuit with the
+    /// `inputs` (for the input IDs 1, 2, ...) and returns its outputs. Every
+    /// call creates a new instance - the path is referenced as canonical,
+    /// absolute path, so the built circuit can be written anywhere.
+    pub fn sub_circuit(&mut self, path: &Path, inputs: &[Wire]) -> Result<Vec<Wire>, BuildError> {
+        let path = try!(fs::canonicalize(path));
+        let path = path.as_path();
+        let info = try!(parser::parse_meta_info(path));
+        if info.inputs == 0 || info.inputs != inputs.len() as u64 {
+            return Err(BuildError::from(format!("sub circuit {} expects {} inputs - got {}",
+                                                path.display(),
+                                                info.inputs,
+                                                inputs.len())));
+        }
+        let outputs = match Graph::compile(&info) {
+            Ok(graph) => graph.outputs().len() as u64,
+            Err(err) => return Err(BuildError::from(format!("{}", err))),
+        };
+
+        self.subs.push(Sub {
+            key: format!("c{}", self.subs.len()),
+            path: path.to_string_lossy().into_owned(),
+            inputs: inputs.to_vec(),
+        });
+        let sub = self.subs.len() - 1;
+        let mut wires = Vec::with_capacity(outputs as usize);
+        for id in 1..outputs + 1 {
+            let wire = self.wire(Source::Sub(sub, id));
+            self.order.push(wire);
+            wires.push(wire);
+        }
+        Ok(wires)
+    }
+
+    /// Returns the meta info and the nodes of the circuit. The meta info
+    /// has no path since the circuit is not written to a file.
+    pub fn build(self) -> (MetaInfo, Vec<Node>) {
+        // only gates and sub circuits, which contribute to an output, are
+        // kept - the wires are created in topological order
+        let mut live = vec![false; self.wires.len()];
+        let mut live_subs = vec![false; self.subs.len()];
+        for wire in &self.outputs {
+            live[wire.0] = true;
+        }
+        for wire in self.order.iter().rev() {
+            if !live[wire.0] {
+                continue;
+            }
+            match self.wires[wire.0] {
+                Source::Gate(g) => {
+                    live[self.gates[g].a.0] = true;
+                    live[self.gates[g].b.0] = true;
+                }
+                Source::Sub(s, _) if !live_subs[s] => {
+                    live_subs[s] = true;
+                    for input in &self.subs[s].inputs {
+                        live[input.0] = true;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut ids = vec![0; self.gates.len()];
+        let mut gates = 0;
+        for wire in &self.order {
+            if let Source::Gate(g) = self.wires[wire.0] {
+                if live[wire.0] {
+                    gates += 1;
+                    ids[g] = gates;
+                }
+            }
+        }
+
+        let mut edges: Vec<Vec<Edge>> = vec![Vec::new(); self.wires.len()];
+        for wire in &self.order {
+            match self.wires[wire.0] {
+                Source::Gate(g) if live[wire.0] => {
+                    let gate = &self.gates[g];
+                    let id = ID::Gate(ids[g]);
+                    edges[gate.a.0].push(Edge::new(id, Some(Pin::Left), None));
+                    edges[gate.b.0].push(Edge::new(id, Some(Pin::Right), None));
+                }
+                Source::Sub(s, 1) if live_subs[s] => {
+                    let sub = &self.subs[s];
+                    for (i, input) in sub.inputs.iter().enumerate() {
+                        edges[input.0].push(Edge::new(ID::Input(i as u64 + 1),
+                                                      None,
+                                                      Some(sub.key.clone())));
+                    }
+                }
+                _ => (),
+            }
+        }
+        for (i, wire) in self.outputs.iter().enumerate() {
+            edges[wire.0].push(Edge::new(ID::Output(i as u64 + 1), None, None));
+        }
+
+        let mut info = MetaInfo::default();
+        info.inputs = self.inputs;
+        info.gates = gates;
+        info.outputs = self.outputs.len() as u64;
+        info.interface = self.interface;
+        for (s, sub) in self.subs.iter().enumerate() {
+            if live_subs[s] {
+                info.sub_circuits.insert(sub.key.clone(), sub.path.clone());
+            }
+        }
+        if let Some(one) = self.one {
+            if !edges[one.0].is_empty() {
+                let one = ::std::mem::replace(&mut edges[one.0], Vec::new());
+                info.one = Some(Node::new(ID::Const, None, None, one));
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(self.wires.len());
+        for (i, source) in self.wires.iter().enumerate() {
+            if let Source::Input(id) = *source {
+                if !edges[i].is_empty() {
+                    let edges = ::std::mem::replace(&mut edges[i], Vec::new());
+                    nodes.push(Node::new(ID::Input(id), None, None, edges));
+                }
+            }
+        }
+        for wire in &self.order {
+            if edges[wire.0].is_empty() {
+                continue;
+            }
+            let (id, gate_type, circuit) = match self.wires[wire.0] {
+                Source::Gate(g) => (ID::Gate(ids[g]), Some(self.gates[g].gate_type), None),
+                Source::Sub(s, id) => (ID::Output(id), None, Some(self.subs[s].key.clone())),
+                _ => continue,
+            };
+            let wire_edges = ::std::mem::replace(&mut edges[wire.0], Vec::new());
+            nodes.push(Node::new(id, gate_type, circuit, wire_edges));
+        }
+        (info, nodes)
+    }
+}
@@ -0,0 +1,46 @@
+use std::io;
+use super::super::super::parser::error::ParseError;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct GarbleError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for GarbleError {
+    fn from(s: &'a str) -> GarbleError {
+        GarbleError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for GarbleError {
+    fn from(s: String) -> GarbleError {
+        GarbleError { msg: s }
+    }
+}
+
+impl error::Error for GarbleError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+impl From<io::Error> for GarbleError {
+    fn from(err: io::Error) -> GarbleError {
+        GarbleError::from(format!("{}", err))
+    }
+}
+
+impl From<ParseError> for GarbleError {
+    fn from(err: ParseError) -> GarbleError {
+        GarbleError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for GarbleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on garbling: {}", self.msg)
+    }
+}
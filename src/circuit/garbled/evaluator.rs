@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use super::error::GarbleError;
+use super::label::Label;
+use super::hash::{TweakableHash, FixedKeyAes};
+use super::scheme::Scheme;
+use super::stream::{TableSource, Tables};
+use super::garbler::{tweak, select, GarbledCircuit};
+use super::super::walker::{Pass, Walker};
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::*;
+use super::super::super::parser::types::GateType::*;
+
+struct Context<'a, H: 'a + TweakableHash, S: 'a + TableSource> {
+    hash: &'a H,
+    scheme: Scheme,
+    source: &'a mut S,
+}
+
+impl<'a, H: TweakableHash, S: TableSource> Context<'a, H, S> {
+    fn next(&mut self, n: usize) -> Result<[Label; 4], GarbleError> {
+        let mut rows = [Label::default(); 4];
+        for row in rows.iter_mut().take(n) {
+            *row = try!(self.source.pull());
+        }
+        Ok(rows)
+    }
+
+    fn eval_gate(&mut self,
+                 gate_type: GateType,
+                 tweak: u128,
+                 a: Label,
+                 b: Label)
+                 -> Result<Label, GarbleError> {
+        if gate_type == XOR && self.scheme.is_free_xor() {
+            return Ok(a ^ b);
+        }
+        if self.scheme == Scheme::HalfGates {
+            let rows = try!(self.next(2));
+            let (j0, j1) = (tweak << 1, (tweak << 1) | 1);
+            let wg = self.hash.hash(a, j0) ^ select(a.point(), rows[0]);
+            let we = self.hash.hash(b, j1) ^ select(b.point(), rows[1] ^ a);
+            return Ok(wg ^ we);
+        }
+        let row = ((a.point() << 1) | b.point()) as usize;
+        if self.scheme == Scheme::RowReduction {
+            let rows = try!(self.next(3));
+            if row == 0 {
+                return Ok(self.hash.hash2(a, b, tweak));
+            }
+            return Ok(rows[row - 1] ^ self.hash.hash2(a, b, tweak));
+        }
+        let rows = try!(self.next(4));
+        Ok(rows[row] ^ self.hash.hash2(a, b, tweak))
+    }
+}
+
+impl<'a, H: TweakableHash, S: TableSource> Pass for Context<'a, H, S> {
+    type Wire = Label;
+    type Error = GarbleError;
+
+    fn constant(&mut self) -> Result<Label, GarbleError> {
+        self.source.pull()
+    }
+
+    fn gate(&mut self,
+            gate_type: GateType,
+            instance: u64,
+            id: u64,
+            a: Label,
+            b: Option<Label>)
+            -> Result<Label, GarbleError> {
+        match (gate_type, b) {
+            (NOT, _) => Ok(a),
+            (_, Some(b)) => self.eval_gate(gate_type, tweak(instance, id), a, b),
+            (_, None) => {
+                Err(GarbleError::from(format!("missing right wire of {} gate", gate_type)))
+            }
+        }
+    }
+}
+
+/// An Evaluator executes a garbled circuit. It walks the same libgc circuit
+/// as the Garbler and holds exactly one label per wire, so it never learns
+/// any intermediate value. Only the outputs are decoded into plaintext bits.
+///
+pub struct Evaluator {
+    walker: Walker<Label>,
+    output: Vec<Label>,
+}
+
+impl Evaluator {
+    pub fn new(info: MetaInfo) -> Result<Evaluator, GarbleError> {
+        Ok(Evaluator {
+            walker: try!(Walker::new(info)),
+            output: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn info(&self) -> &MetaInfo {
+        self.walker.info()
+    }
+
+    /// Evaluates the garbled circuit `gc` on the given input labels - one label
+    /// for every input of the circuit - and decodes the output labels with the
+    /// decoding information of the garbler. The output bits are returned in
+    /// the order of the output IDs.
+    pub fn evaluate(&mut self,
+                    gc: &GarbledCircuit,
+                    inputs: &HashMap<u64, Label>,
+                    decoding: &HashMap<u64, u8>)
+                    -> Result<Vec<u8>, GarbleError> {
+        self.evaluate_with(gc, inputs, decoding, &FixedKeyAes::default())
+    }
+
+    /// Evaluates the garbled circuit `gc` using the given hash function, which
+    /// must be the same as the one used by the garbler.
+    pub fn evaluate_with<H: TweakableHash>(&mut self,
+                                           gc: &GarbledCircuit,
+                                           inputs: &HashMap<u64, Label>,
+                                           decoding: &HashMap<u64, u8>,
+                                           hash: &H)
+                                           -> Result<Vec<u8>, GarbleError> {
+        try!(self.evaluate_from(gc.scheme(), &mut Tables::new(gc.tables()), inputs, hash));
+        self.decode(decoding)
+    }
+
+    /// Evaluates a garbled circuit whose tables are pulled from `source` while
+    /// the circuit is streamed from disk. Only the labels of wires which are
+    /// still to be consumed are kept in memory. The output labels can be
+    /// obtained with `get_output` or decoded with `decode`.
+    pub fn evaluate_from<S: TableSource, H: TweakableHash>(&mut self,
+                                                           scheme: Scheme,
+                                                           source: &mut S,
+                                                           inputs: &HashMap<u64, Label>,
+                                                           hash: &H)
+                                                           -> Result<(), GarbleError> {
+        let mut ctx = Context {
+            hash: hash,
+            scheme: scheme,
+            source: source,
+        };
+        self.output.clear();
+
+        let mut labels = Vec::with_capacity(inputs.len());
+        for id in 1..self.info().inputs + 1 {
+            match inputs.get(&id) {
+                Some(label) => labels.push(*label),
+                None => return Err(GarbleError::from(format!("missing input label {}", id))),
+            }
+        }
+        self.output = try!(self.walker.walk(&mut ctx, labels));
+        ctx.source.finish()
+    }
+
+    /// Decodes the output labels of the last evaluation with the decoding
+    /// information of the garbler. The output bits are returned in the order
+    /// of the output IDs.
+    pub fn decode(&self, decoding: &HashMap<u64, u8>) -> Result<Vec<u8>, GarbleError> {
+        let mut output = Vec::with_capacity(self.output.len());
+        for id in 1..self.output.len() as u64 + 1 {
+            let label = try!(self.get_output(id));
+            let bit = match decoding.get(&id) {
+                Some(bit) => *bit,
+                None => {
+                    return Err(GarbleError::from(format!("no decoding information for output {}",
+                                                         id)))
+                }
+            };
+            output.push(label.point() ^ bit);
+        }
+        Ok(output)
+    }
+
+    /// Returns the label of the output `id` computed by the last evaluation.
+    pub fn get_output(&self, id: u64) -> Result<Label, GarbleError> {
+        match self.output.get((id as usize).wrapping_sub(1)) {
+            Some(val) => Ok(*val),
+            None => Err(GarbleError::from(format!("no output for id {}", id))),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::io::{Read, Write};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::error::GarbleError;
+use super::label::{Label, LABEL_SIZE};
+use super::scheme::Scheme;
+use super::garbler::GarbledCircuit;
+use super::super::super::parser::{self, MetaInfo};
+use super::super::super::parser::types::GateType;
+
+/// The magic bytes at the beginning of every encoded garbled circuit.
+pub const MAGIC: [u8; 4] = [b'L', b'G', b'C', b'G'];
+
+/// The current version of the encoding.
+pub const VERSION: u8 = 1;
+
+/// The maximal number of labels of one chunk.
+const CHUNK_SIZE: usize = 4096;
+
+const TABLES: u8 = 1;
+const DECODING: u8 = 2;
+
+/// Header is the beginning of an encoded garbled circuit:
+///
+///   MAGIC (4 bytes) | VERSION (1) | SCHEME (1) | LABEL SIZE (1) |
+///   INPUTS (8) | OUTPUTS (8) | GATES (8)
+///
+/// The INPUTS, OUTPUTS and GATES entries are the ones of the meta info of
+/// the garbled libgc circuit. All integers are encoded big-endian.
+/// The header is followed by chunks of garbled tables in gate order:
+///
+///   1 (1 byte) | NUMBER OF LABELS (4) | LABELS
+///
+/// and a final chunk with the decoding bits (1 bit per output - packed
+/// in bytes, starting with the least significant bit):
+///
+///   2 (1 byte) | NUMBER OF OUTPUTS (8) | DECODING BITS
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Header {
+    pub scheme: Scheme,
+    pub inputs: u64,
+    pub outputs: u64,
+    pub gates: u64,
+}
+
+impl Header {
+    pub fn new(scheme: Scheme, info: &MetaInfo) -> Header {
+        Header {
+            scheme: scheme,
+            inputs: info.inputs,
+            outputs: info.outputs,
+            gates: info.gates,
+        }
+    }
+
+    /// Returns an error if the header doesn't match the meta info.
+    pub fn check(&self, info: &MetaInfo) -> Result<(), GarbleError> {
+        if self.inputs != info.inputs || self.outputs != info.outputs ||
+           self.gates != info.gates {
+            return Err(GarbleError::from(format!("garbled circuit (INPUTS = {}, OUTPUTS = {}, \
+                                                  GATES = {}) doesn't match the circuit \
+                                                  (INPUTS = {}, OUTPUTS = {}, GATES = {})",
+                                                 self.inputs,
+                                                 self.outputs,
+                                                 self.gates,
+                                                 info.inputs,
+                                                 info.outputs,
+                                                 info.gates)));
+        }
+        Ok(())
+    }
+}
+
+fn write_u64<W: Write>(w: &mut W, val: u64) -> Result<(), GarbleError> {
+    let mut buf = [0u8; 8];
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = (val >> (56 - 8 * i)) as u8;
+    }
+    try!(w.write_all(&buf));
+    Ok(())
+}
+
+fn read_u64<R: Read>(r: &mut R, bytes: usize) -> Result<u64, GarbleError> {
+    let mut buf = [0u8; 8];
+    try!(r.read_exact(&mut buf[..bytes]));
+    let mut val = 0;
+    for b in buf[..bytes].iter() {
+        val = (val << 8) | (*b as u64);
+    }
+    Ok(val)
+}
+
+/// Writer encodes a garbled circuit. The garbled tables can be written
+/// label by label - only one chunk is kept in memory.
+///
+pub struct Writer<W: Write> {
+    w: W,
+    chunk: Vec<Label>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Returns a new writer and writes the header.
+    pub fn new(mut w: W, header: &Header) -> Result<Writer<W>, GarbleError> {
+        try!(w.write_all(&MAGIC));
+        try!(w.write_all(&[VERSION, header.scheme.id(), LABEL_SIZE as u8]));
+        try!(write_u64(&mut w, header.inputs));
+        try!(write_u64(&mut w, header.outputs));
+        try!(write_u64(&mut w, header.gates));
+        Ok(Writer {
+            w: w,
+            chunk: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    pub fn write_label(&mut self, label: Label) -> Result<(), GarbleError> {
+        self.chunk.push(label);
+        if self.chunk.len() == CHUNK_SIZE {
+            try!(self.write_chunk());
+        }
+        Ok(())
+    }
+
+    pub fn write_tables(&mut self, tables: &[Label]) -> Result<(), GarbleError> {
+        for label in tables {
+            try!(self.write_label(*label));
+        }
+        Ok(())
+    }
+
+    fn write_chunk(&mut self) -> Result<(), GarbleError> {
+        if self.chunk.is_empty() {
+            return Ok(());
+        }
+        let n = self.chunk.len() as u32;
+        try!(self.w.write_all(&[TABLES]));
+        try!(self.w.write_all(&[(n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8]));
+        for label in &self.chunk {
+            try!(self.w.write_all(&label.to_bytes()));
+        }
+        self.chunk.clear();
+        Ok(())
+    }
+
+    /// Writes the remaining tables and the decoding information and
+    /// returns the underlying writer.
+    pub fn finish(mut self, decoding: &HashMap<u64, u8>) -> Result<W, GarbleError> {
+        try!(self.write_chunk());
+
+        let n = decoding.len() as u64;
+        let mut bits = vec![0u8; (decoding.len() + 7) / 8];
+        for id in 1..n + 1 {
+            match decoding.get(&id) {
+                Some(bit) => bits[(id as usize - 1) / 8] |= (bit & 1) << ((id - 1) % 8),
+                None => return Err(GarbleError::from(format!("no decoding bit for output {}", id))),
+            }
+        }
+        try!(self.w.write_all(&[DECODING]));
+        try!(write_u64(&mut self.w, n));
+        try!(self.w.write_all(&bits));
+        try!(self.w.flush());
+        Ok(self.w)
+    }
+}
+
+/// Returns the maximal number of labels of the garbled tables of `gates`
+/// gates - AND and OR gates have the largest tables.
+fn max_labels(scheme: Scheme, gates: u64) -> u64 {
+    gates.saturating_mul(scheme.rows(GateType::AND) as u64)
+}
+
+/// Returns the number of gates of the circuit and all its sub circuits.
+fn total_gates(info: &MetaInfo) -> Result<u64, GarbleError> {
+    let mut gates = info.gates;
+    for path in info.sub_circuits.values() {
+        let sub = try!(parser::parse_meta_info(PathBuf::from(path).as_path()));
+        gates = gates.saturating_add(try!(total_gates(&sub)));
+    }
+    Ok(gates)
+}
+
+/// Reader decodes a garbled circuit written by a Writer. The garbled
+/// tables can be read label by label. The number of decoding bits must
+/// match the OUTPUTS of the header and the number of labels must not exceed
+/// the tables of its GATES, which should be checked against the meta info
+/// of the circuit - see `open`.
+///
+pub struct Reader<R: Read> {
+    r: R,
+    header: Header,
+    remaining: u32,
+    labels: u64,
+    done: bool,
+}
+
+impl<R: Read> Reader<R> {
+    /// Returns a new reader and reads the header.
+    pub fn new(mut r: R) -> Result<Reader<R>, GarbleError> {
+        let mut buf = [0u8; 7];
+        try!(r.read_exact(&mut buf));
+        if buf[..4] != MAGIC {
+            return Err(GarbleError::from("invalid garbled circuit: unknown format"));
+        }
+        if buf[4] != VERSION {
+            return Err(GarbleError::from(format!("unsupported garbled circuit version: {}",
+                                                 buf[4])));
+        }
+        let scheme = match Scheme::from_id(buf[5]) {
+            Some(scheme) => scheme,
+            None => return Err(GarbleError::from(format!("unknown garbling scheme: {}", buf[5]))),
+        };
+        if buf[6] as usize != LABEL_SIZE {
+            return Err(GarbleError::from(format!("unsupported label size: {}", buf[6])));
+        }
+        let inputs = try!(read_u64(&mut r, 8));
+        let outputs = try!(read_u64(&mut r, 8));
+        let gates = try!(read_u64(&mut r, 8));
+        Ok(Reader {
+            r: r,
+            header: Header {
+                scheme: scheme,
+                inputs: inputs,
+                outputs: outputs,
+                gates: gates,
+            },
+            remaining: 0,
+            labels: max_labels(scheme, gates),
+            done: false,
+        })
+    }
+
+    /// Returns a new reader and checks the header against the meta info
+    /// of the circuit. The tables of the sub circuits of the circuit are
+    /// allowed in addition to the ones of its GATES.
+    pub fn open(r: R, info: &MetaInfo) -> Result<Reader<R>, GarbleError> {
+        let mut reader = try!(Reader::new(r));
+        try!(reader.header.check(info));
+        reader.labels = max_labels(reader.header.scheme, try!(total_gates(info)));
+        Ok(reader)
+    }
+
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the next label of the garbled tables or None if all tables
+    /// were read.
+    pub fn read_label(&mut self) -> Result<Option<Label>, GarbleError> {
+        while self.remaining == 0 {
+            if self.done {
+                return Ok(None);
+            }
+            let mut tag = [0u8; 1];
+            try!(self.r.read_exact(&mut tag));
+            match tag[0] {
+                TABLES => {
+                    let n = try!(read_u64(&mut self.r, 4));
+                    if n > self.labels {
+                        return Err(GarbleError::from(format!("garbled circuit contains more \
+                                                              tables than its {} gates",
+                                                             self.header.gates)));
+                    }
+                    self.labels -= n;
+                    self.remaining = n as u32;
+                }
+                DECODING => self.done = true,
+                _ => return Err(GarbleError::from(format!("invalid chunk type: {}", tag[0]))),
+            }
+        }
+        let mut buf = [0u8; LABEL_SIZE];
+        try!(self.r.read_exact(&mut buf));
+        self.remaining -= 1;
+        Ok(Some(Label::from_bytes(&buf)))
+    }
+
+    /// Reads the decoding information. All tables must be read before.
+    pub fn read_decoding(&mut self) -> Result<HashMap<u64, u8>, GarbleError> {
+        if !self.done {
+            return Err(GarbleError::from("garbled circuit contains unread tables"));
+        }
+        // OUTPUTS of circuits converted by cbmc-gc is the number of outputs
+        // plus one
+        let n = try!(read_u64(&mut self.r, 8));
+        if n != self.header.outputs && n.checked_add(1) != Some(self.header.outputs) {
+            return Err(GarbleError::from(format!("invalid number of decoding bits: {} \
+                                                  (OUTPUTS = {})",
+                                                 n,
+                                                 self.header.outputs)));
+        }
+        let mut bits = vec![0u8; (n as usize + 7) / 8];
+        try!(self.r.read_exact(&mut bits));
+
+        let mut decoding = HashMap::with_capacity(n as usize);
+        for id in 1..n + 1 {
+            decoding.insert(id, (bits[(id as usize - 1) / 8] >> ((id - 1) % 8)) & 1);
+        }
+        Ok(decoding)
+    }
+
+    /// Reads the whole garbled circuit and the decoding information.
+    pub fn read_circuit(&mut self) -> Result<(GarbledCircuit, HashMap<u64, u8>), GarbleError> {
+        let mut tables = Vec::new();
+        while let Some(label) = try!(self.read_label()) {
+            tables.push(label);
+        }
+        let decoding = try!(self.read_decoding());
+        Ok((GarbledCircuit::new(self.header.scheme, tables), decoding))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.r
+    }
+}
+
+/// Writes the garbled circuit and the decoding information to `w`.
+pub fn write_circuit<W: Write>(w: W,
+                               info: &MetaInfo,
+                               gc: &GarbledCircuit,
+                               decoding: &HashMap<u64, u8>)
+                               -> Result<W, GarbleError> {
+    let mut writer = try!(Writer::new(w, &Header::new(gc.scheme(), info)));
+    try!(writer.write_tables(gc.tables()));
+    writer.finish(decoding)
+}
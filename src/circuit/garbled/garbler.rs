@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::error::GarbleError;
+use super::label::{Label, LABEL_SIZE};
+use super::hash::{TweakableHash, FixedKeyAes};
+use super::scheme::Scheme;
+use super::stream::TableSink;
+use super::super::walker::{Pass, Walker};
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::*;
+use super::super::super::parser::types::GateType::*;
+
+/// A LabelPair holds the two labels of a wire - the first one represents
+/// the value 0 and the second one the value 1.
+pub type LabelPair = (Label, Label);
+
+/// A GarbledCircuit contains the garbled tables of all gates in the order
+/// in which the gates of a libgc circuit (and its sub circuits) are processed.
+/// Besides the tables the stream contains the label for the value 1 of every
+/// ONE constant - right before the tables of the (sub) circuit the ONE belongs to.
+///
+pub struct GarbledCircuit {
+    scheme: Scheme,
+    tables: Vec<Label>,
+}
+
+impl GarbledCircuit {
+    pub fn new(scheme: Scheme, tables: Vec<Label>) -> GarbledCircuit {
+        GarbledCircuit {
+            scheme: scheme,
+            tables: tables,
+        }
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    #[inline]
+    pub fn tables(&self) -> &[Label] {
+        self.tables.as_slice()
+    }
+
+    /// Returns the number of labels (ciphertexts and constants).
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns the size of the garbled tables in bytes.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.tables.len() * LABEL_SIZE
+    }
+}
+
+/// A Garbling is the result of garbling a circuit: the garbled circuit itself
+/// and the secret label pairs of all circuit inputs and outputs.
+///
+pub struct Garbling {
+    pub circuit: GarbledCircuit,
+    pub inputs: HashMap<u64, LabelPair>,
+    pub outputs: HashMap<u64, LabelPair>,
+}
+
+impl Garbling {
+    /// Returns the label which represents `val` for the input `id`.
+    pub fn encode(&self, id: u64, val: u8) -> Option<Label> {
+        encode(&self.inputs, id, val)
+    }
+
+    /// Returns the output decoding information - the point-and-permute bit
+    /// of the 0-label of every output.
+    pub fn decoding(&self) -> HashMap<u64, u8> {
+        decoding(&self.outputs)
+    }
+}
+
+/// An Encoding holds the secret label pairs of all circuit inputs and
+/// outputs of a garbling whose tables were pushed into a TableSink.
+///
+pub struct Encoding {
+    pub inputs: HashMap<u64, LabelPair>,
+    pub outputs: HashMap<u64, LabelPair>,
+}
+
+impl Encoding {
+    /// Returns the label which represents `val` for the input `id`.
+    pub fn encode(&self, id: u64, val: u8) -> Option<Label> {
+        encode(&self.inputs, id, val)
+    }
+
+    /// Returns the output decoding information - the point-and-permute bit
+    /// of the 0-label of every output.
+    pub fn decoding(&self) -> HashMap<u64, u8> {
+        decoding(&self.outputs)
+    }
+}
+
+fn encode(inputs: &HashMap<u64, LabelPair>, id: u64, val: u8) -> Option<Label> {
+    match inputs.get(&id) {
+        Some(pair) if val == 0 => Some(pair.0),
+        Some(pair) => Some(pair.1),
+        None => None,
+    }
+}
+
+fn decoding(outputs: &HashMap<u64, LabelPair>) -> HashMap<u64, u8> {
+    let mut decoding = HashMap::with_capacity(outputs.len());
+    for (id, pair) in outputs {
+        decoding.insert(*id, pair.0.point());
+    }
+    decoding
+}
+
+/// Gates garbles single gates. The tables are pushed into a TableSink.
+pub struct Gates<'a, H: 'a + TweakableHash> {
+    pub hash: &'a H,
+    pub scheme: Scheme,
+    pub delta: Label,
+}
+
+impl<'a, H: TweakableHash> Gates<'a, H> {
+    pub fn new_pair<R: Rng>(&self, rng: &mut R) -> LabelPair {
+        let l0 = Label::random(rng);
+        if self.scheme.is_free_xor() {
+            return (l0, l0 ^ self.delta);
+        }
+        let l1 = Label::random(rng).with_point(l0.point() ^ 1);
+        (l0, l1)
+    }
+
+    /// Returns true if garbling a gate of type `gate_type` requires a new,
+    /// random label pair for the output wire.
+    pub fn needs_pair(&self, gate_type: GateType) -> bool {
+        match (self.scheme, gate_type) {
+            (_, NOT) => false,
+            (_, XOR) if self.scheme.is_free_xor() => false,
+            (Scheme::HalfGates, _) | (Scheme::RowReduction, _) => false,
+            _ => true,
+        }
+    }
+
+    /// Garbles the binary gate with the input labels `a` and `b`. The pair
+    /// `c` is the output label pair if `needs_pair(gate_type)` is true and
+    /// ignored otherwise.
+    pub fn garble<S: TableSink>(&self,
+                                gate_type: GateType,
+                                tweak: u128,
+                                a: LabelPair,
+                                b: LabelPair,
+                                c: LabelPair,
+                                sink: &mut S)
+                                -> Result<LabelPair, GarbleError> {
+        if gate_type == XOR && self.scheme.is_free_xor() {
+            let c0 = a.0 ^ b.0;
+            return Ok((c0, c0 ^ self.delta));
+        }
+        match (self.scheme, gate_type) {
+            (Scheme::HalfGates, OR) => {
+                // De Morgan: a OR b = NOT (NOT a AND NOT b)
+                let c = try!(self.garble_half_gates(tweak, (a.1, a.0), (b.1, b.0), sink));
+                Ok((c.1, c.0))
+            }
+            (Scheme::HalfGates, _) => self.garble_half_gates(tweak, a, b, sink),
+            (Scheme::RowReduction, _) => self.garble_reduced_table(gate_type, tweak, a, b, sink),
+            _ => self.garble_table(gate_type, tweak, a, b, c, sink),
+        }
+    }
+
+    fn garble_table<S: TableSink>(&self,
+                                  gate_type: GateType,
+                                  tweak: u128,
+                                  a: LabelPair,
+                                  b: LabelPair,
+                                  c: LabelPair,
+                                  sink: &mut S)
+                                  -> Result<LabelPair, GarbleError> {
+        let mut rows = [Label::default(); 4];
+        for va in 0..2u8 {
+            for vb in 0..2u8 {
+                let la = if va == 0 { a.0 } else { a.1 };
+                let lb = if vb == 0 { b.0 } else { b.1 };
+                let lc = if eval(gate_type, va, vb) == 0 { c.0 } else { c.1 };
+                let row = ((la.point() << 1) | lb.point()) as usize;
+                rows[row] = self.hash.hash2(la, lb, tweak) ^ lc;
+            }
+        }
+        for row in rows.iter() {
+            try!(sink.push(*row));
+        }
+        Ok(c)
+    }
+
+    fn garble_reduced_table<S: TableSink>(&self,
+                                          gate_type: GateType,
+                                          tweak: u128,
+                                          a: LabelPair,
+                                          b: LabelPair,
+                                          sink: &mut S)
+                                          -> Result<LabelPair, GarbleError> {
+        // the output label of the first row is the hash of the input labels
+        // with point 0 - so the first ciphertext is always zero.
+        let (va, vb) = (a.0.point(), b.0.point());
+        let z = self.hash.hash2(select_pair(va, a), select_pair(vb, b), tweak);
+        let c = if eval(gate_type, va, vb) == 0 {
+            (z, z ^ self.delta)
+        } else {
+            (z ^ self.delta, z)
+        };
+        let mut rows = [Label::default(); 4];
+        for va in 0..2u8 {
+            for vb in 0..2u8 {
+                let (la, lb) = (select_pair(va, a), select_pair(vb, b));
+                let lc = select_pair(eval(gate_type, va, vb), c);
+                let row = ((la.point() << 1) | lb.point()) as usize;
+                rows[row] = self.hash.hash2(la, lb, tweak) ^ lc;
+            }
+        }
+        for row in rows[1..].iter() {
+            try!(sink.push(*row));
+        }
+        Ok(c)
+    }
+
+    fn garble_half_gates<S: TableSink>(&self,
+                                       tweak: u128,
+                                       a: LabelPair,
+                                       b: LabelPair,
+                                       sink: &mut S)
+                                       -> Result<LabelPair, GarbleError> {
+        let (j0, j1) = (tweak << 1, (tweak << 1) | 1);
+        let (pa, pb) = (a.0.point(), b.0.point());
+
+        // generator half-gate
+        let (ha0, ha1) = (self.hash.hash(a.0, j0), self.hash.hash(a.1, j0));
+        let tg = ha0 ^ ha1 ^ select(pb, self.delta);
+        let wg = ha0 ^ select(pa, tg);
+
+        // evaluator half-gate
+        let (hb0, hb1) = (self.hash.hash(b.0, j1), self.hash.hash(b.1, j1));
+        let te = hb0 ^ hb1 ^ a.0;
+        let we = hb0 ^ select(pb, te ^ a.0);
+
+        try!(sink.push(tg));
+        try!(sink.push(te));
+        let c0 = wg ^ we;
+        Ok((c0, c0 ^ self.delta))
+    }
+}
+
+struct Context<'a, R: 'a + Rng, H: 'a + TweakableHash, S: 'a + TableSink> {
+    rng: &'a mut R,
+    gates: Gates<'a, H>,
+    sink: &'a mut S,
+}
+
+impl<'a, R: Rng, H: TweakableHash, S: TableSink> Context<'a, R, H, S> {
+    fn new_pair(&mut self) -> LabelPair {
+        self.gates.new_pair(self.rng)
+    }
+
+}
+
+impl<'a, R: Rng, H: TweakableHash, S: TableSink> Pass for Context<'a, R, H, S> {
+    type Wire = LabelPair;
+    type Error = GarbleError;
+
+    fn constant(&mut self) -> Result<LabelPair, GarbleError> {
+        let pair = self.new_pair();
+        try!(self.sink.push(pair.1));
+        Ok(pair)
+    }
+
+    fn gate(&mut self,
+            gate_type: GateType,
+            instance: u64,
+            id: u64,
+            a: LabelPair,
+            b: Option<LabelPair>)
+            -> Result<LabelPair, GarbleError> {
+        let b = match (gate_type, b) {
+            (NOT, _) => return Ok((a.1, a.0)),
+            (_, Some(b)) => b,
+            (_, None) => {
+                return Err(GarbleError::from(format!("missing right wire of {} gate", gate_type)))
+            }
+        };
+        let c = if self.gates.needs_pair(gate_type) {
+            self.new_pair()
+        } else {
+            (Label::default(), Label::default())
+        };
+        self.gates.garble(gate_type, tweak(instance, id), a, b, c, self.sink)
+    }
+}
+
+// the global offset is chosen first and the input labels right after it
+fn draw_inputs<R: Rng, H: TweakableHash>(gates: &Gates<H>,
+                                         rng: &mut R,
+                                         inputs: u64)
+                                         -> HashMap<u64, LabelPair> {
+    let mut pairs = HashMap::with_capacity(inputs as usize);
+    for id in 1..inputs + 1 {
+        pairs.insert(id, gates.new_pair(rng));
+    }
+    pairs
+}
+
+/// Returns `x` if `bit` is 1 and the all-zero label otherwise.
+#[inline]
+pub fn select(bit: u8, x: Label) -> Label {
+    if bit & 0x01 == 1 { x } else { Label::default() }
+}
+
+/// Returns the label of `pair` which represents `val`.
+#[inline]
+fn select_pair(val: u8, pair: LabelPair) -> Label {
+    if val & 0x01 == 0 { pair.0 } else { pair.1 }
+}
+
+fn eval(gate_type: GateType, a: u8, b: u8) -> u8 {
+    match gate_type {
+        AND => a & b,
+        XOR => a ^ b,
+        OR => a | b,
+        NOT => !a & 0x01,
+    }
+}
+
+/// Returns the tweak of a gate - the gate ID combined with the index of
+/// the (sub) circuit instance the gate belongs to.
+pub fn tweak(instance: u64, id: u64) -> u128 {
+    ((instance as u128) << 64) | id as u128
+}
+
+/// A Garbler turns a libgc circuit into a garbled circuit using the
+/// point-and-permute technique. Every wire gets a pair of random labels
+/// and every binary gate an encrypted truth table of four rows.
+/// NOT gates are free - the labels of the input wire are just swapped.
+/// With free-XOR (the default scheme) the two labels of every wire differ
+/// by a global offset, so XOR gates are free, too. Garbled row reduction
+/// drops one of the four ciphertexts and the half-gates scheme reduces the
+/// tables of AND and OR gates to two ciphertexts.
+///
+pub struct Garbler {
+    scheme: Scheme,
+    walker: Walker<LabelPair>,
+}
+
+impl Garbler {
+    pub fn new(info: MetaInfo) -> Result<Garbler, GarbleError> {
+        Garbler::with_scheme(info, Scheme::default())
+    }
+
+    pub fn with_scheme(info: MetaInfo, scheme: Scheme) -> Result<Garbler, GarbleError> {
+        Ok(Garbler {
+            scheme: scheme,
+            walker: try!(Walker::new(info)),
+        })
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    #[inline]
+    pub fn info(&self) -> &MetaInfo {
+        self.walker.info()
+    }
+
+    /// Garbles the circuit using the fixed-key AES hash. The labels of all
+    /// inputs are chosen at random.
+    pub fn garble<R: Rng>(&mut self, rng: &mut R) -> Result<Garbling, GarbleError> {
+        self.garble_with(rng, &FixedKeyAes::default())
+    }
+
+    /// Garbles the circuit using the given hash function.
+    pub fn garble_with<R: Rng, H: TweakableHash>(&mut self,
+                                                 rng: &mut R,
+                                                 hash: &H)
+                                                 -> Result<Garbling, GarbleError> {
+        let mut tables = Vec::with_capacity(4 * self.info().gates as usize);
+        let encoding = try!(self.garble_into(rng, hash, &mut tables));
+        Ok(Garbling {
+            circuit: GarbledCircuit::new(self.scheme, tables),
+            inputs: encoding.inputs,
+            outputs: encoding.outputs,
+        })
+    }
+
+    /// Garbles the circuit using the given hash function and pushes the
+    /// garbled tables into `sink` while the circuit is streamed from disk.
+    /// Only the labels of wires which are still to be consumed are kept in
+    /// memory.
+    pub fn garble_into<R: Rng, H: TweakableHash, S: TableSink>(&mut self,
+                                                               rng: &mut R,
+                                                               hash: &H,
+                                                               sink: &mut S)
+                                                               -> Result<Encoding, GarbleError> {
+        let gates = Gates {
+            hash: hash,
+            scheme: self.scheme,
+            delta: Label::random(rng).with_point(1),
+        };
+        let inputs = draw_inputs(&gates, rng, self.info().inputs);
+        let mut ctx = Context {
+            rng: rng,
+            gates: gates,
+            sink: sink,
+        };
+        let wires = (1..inputs.len() as u64 + 1).map(|id| inputs[&id]).collect();
+        let pairs = try!(self.walker.walk(&mut ctx, wires));
+
+        let mut outputs = HashMap::with_capacity(pairs.len());
+        for (i, pair) in pairs.into_iter().enumerate() {
+            outputs.insert(i as u64 + 1, pair);
+        }
+        Ok(Encoding {
+            inputs: inputs,
+            outputs: outputs,
+        })
+    }
+
+    /// Returns the label pairs of the `inputs` circuit inputs, which
+    /// `garble_into` chooses for the randomness `rng`. They only depend on
+    /// the scheme and the number of inputs - not on the gates - so the input
+    /// labels of a seeded garbling are known before the circuit is.
+    pub fn input_labels<R: Rng>(scheme: Scheme,
+                                inputs: u64,
+                                rng: &mut R)
+                                -> HashMap<u64, LabelPair> {
+        let hash = FixedKeyAes::default();
+        let gates = Gates {
+            hash: &hash,
+            scheme: scheme,
+            delta: Label::random(rng).with_point(1),
+        };
+        draw_inputs(&gates, rng, inputs)
+    }
+
+}
@@ -0,0 +1,58 @@
+use std::default::Default;
+
+use super::label::Label;
+use super::super::super::crypto::aes::Aes128;
+
+/// The fixed AES key of the default hash - the first 16 bytes of the
+/// fractional part of pi.
+const FIXED_KEY: [u8; 16] = [0x24, 0x3f, 0x6a, 0x88, 0x85, 0xa3, 0x08, 0xd3, 0x13, 0x19, 0x8a,
+                             0x2e, 0x03, 0x70, 0x73, 0x44];
+
+/// TweakableHash is the correlation robust hash function H(label, tweak)
+/// used to encrypt and decrypt garbled tables. Garbler and evaluator must
+/// use the same hash function.
+///
+pub trait TweakableHash {
+    /// Hashes a single label under the given tweak.
+    fn hash(&self, x: Label, tweak: u128) -> Label;
+
+    /// Hashes two labels under the given tweak.
+    fn hash2(&self, a: Label, b: Label, tweak: u128) -> Label {
+        self.hash(a ^ b.double(), tweak)
+    }
+}
+
+/// FixedKeyAes implements the hash H(x, T) = π(2x ⊕ T) ⊕ 2x ⊕ T where π is
+/// AES-128 under a fixed, public key. The key schedule is computed once
+/// when the hash is created.
+///
+#[derive(Clone)]
+pub struct FixedKeyAes {
+    aes: Aes128,
+}
+
+impl FixedKeyAes {
+    pub fn new(key: &[u8; 16]) -> FixedKeyAes {
+        FixedKeyAes { aes: Aes128::new(key) }
+    }
+
+    /// Applies the fixed-key permutation to a label.
+    #[inline]
+    pub fn permute(&self, x: Label) -> Label {
+        Label::from_bytes(&self.aes.encrypt_block(&x.to_bytes()))
+    }
+}
+
+impl Default for FixedKeyAes {
+    fn default() -> FixedKeyAes {
+        FixedKeyAes::new(&FIXED_KEY)
+    }
+}
+
+impl TweakableHash for FixedKeyAes {
+    #[inline]
+    fn hash(&self, x: Label, tweak: u128) -> Label {
+        let k = x.double() ^ Label::new(tweak);
+        self.permute(k) ^ k
+    }
+}
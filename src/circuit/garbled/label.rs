@@ -0,0 +1,83 @@
+use std::fmt;
+use std::ops::BitXor;
+
+use rand::Rng;
+
+/// The size of a wire label in bytes.
+pub const LABEL_SIZE: usize = 16;
+
+/// A Label is the (secret) key which represents one of the two
+/// possible values of a wire within a garbled circuit.
+/// The least significant bit of a label is its point-and-permute bit.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct Label(u128);
+
+impl Label {
+    pub fn new(val: u128) -> Label {
+        Label(val)
+    }
+
+    pub fn random<R: Rng>(rng: &mut R) -> Label {
+        let hi = rng.next_u64() as u128;
+        let lo = rng.next_u64() as u128;
+        Label((hi << 64) | lo)
+    }
+
+    pub fn from_bytes(buf: &[u8; LABEL_SIZE]) -> Label {
+        let mut val: u128 = 0;
+        for b in buf.iter() {
+            val = (val << 8) | (*b as u128);
+        }
+        Label(val)
+    }
+
+    pub fn to_bytes(&self) -> [u8; LABEL_SIZE] {
+        let mut buf = [0u8; LABEL_SIZE];
+        let mut val = self.0;
+        for b in buf.iter_mut().rev() {
+            *b = val as u8;
+            val >>= 8;
+        }
+        buf
+    }
+
+    #[inline]
+    pub fn value(&self) -> u128 {
+        self.0
+    }
+
+    /// Returns the point-and-permute bit of the label.
+    #[inline]
+    pub fn point(&self) -> u8 {
+        (self.0 & 0x01) as u8
+    }
+
+    /// Returns the label multiplied by 2 in GF(2^128).
+    #[inline]
+    pub fn double(&self) -> Label {
+        let carry = (self.0 >> 127) as u8;
+        Label((self.0 << 1) ^ (0x87 * carry as u128))
+    }
+
+    /// Returns the label with the point-and-permute bit set to `bit`.
+    #[inline]
+    pub fn with_point(&self, bit: u8) -> Label {
+        Label((self.0 & !1) | (bit & 0x01) as u128)
+    }
+}
+
+impl BitXor for Label {
+    type Output = Label;
+
+    #[inline]
+    fn bitxor(self, rhs: Label) -> Label {
+        Label(self.0 ^ rhs.0)
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
@@ -0,0 +1,20 @@
+mod error;
+mod label;
+mod hash;
+mod scheme;
+mod garbler;
+mod evaluator;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod stream;
+pub mod format;
+
+pub use self::error::GarbleError;
+pub use self::label::{Label, LABEL_SIZE};
+pub use self::hash::{TweakableHash, FixedKeyAes};
+pub use self::scheme::Scheme;
+pub use self::garbler::{Garbler, Garbling, Encoding, GarbledCircuit, LabelPair};
+pub use self::stream::{TableSink, TableSource, Tables};
+pub use self::evaluator::Evaluator;
+#[cfg(feature = "parallel")]
+pub use self::parallel::ParallelGarbler;
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::error::GarbleError;
+use super::label::Label;
+use super::hash::{TweakableHash, FixedKeyAes};
+use super::scheme::Scheme;
+use super::garbler::{self, Gates, Garbling, GarbledCircuit, LabelPair};
+use super::super::binary::{Graph, Pool};
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::GateType;
+
+/// A ParallelGarbler garbles a compiled circuit level by level on a pool
+/// of worker threads. All gates of one level are garbled in parallel. The
+/// garbled circuit is the same stream of tables and constants a Garbler
+/// produces, so it can be evaluated by an Evaluator.
+///
+pub struct ParallelGarbler {
+    graph: Graph,
+    levels: Vec<Vec<usize>>,
+    scheme: Scheme,
+    pool: Pool,
+}
+
+impl ParallelGarbler {
+    pub fn new(info: MetaInfo, threads: usize) -> Result<ParallelGarbler, GarbleError> {
+        ParallelGarbler::with_scheme(info, Scheme::default(), threads)
+    }
+
+    pub fn with_scheme(info: MetaInfo,
+                       scheme: Scheme,
+                       threads: usize)
+                       -> Result<ParallelGarbler, GarbleError> {
+        let graph = match Graph::compile(&info) {
+            Ok(graph) => graph,
+            Err(err) => return Err(GarbleError::from(format!("{}", err))),
+        };
+        let levels = match graph.levels() {
+            Ok(levels) => levels,
+            Err(err) => return Err(GarbleError::from(format!("{}", err))),
+        };
+        Ok(ParallelGarbler {
+            graph: graph,
+            levels: levels,
+            scheme: scheme,
+            pool: Pool::new(threads),
+        })
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    #[inline]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Garbles the circuit using the fixed-key AES hash. The labels of all
+    /// inputs are chosen at random.
+    pub fn garble<R: Rng>(&self, rng: &mut R) -> Result<Garbling, GarbleError> {
+        self.garble_with(rng, &FixedKeyAes::default())
+    }
+
+    /// Garbles the circuit using the given hash function. All random labels
+    /// are chosen up front, so that the workers only compute the tables.
+    pub fn garble_with<R: Rng, H: TweakableHash + Sync>(&self,
+                                                        rng: &mut R,
+                                                        hash: &H)
+                                                        -> Result<Garbling, GarbleError> {
+        let gates = Gates {
+            hash: hash,
+            scheme: self.scheme,
+            delta: Label::random(rng).with_point(1),
+        };
+        let (graph, ops) = (&self.graph, self.graph.operations());
+        let none = (Label::default(), Label::default());
+
+        let mut values: Vec<LabelPair> = vec![none; graph.wires()];
+        let mut inputs = HashMap::with_capacity(graph.inputs());
+        for (i, val) in values.iter_mut().take(graph.inputs()).enumerate() {
+            *val = gates.new_pair(rng);
+            inputs.insert(i as u64 + 1, *val);
+        }
+        for constant in graph.constants() {
+            values[constant.wire] = gates.new_pair(rng);
+        }
+        let pairs: Vec<LabelPair> = ops.iter()
+            .map(|op| if gates.needs_pair(op.gate_type) {
+                gates.new_pair(rng)
+            } else {
+                none
+            })
+            .collect();
+
+        let mut tables: Vec<Vec<Label>> = vec![Vec::new(); ops.len()];
+        let mut error = None;
+        {
+            let compute = |values: &[LabelPair], i: usize| {
+                let op = &ops[i];
+                let a = values[op.a];
+                if op.gate_type == GateType::NOT {
+                    return Ok(((a.1, a.0), Vec::new()));
+                }
+                let mut rows = Vec::with_capacity(4);
+                let tweak = garbler::tweak(op.instance, op.id);
+                let b = values[op.b];
+                let c = try!(gates.garble(op.gate_type, tweak, a, b, pairs[i], &mut rows));
+                Ok((c, rows))
+            };
+            let apply = |values: &mut [LabelPair],
+                         i: usize,
+                         result: Result<(LabelPair, Vec<Label>), GarbleError>| {
+                match result {
+                    Ok((pair, rows)) => {
+                        values[ops[i].out] = pair;
+                        tables[i] = rows;
+                    }
+                    Err(err) => error = Some(err),
+                }
+            };
+            self.pool.run(&self.levels, &mut values, compute, apply);
+        }
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        // the label of every ONE precedes the tables of its circuit
+        let mut stream = Vec::with_capacity(4 * ops.len());
+        let mut constants = graph.constants().iter().peekable();
+        for (i, rows) in tables.into_iter().enumerate() {
+            while constants.peek().map_or(false, |c| c.position == i) {
+                stream.push(values[constants.next().unwrap().wire].1);
+            }
+            stream.extend(rows);
+        }
+        for constant in constants {
+            stream.push(values[constant.wire].1);
+        }
+
+        let mut outputs = HashMap::with_capacity(graph.outputs().len());
+        for (i, wire) in graph.outputs().iter().enumerate() {
+            outputs.insert(i as u64 + 1, values[*wire]);
+        }
+        Ok(Garbling {
+            circuit: GarbledCircuit::new(self.scheme, stream),
+            inputs: inputs,
+            outputs: outputs,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::default::Default;
+
+use super::super::super::parser::types::GateType;
+
+/// Scheme represents the different garbling techniques:
+///
+///   - PointAndPermute: every binary gate is garbled into a table of four rows.
+///   - FreeXOR: point-and-permute with a global offset between the labels of
+///              a wire - XOR gates don't need any table.
+///   - RowReduction: free-XOR and garbled row reduction (GRR3) - the first
+///                   row of every table is implied by the input labels, so
+///                   only three ciphertexts are left for AND and OR gates.
+///   - HalfGates: free-XOR and two ciphertexts for every AND gate. OR gates
+///                are garbled as AND gates with negated inputs and output.
+///
+/// NOT gates are free in every scheme.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Scheme {
+    PointAndPermute,
+    FreeXOR,
+    RowReduction,
+    HalfGates,
+}
+
+impl Scheme {
+    /// Returns the number of ciphertexts of a garbled gate of the given type.
+    pub fn rows(&self, gate_type: GateType) -> usize {
+        match (*self, gate_type) {
+            (_, GateType::NOT) => 0,
+            (Scheme::PointAndPermute, _) => 4,
+            (_, GateType::XOR) => 0,
+            (Scheme::FreeXOR, _) => 4,
+            (Scheme::RowReduction, _) => 3,
+            (Scheme::HalfGates, _) => 2,
+        }
+    }
+
+    /// Returns the numeric identifier of the scheme used by encodings.
+    pub fn id(&self) -> u8 {
+        match *self {
+            Scheme::PointAndPermute => 1,
+            Scheme::FreeXOR => 2,
+            Scheme::HalfGates => 3,
+            Scheme::RowReduction => 4,
+        }
+    }
+
+    /// Returns the scheme with the given numeric identifier.
+    pub fn from_id(id: u8) -> Option<Scheme> {
+        match id {
+            1 => Some(Scheme::PointAndPermute),
+            2 => Some(Scheme::FreeXOR),
+            3 => Some(Scheme::HalfGates),
+            4 => Some(Scheme::RowReduction),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the labels of a wire differ by the global offset.
+    #[inline]
+    pub fn is_free_xor(&self) -> bool {
+        match *self {
+            Scheme::PointAndPermute => false,
+            _ => true,
+        }
+    }
+}
+
+impl Default for Scheme {
+    fn default() -> Scheme {
+        Scheme::FreeXOR
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Scheme::PointAndPermute => write!(f, "point-and-permute"),
+            Scheme::FreeXOR => write!(f, "free-XOR"),
+            Scheme::RowReduction => write!(f, "row-reduction"),
+            Scheme::HalfGates => write!(f, "half-gates"),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{Read, Write};
+
+use super::error::GarbleError;
+use super::label::Label;
+use super::format::{Reader, Writer};
+
+/// A TableSink receives the garbled tables from the Garbler - label by
+/// label in the order in which the gates are processed. A sink which
+/// writes the labels to a file or socket allows to garble circuits of
+/// any size in bounded memory.
+///
+pub trait TableSink {
+    fn push(&mut self, label: Label) -> Result<(), GarbleError>;
+}
+
+/// A TableSource provides the garbled tables to the Evaluator in the
+/// order in which they were pushed into a TableSink.
+///
+pub trait TableSource {
+    /// Returns the next label of the garbled tables.
+    fn pull(&mut self) -> Result<Label, GarbleError>;
+
+    /// Returns an error if the source contains unused labels.
+    fn finish(&mut self) -> Result<(), GarbleError>;
+}
+
+impl TableSink for Vec<Label> {
+    fn push(&mut self, label: Label) -> Result<(), GarbleError> {
+        Vec::push(self, label);
+        Ok(())
+    }
+}
+
+impl<W: Write> TableSink for Writer<W> {
+    fn push(&mut self, label: Label) -> Result<(), GarbleError> {
+        self.write_label(label)
+    }
+}
+
+/// Tables is a TableSource over garbled tables held in memory.
+pub struct Tables<'a> {
+    tables: &'a [Label],
+    pos: usize,
+}
+
+impl<'a> Tables<'a> {
+    pub fn new(tables: &'a [Label]) -> Tables<'a> {
+        Tables {
+            tables: tables,
+            pos: 0,
+        }
+    }
+}
+
+impl<'a> TableSource for Tables<'a> {
+    fn pull(&mut self) -> Result<Label, GarbleError> {
+        match self.tables.get(self.pos) {
+            Some(label) => {
+                self.pos += 1;
+                Ok(*label)
+            }
+            None => Err(GarbleError::from("garbled circuit is too short")),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), GarbleError> {
+        if self.pos != self.tables.len() {
+            return Err(GarbleError::from("garbled circuit contains unused tables"));
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> TableSource for Reader<R> {
+    fn pull(&mut self) -> Result<Label, GarbleError> {
+        match try!(self.read_label()) {
+            Some(label) => Ok(label),
+            None => Err(GarbleError::from("garbled circuit is too short")),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), GarbleError> {
+        match try!(self.read_label()) {
+            Some(_) => Err(GarbleError::from("garbled circuit contains unused tables")),
+            None => Ok(()),
+        }
+    }
+}
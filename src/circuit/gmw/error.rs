@@ -0,0 +1,54 @@
+use std::io;
+use super::super::super::parser::error::ParseError;
+use super::super::super::ot::OTError;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct GmwError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for GmwError {
+    fn from(s: &'a str) -> GmwError {
+        GmwError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for GmwError {
+    fn from(s: String) -> GmwError {
+        GmwError { msg: s }
+    }
+}
+
+impl error::Error for GmwError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+
+impl From<io::Error> for GmwError {
+    fn from(err: io::Error) -> GmwError {
+        GmwError::from(format!("{}", err))
+    }
+}
+
+impl From<ParseError> for GmwError {
+    fn from(err: ParseError) -> GmwError {
+        GmwError::from(format!("{}", err))
+    }
+}
+
+impl From<OTError> for GmwError {
+    fn from(err: OTError) -> GmwError {
+        GmwError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for GmwError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on GMW evaluation: {}", self.msg)
+    }
+}
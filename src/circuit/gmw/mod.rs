@@ -0,0 +1,222 @@
+mod error;
+mod triple;
+
+pub use self::error::GmwError;
+pub use self::triple::{Triple, count_triples, deal_triples, generate_triples};
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::binary::Graph;
+use super::super::channel::{Channel, Network};
+use super::super::parser::MetaInfo;
+use super::super::parser::types::GateType::*;
+
+/// Computes the shares of `x[i] AND y[i]` for all shared bits `x` and `y`
+/// with one round of communication - using the triple `triples[i]` for the
+/// i-th product.
+pub fn multiply<C: Channel>(net: &mut Network<C>,
+                            triples: &[Triple],
+                            x: &[u8],
+                            y: &[u8])
+                            -> Result<Vec<u8>, GmwError> {
+    if triples.len() < x.len() || x.len() != y.len() {
+        return Err(GmwError::from(format!("cannot multiply {} and {} bits with {} triples",
+                                          x.len(),
+                                          y.len(),
+                                          triples.len())));
+    }
+    let one = if net.id() == 0 { 1 } else { 0 };
+    let mut de: Vec<u8> = (0..x.len())
+        .map(|i| (((x[i] ^ triples[i].a) & 1) << 1) | ((y[i] ^ triples[i].b) & 1))
+        .collect();
+    for shares in try!(net.exchange(&de)) {
+        for (v, share) in de.iter_mut().zip(shares.iter()) {
+            *v ^= *share;
+        }
+    }
+    Ok(de.iter()
+        .zip(triples.iter())
+        .map(|(v, t)| {
+            let (d, e) = ((v >> 1) & 1, v & 1);
+            t.c ^ (d & t.b) ^ (e & t.a) ^ (d & e & one)
+        })
+        .collect())
+}
+
+/// An Evaluator runs the GMW protocol on a libgc circuit. Every wire value
+/// is XOR-shared between all parties: XOR and NOT gates are computed
+/// locally, AND and OR gates need a multiplication triple. The circuit is
+/// compiled into a Graph and evaluated level by level - the AND and OR
+/// gates of one level share one round of communication. Only the outputs
+/// are opened.
+///
+pub struct Evaluator {
+    info: MetaInfo,
+    graph: Graph,
+    levels: Vec<Vec<usize>>,
+}
+
+impl Evaluator {
+    pub fn new(info: MetaInfo) -> Result<Evaluator, GmwError> {
+        let graph = match Graph::compile(&info) {
+            Ok(graph) => graph,
+            Err(err) => return Err(GmwError::from(format!("{}", err))),
+        };
+        let levels = match graph.levels() {
+            Ok(levels) => levels,
+            Err(err) => return Err(GmwError::from(format!("{}", err))),
+        };
+        Ok(Evaluator {
+            info: info,
+            graph: graph,
+            levels: levels,
+        })
+    }
+
+    #[inline]
+    pub fn info(&self) -> &MetaInfo {
+        &self.info
+    }
+
+    /// Returns the number of multiplication triples required by `evaluate`.
+    pub fn triples(&self) -> Result<u64, GmwError> {
+        count_triples(&self.info)
+    }
+
+    /// Evaluates the circuit together with all other parties of the network.
+    /// The input `id` belongs to the party `owners[id - 1]` and `inputs`
+    /// contains the values (input ID -> value) of all inputs of the own party.
+    /// Every party needs one triple for every AND and OR gate. The output bits
+    /// are returned in the order of the output IDs.
+    pub fn evaluate<C, R, I>(&mut self,
+                             net: &mut Network<C>,
+                             rng: &mut R,
+                             triples: &mut I,
+                             owners: &[usize],
+                             inputs: &HashMap<u64, u8>)
+                             -> Result<Vec<u8>, GmwError>
+        where C: Channel,
+              R: Rng,
+              I: Iterator<Item = Triple>
+    {
+        if owners.len() as u64 != self.info.inputs {
+            return Err(GmwError::from(format!("expected {} input owners - got {}",
+                                              self.info.inputs,
+                                              owners.len())));
+        }
+        let mut values = vec![0u8; self.graph.wires()];
+        try!(self.share_inputs(net, rng, owners, inputs, &mut values));
+
+        // the share of the constant 1 - party 0 holds 1, all other parties 0
+        let one = if net.id() == 0 { 1 } else { 0 };
+        for constant in self.graph.constants() {
+            values[constant.wire] = one;
+        }
+
+        let ops = self.graph.operations();
+        for level in &self.levels {
+            let mut and = Vec::new();
+            let (mut x, mut y, mut t) = (Vec::new(), Vec::new(), Vec::new());
+            for i in level {
+                let op = &ops[*i];
+                let (a, b) = (values[op.a], values[op.b]);
+                match op.gate_type {
+                    XOR => values[op.out] = a ^ b,
+                    NOT => values[op.out] = a ^ one,
+                    AND | OR => {
+                        match triples.next() {
+                            Some(triple) => t.push(triple),
+                            None => return Err(GmwError::from("not enough multiplication triples")),
+                        }
+                        and.push(*i);
+                        x.push(a);
+                        y.push(b);
+                    }
+                }
+            }
+            if and.is_empty() {
+                continue;
+            }
+            let z = try!(multiply(net, &t, &x, &y));
+            for (k, i) in and.iter().enumerate() {
+                let op = &ops[*i];
+                values[op.out] = match op.gate_type {
+                    OR => z[k] ^ x[k] ^ y[k],
+                    _ => z[k],
+                };
+            }
+        }
+
+        let shares: Vec<u8> = self.graph.outputs().iter().map(|w| values[*w]).collect();
+        let mut output = shares.clone();
+        for other in try!(net.exchange(&shares)) {
+            for (bit, share) in output.iter_mut().zip(other.iter()) {
+                *bit ^= *share;
+            }
+        }
+        Ok(output)
+    }
+
+    /// Splits the own inputs into random shares, sends them to the other
+    /// parties and receives the shares of their inputs. The share of the
+    /// input `id` is stored in `values[id - 1]`.
+    fn share_inputs<C: Channel, R: Rng>(&self,
+                                        net: &mut Network<C>,
+                                        rng: &mut R,
+                                        owners: &[usize],
+                                        inputs: &HashMap<u64, u8>,
+                                        values: &mut [u8])
+                                        -> Result<(), GmwError> {
+        let me = net.id();
+        let mut own = Vec::new();
+        for (i, owner) in owners.iter().enumerate() {
+            if *owner >= net.parties() {
+                return Err(GmwError::from(format!("unknown owner {} of input {}", owner, i + 1)));
+            }
+            if *owner == me {
+                let id = i as u64 + 1;
+                    let val = match inputs.get(&id) {
+                    Some(val) => *val,
+                    None => return Err(GmwError::from(format!("missing input value {}", id))),
+                };
+                own.push((id, val & 0x01));
+            }
+        }
+        if own.len() != inputs.len() {
+            return Err(GmwError::from("inputs contain values of other parties"));
+        }
+
+        let mut shares: Vec<u8> = own.iter().map(|&(_, val)| val).collect();
+        for j in 0..net.parties() {
+            if j == me {
+                continue;
+            }
+            let r: Vec<u8> = own.iter().map(|_| rng.gen::<u8>() & 0x01).collect();
+            for (share, bit) in shares.iter_mut().zip(r.iter()) {
+                *share ^= *bit;
+            }
+            let ch = net.channel(j).unwrap();
+            try!(ch.send_bytes(&r));
+            try!(ch.flush());
+        }
+        for (&(id, _), share) in own.iter().zip(shares.iter()) {
+            values[id as usize - 1] = *share;
+        }
+
+        for j in 0..net.parties() {
+            if j == me {
+                continue;
+            }
+            let ids: Vec<u64> = (1..owners.len() as u64 + 1)
+                .filter(|id| owners[*id as usize - 1] == j)
+                .collect();
+            let r = try!(net.channel(j).unwrap().recv_bytes(ids.len()));
+            for (id, share) in ids.iter().zip(r.iter()) {
+                values[*id as usize - 1] = share & 0x01;
+            }
+        }
+        Ok(())
+    }
+}
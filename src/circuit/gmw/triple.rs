@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use rand::Rng;
+
+use super::error::GmwError;
+use super::super::garbled::Label;
+use super::super::super::channel::{Channel, Network};
+use super::super::super::ot::{Sender, Receiver, IknpSender, IknpReceiver};
+use super::super::super::parser;
+use super::super::super::parser::MetaInfo;
+use super::super::super::parser::types::GateType;
+
+/// A Triple is one party's share of a multiplication (Beaver) triple:
+/// the XOR of the shares `a`, `b` and `c` of all parties are bits with
+/// `c = a AND b`.
+///
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Triple {
+    pub a: u8,
+    pub b: u8,
+    pub c: u8,
+}
+
+impl Triple {
+    pub fn new(a: u8, b: u8, c: u8) -> Triple {
+        Triple {
+            a: a & 0x01,
+            b: b & 0x01,
+            c: c & 0x01,
+        }
+    }
+}
+
+/// Returns the number of multiplication triples a party needs to evaluate
+/// the circuit - one for every AND and OR gate of the circuit and its sub
+/// circuits.
+pub fn count_triples(info: &MetaInfo) -> Result<u64, GmwError> {
+    let (mut gates, mut triples) = (0, 0);
+    for node in try!(parser::read_circuit(info.path.as_path())) {
+        match try!(node).gate_type() {
+            Some(GateType::AND) | Some(GateType::OR) => {
+                gates += 1;
+                triples += 1;
+            }
+            Some(_) => gates += 1,
+            None => (),
+        }
+    }
+    if gates > info.gates {
+        return Err(GmwError::from(format!("circuit contains {} gates - expected at most {}",
+                                          gates,
+                                          info.gates)));
+    }
+    for path in info.sub_circuits.values() {
+        let sub = try!(parser::parse_meta_info(Path::new(path)));
+        triples += try!(count_triples(&sub));
+    }
+    Ok(triples)
+}
+
+/// Returns `count` random triples for every party of `parties` parties.
+/// The dealer knows all shares - so it must be trusted and should only
+/// be used for testing. Fails if there are no parties.
+pub fn deal_triples<R: Rng>(rng: &mut R,
+                            parties: usize,
+                            count: u64)
+                            -> Result<Vec<Vec<Triple>>, GmwError> {
+    if parties == 0 {
+        return Err(GmwError::from("cannot deal triples for 0 parties"));
+    }
+    let mut triples = vec![Vec::with_capacity(count as usize); parties];
+    for _ in 0..count {
+        let (a, b) = (rng.gen::<u8>() & 0x01, rng.gen::<u8>() & 0x01);
+        let mut last = Triple::new(a, b, a & b);
+        for shares in triples.iter_mut().take(parties - 1) {
+            let t = Triple::new(rng.gen(), rng.gen(), rng.gen());
+            last = Triple::new(last.a ^ t.a, last.b ^ t.b, last.c ^ t.c);
+            shares.push(t);
+        }
+        triples[parties - 1].push(last);
+    }
+    Ok(triples)
+}
+
+/// Generates `count` triples together with all other parties of the network
+/// and returns the own shares. Every party chooses random shares `a` and `b`.
+/// The cross terms `a_i AND b_j` of every pair of parties are shared with one
+/// oblivious transfer: party i sends `(r, r XOR a_i)` and party j chooses
+/// with `b_j`.
+pub fn generate_triples<C: Channel, R: Rng>(net: &mut Network<C>,
+                                            rng: &mut R,
+                                            count: u64)
+                                            -> Result<Vec<Triple>, GmwError> {
+    let n = count as usize;
+    let a: Vec<u8> = (0..n).map(|_| rng.gen::<u8>() & 0x01).collect();
+    let b: Vec<u8> = (0..n).map(|_| rng.gen::<u8>() & 0x01).collect();
+    let mut c: Vec<u8> = a.iter().zip(b.iter()).map(|(a, b)| a & b).collect();
+
+    let me = net.id();
+    for j in 0..net.parties() {
+        if j == me {
+            continue;
+        }
+        let ch = net.channel(j).unwrap();
+        // the party with the lower number sends first
+        let order = if me < j { [true, false] } else { [false, true] };
+        for sender in order.iter() {
+            if *sender {
+                let mut messages = Vec::with_capacity(n);
+                for (i, a) in a.iter().enumerate() {
+                    let r = rng.gen::<u8>() & 0x01;
+                    c[i] ^= r;
+                    messages.push((Label::new(r as u128), Label::new((r ^ a) as u128)));
+                }
+                try!(IknpSender::new().send(ch, rng, &messages));
+            } else {
+                let labels = try!(IknpReceiver::new().receive(ch, rng, &b));
+                for (i, label) in labels.iter().enumerate() {
+                    c[i] ^= label.value() as u8 & 0x01;
+                }
+            }
+        }
+    }
+    Ok((0..n).map(|i| Triple::new(a[i], b[i], c[i])).collect())
+}
@@ -1,2 +1,7 @@
-
 pub mod binary;
+pub mod bmr;
+pub mod builder;
+pub mod garbled;
+pub mod gmw;
+
+mod walker;
@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+use std::collections::HashMap;
+
+use super::super::parser;
+use super::super::parser::MetaInfo;
+use super::super::parser::error::ParseError;
+use super::super::parser::types::*;
+use super::super::parser::types::ID::*;
+
+type Entry = (ID, Pin);
+
+/// A Pass defines what is computed for the wires of a circuit while a
+/// Walker processes the gates.
+pub trait Pass {
+    type Wire: Clone;
+    type Error: From<String> + From<ParseError>;
+
+    /// Returns the wire of a ONE constant.
+    fn constant(&mut self) -> Result<Self::Wire, Self::Error>;
+
+    /// Returns the output wire of the gate `id` of the (sub) circuit
+    /// instance `instance` - `b` is None for NOT gates.
+    fn gate(&mut self,
+            gate_type: GateType,
+            instance: u64,
+            id: u64,
+            a: Self::Wire,
+            b: Option<Self::Wire>)
+            -> Result<Self::Wire, Self::Error>;
+}
+
+/// A Walker processes the gates of a libgc circuit (and its sub circuits)
+/// while the circuit is streamed from disk and lets a Pass compute the
+/// wires. Wires are dropped after their last consumer, so only the wires
+/// which are still to be consumed are kept in memory. The sub circuits are
+/// numbered in the order of their names - like `binary::Graph` numbers
+/// them - so that every party derives the same instances.
+///
+pub struct Walker<W: Clone> {
+    info: MetaInfo,
+    instance: u64,
+    input: HashMap<ID, W>,
+    output: HashMap<ID, W>,
+    lookup: HashMap<Entry, W>,
+    sub: HashMap<String, Walker<W>>,
+}
+
+impl<W: Clone> Walker<W> {
+    pub fn new(info: MetaInfo) -> Result<Walker<W>, ParseError> {
+        let mut instance = 0;
+        Walker::with_instance(info, &mut instance)
+    }
+
+    fn with_instance(info: MetaInfo, instance: &mut u64) -> Result<Walker<W>, ParseError> {
+        let mut w = Walker {
+            info: info,
+            instance: *instance,
+            input: HashMap::new(),
+            output: HashMap::new(),
+            lookup: HashMap::new(),
+            sub: HashMap::new(),
+        };
+        let mut keys: Vec<String> = w.info.sub_circuits.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let path = PathBuf::from(&w.info.sub_circuits[&key]);
+            let inf = try!(parser::parse_meta_info(path.as_path()));
+            *instance += 1;
+            let sub = try!(Walker::with_instance(inf, instance));
+            w.sub.insert(key, sub);
+        }
+        Ok(w)
+    }
+
+    #[inline]
+    pub fn info(&self) -> &MetaInfo {
+        &self.info
+    }
+
+    /// Processes the circuit with the given input wires - `inputs[id - 1]`
+    /// is the wire of the input `id` - and returns the output wires in the
+    /// order of the output IDs.
+    pub fn walk<P: Pass<Wire = W>>(&mut self,
+                                   pass: &mut P,
+                                   inputs: Vec<W>)
+                                   -> Result<Vec<W>, P::Error> {
+        self.reset();
+        if inputs.len() as u64 != self.info.inputs {
+            return Err(P::Error::from(format!("expected {} input wires - got {}",
+                                              self.info.inputs,
+                                              inputs.len())));
+        }
+        for (i, wire) in inputs.into_iter().enumerate() {
+            self.input.insert(Input(i as u64 + 1), wire);
+        }
+        try!(self.execute(pass));
+
+        let mut output = Vec::with_capacity(self.output.len());
+        for id in 1..self.output.len() as u64 + 1 {
+            output.push(try!(expect_some(self.output.remove(&Output(id)),
+                                         format!("no output for id {}", id))));
+        }
+        Ok(output)
+    }
+
+    fn reset(&mut self) {
+        self.input.clear();
+        self.output.clear();
+        self.lookup.clear();
+        for (_, sub) in self.sub.iter_mut() {
+            sub.reset();
+        }
+    }
+
+    fn is_executable(&self) -> bool {
+        self.info.inputs == self.input.len() as u64
+    }
+
+    fn execute<P: Pass<Wire = W>>(&mut self, pass: &mut P) -> Result<(), P::Error> {
+        if !self.is_executable() {
+            return Err(P::Error::from(String::from("circuit is not executable")));
+        }
+        let one: Option<Vec<Edge>> = self.info.one.as_ref().map(|node| node.edges().to_vec());
+        if let Some(edges) = one {
+            let wire = try!(pass.constant());
+            try!(self.propagate(&edges, wire, pass));
+        }
+
+        let path = PathBuf::from(self.info.path.as_path());
+        for node in try!(parser::read_circuit(path.as_path())) {
+            let node: Node = try!(node);
+
+            try!(match node.id() {
+                ID::Input(_) => self.process_input(&node, pass),
+                ID::Output(_) => self.process_output(&node, pass),
+                ID::Gate(_) => self.process_gate(&node, pass),
+                _ => Err(P::Error::from(format!("invalid node id: {}", node.id()))),
+            });
+        }
+        Ok(())
+    }
+
+    fn propagate<P: Pass<Wire = W>>(&mut self,
+                                     edges: &[Edge],
+                                     val: W,
+                                     pass: &mut P)
+                                     -> Result<(), P::Error> {
+        for edge in edges {
+            match edge.id() {
+                Output(id) => {
+                    self.output.insert(Output(id), val.clone());
+                }
+                Gate(id) => {
+                    let pin = try!(expect_some(edge.pin(),
+                                               format!("pin is required for edge: {}", edge)));
+                    self.lookup.insert((Gate(id), pin), val.clone());
+                }
+                Input(id) => {
+                    let key = try!(expect_some(edge.circuit(),
+                                               format!("invalid edge: expected sub circuit - {}",
+                                                       edge)));
+                    let sub: &mut Walker<W> =
+                        try!(expect_some(self.sub.get_mut(&key),
+                                         format!("unknown sub circuit: {}", key)));
+                    sub.input.insert(Input(id), val.clone());
+                    if sub.is_executable() {
+                        try!(sub.execute(pass));
+                        sub.input.clear();
+                    }
+                }
+                _ => {
+                    return Err(P::Error::from(format!("invalid edge: {}", edge)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn process_input<P: Pass<Wire = W>>(&mut self,
+                                         node: &Node,
+                                         pass: &mut P)
+                                         -> Result<(), P::Error> {
+        let val = try!(expect_some(self.input.get(&node.id()),
+                                   format!("missing input {}", node.id())))
+            .clone();
+        for edge in node.edges() {
+            if edge.circuit().is_none() && edge.id().is_input() {
+                return Err(P::Error::from(format!("invalid edge: {}", edge)));
+            }
+        }
+        self.propagate(node.edges(), val, pass)
+    }
+
+    fn process_output<P: Pass<Wire = W>>(&mut self,
+                                          node: &Node,
+                                          pass: &mut P)
+                                          -> Result<(), P::Error> {
+        let val = match node.circuit() {
+            Some(key) => {
+                // the output of a sub circuit has exactly one consumer
+                let sub = try!(expect_some(self.sub.get_mut(&key),
+                                           format!("unknown sub circuit: {}", key)));
+                try!(expect_some(sub.output.remove(&node.id()),
+                                 format!("no output for id {}", node.id())))
+            }
+            None => {
+                try!(expect_some(self.output.get(&node.id()),
+                                 format!("no output for id {}", node.id())))
+                    .clone()
+            }
+        };
+        self.propagate(node.edges(), val, pass)
+    }
+
+    fn process_gate<P: Pass<Wire = W>>(&mut self,
+                                        node: &Node,
+                                        pass: &mut P)
+                                        -> Result<(), P::Error> {
+        if node.circuit().is_some() {
+            return Err(P::Error::from(format!("node with id: {} cannot reference sub circuit",
+                                              node.id())));
+        }
+        let gate_type = try!(expect_some(node.gate_type(),
+                                         format!("node with id: {} must have a gate type",
+                                                 node.id())));
+        let a = try!(expect_some(self.lookup.remove(&(node.id(), Pin::Left)),
+                                 format!("cannot find left pin of node: {}", node.id())));
+        let b = match gate_type {
+            GateType::NOT => None,
+            _ => {
+                Some(try!(expect_some(self.lookup.remove(&(node.id(), Pin::Right)),
+                                      format!("cannot find right pin of node: {}", node.id()))))
+            }
+        };
+        let val = try!(pass.gate(gate_type, self.instance, node.id().into(), a, b));
+        self.propagate(node.edges(), val, pass)
+    }
+}
+
+fn expect_some<O>(expr: Option<O>, msg: String) -> Result<O, String> {
+    match expr {
+        Some(val) => Ok(val),
+        None => Err(msg),
+    }
+}
@@ -0,0 +1,177 @@
+/// The size of an AES block in bytes.
+pub const BLOCK_SIZE: usize = 16;
+
+const ROUNDS: usize = 10;
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const RCON: [u8; ROUNDS] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Aes128 is the AES block cipher with a 128 bit key. The key schedule is
+/// computed once, so a fixed-key instance can be used as a fast public
+/// random permutation. On x86_64 CPUs with AES-NI the hardware instructions
+/// are used - otherwise a portable implementation.
+///
+#[derive(Clone)]
+pub struct Aes128 {
+    round_keys: [[u8; BLOCK_SIZE]; ROUNDS + 1],
+    aes_ni: bool,
+}
+
+impl Aes128 {
+    pub fn new(key: &[u8; BLOCK_SIZE]) -> Aes128 {
+        let mut aes = Aes128::portable(key);
+        aes.aes_ni = has_aes_ni();
+        aes
+    }
+
+    /// Returns an instance which never uses the AES-NI instructions.
+    pub fn portable(key: &[u8; BLOCK_SIZE]) -> Aes128 {
+        Aes128 {
+            round_keys: expand_key(key),
+            aes_ni: false,
+        }
+    }
+
+    /// Returns true if the instance uses the AES-NI instructions.
+    #[inline]
+    pub fn is_aes_ni(&self) -> bool {
+        self.aes_ni
+    }
+
+    #[inline]
+    pub fn encrypt_block(&self, block: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+        if self.aes_ni {
+            return encrypt_aes_ni(&self.round_keys, block);
+        }
+        encrypt_portable(&self.round_keys, block)
+    }
+}
+
+fn expand_key(key: &[u8; BLOCK_SIZE]) -> [[u8; BLOCK_SIZE]; ROUNDS + 1] {
+    let mut w = [[0u8; 4]; 4 * (ROUNDS + 1)];
+    for i in 0..4 {
+        w[i].copy_from_slice(&key[4 * i..4 * i + 4]);
+    }
+    for i in 4..4 * (ROUNDS + 1) {
+        let mut t = w[i - 1];
+        if i % 4 == 0 {
+            t = [SBOX[t[1] as usize] ^ RCON[i / 4 - 1],
+                 SBOX[t[2] as usize],
+                 SBOX[t[3] as usize],
+                 SBOX[t[0] as usize]];
+        }
+        for j in 0..4 {
+            w[i][j] = w[i - 4][j] ^ t[j];
+        }
+    }
+    let mut round_keys = [[0u8; BLOCK_SIZE]; ROUNDS + 1];
+    for (i, rk) in round_keys.iter_mut().enumerate() {
+        for j in 0..4 {
+            rk[4 * j..4 * j + 4].copy_from_slice(&w[4 * i + j]);
+        }
+    }
+    round_keys
+}
+
+#[inline]
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ (((b >> 7) & 1) * 0x1b)
+}
+
+fn encrypt_portable(round_keys: &[[u8; BLOCK_SIZE]; ROUNDS + 1],
+                    block: &[u8; BLOCK_SIZE])
+                    -> [u8; BLOCK_SIZE] {
+    let mut s = *block;
+    for i in 0..BLOCK_SIZE {
+        s[i] ^= round_keys[0][i];
+    }
+    for round in 1..ROUNDS + 1 {
+        // SubBytes and ShiftRows - the state is stored column by column
+        let mut t = [0u8; BLOCK_SIZE];
+        for c in 0..4 {
+            for r in 0..4 {
+                t[4 * c + r] = SBOX[s[4 * ((c + r) % 4) + r] as usize];
+            }
+        }
+        // MixColumns
+        if round != ROUNDS {
+            for c in 0..4 {
+                let (a0, a1, a2, a3) = (t[4 * c], t[4 * c + 1], t[4 * c + 2], t[4 * c + 3]);
+                let all = a0 ^ a1 ^ a2 ^ a3;
+                t[4 * c] = a0 ^ all ^ xtime(a0 ^ a1);
+                t[4 * c + 1] = a1 ^ all ^ xtime(a1 ^ a2);
+                t[4 * c + 2] = a2 ^ all ^ xtime(a2 ^ a3);
+                t[4 * c + 3] = a3 ^ all ^ xtime(a3 ^ a0);
+            }
+        }
+        for i in 0..BLOCK_SIZE {
+            s[i] = t[i] ^ round_keys[round][i];
+        }
+    }
+    s
+}
+
+#[cfg(target_arch = "x86_64")]
+fn has_aes_ni() -> bool {
+    is_x86_feature_detected!("aes") && is_x86_feature_detected!("sse2")
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn has_aes_ni() -> bool {
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+fn encrypt_aes_ni(round_keys: &[[u8; BLOCK_SIZE]; ROUNDS + 1],
+                  block: &[u8; BLOCK_SIZE])
+                  -> [u8; BLOCK_SIZE] {
+    // Safe because an instance only uses AES-NI if the CPU supports it.
+    unsafe { aes_ni::encrypt(round_keys, block) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn encrypt_aes_ni(round_keys: &[[u8; BLOCK_SIZE]; ROUNDS + 1],
+                  block: &[u8; BLOCK_SIZE])
+                  -> [u8; BLOCK_SIZE] {
+    encrypt_portable(round_keys, block)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod aes_ni {
+    use std::arch::x86_64::*;
+    use super::{BLOCK_SIZE, ROUNDS};
+
+    #[target_feature(enable = "aes,sse2")]
+    pub unsafe fn encrypt(round_keys: &[[u8; BLOCK_SIZE]; ROUNDS + 1],
+                          block: &[u8; BLOCK_SIZE])
+                          -> [u8; BLOCK_SIZE] {
+        let mut s = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+        s = _mm_xor_si128(s, _mm_loadu_si128(round_keys[0].as_ptr() as *const __m128i));
+        for rk in round_keys.iter().take(ROUNDS).skip(1) {
+            s = _mm_aesenc_si128(s, _mm_loadu_si128(rk.as_ptr() as *const __m128i));
+        }
+        s = _mm_aesenclast_si128(s,
+                                 _mm_loadu_si128(round_keys[ROUNDS].as_ptr() as *const __m128i));
+        let mut out = [0u8; BLOCK_SIZE];
+        _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, s);
+        out
+    }
+}
@@ -0,0 +1,488 @@
+use std::ops::{Add, Sub, Mul, Neg};
+
+use rand::Rng;
+
+/// The size of an encoded (affine, uncompressed) point in bytes.
+pub const POINT_SIZE: usize = 64;
+
+/// The size of a scalar in bytes.
+pub const SCALAR_SIZE: usize = 32;
+
+const MASK: u64 = (1 << 51) - 1;
+
+// the curve constants d, 2d and the base point in radix 2^51, so that they
+// are not decoded on every use
+const D: FieldElement = FieldElement([0x34dca135978a3,
+                                      0x1a8283b156ebd,
+                                      0x5e7a26001c029,
+                                      0x739c663a03cbb,
+                                      0x52036cee2b6ff]);
+
+const D2: FieldElement = FieldElement([0x69b9426b2f159,
+                                       0x35050762add7a,
+                                       0x3cf44c0038052,
+                                       0x6738cc7407977,
+                                       0x2406d9dc56dff]);
+
+const BASE_X: FieldElement = FieldElement([0x62d608f25d51a,
+                                           0x412a4b4f6592a,
+                                           0x75b7171a4b31d,
+                                           0x1ff60527118fe,
+                                           0x216936d3cd6e5]);
+
+const BASE_Y: FieldElement = FieldElement([0x6666666666658,
+                                           0x4cccccccccccc,
+                                           0x1999999999999,
+                                           0x3333333333333,
+                                           0x6666666666666]);
+
+const BASE_T: FieldElement = FieldElement([0x68ab3a5b7dda3,
+                                           0x00eea2a5eadbb,
+                                           0x2af8df483c27e,
+                                           0x332b375274732,
+                                           0x67875f0fd78b7]);
+
+// returns an all-one mask if the lowest bit of choice is set and zero
+// otherwise - without branching on the choice
+fn mask(choice: u8) -> u64 {
+    0u64.wrapping_sub((choice & 1) as u64)
+}
+
+/// FieldElement is an element of GF(2^255 - 19) in radix 2^51.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    pub fn zero() -> FieldElement {
+        FieldElement([0; 5])
+    }
+
+    pub fn one() -> FieldElement {
+        FieldElement([1, 0, 0, 0, 0])
+    }
+
+    /// Decodes a little-endian byte string. Returns None if the value is not
+    /// canonical - i.e. not smaller than 2^255 - 19.
+    pub fn from_bytes(buf: &[u8; 32]) -> Option<FieldElement> {
+        let mut w = [0u64; 4];
+        for i in 0..4 {
+            for j in 0..8 {
+                w[i] |= (buf[8 * i + j] as u64) << (8 * j);
+            }
+        }
+        let fe = FieldElement([w[0] & MASK,
+                               ((w[0] >> 51) | (w[1] << 13)) & MASK,
+                               ((w[1] >> 38) | (w[2] << 26)) & MASK,
+                               ((w[2] >> 25) | (w[3] << 39)) & MASK,
+                               (w[3] >> 12) & MASK]);
+        if fe.to_bytes() != *buf {
+            return None;
+        }
+        Some(fe)
+    }
+
+    /// Encodes the field element as canonical little-endian byte string.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut h = self.carry().0;
+        let mut q = (h[0] + 19) >> 51;
+        q = (h[1] + q) >> 51;
+        q = (h[2] + q) >> 51;
+        q = (h[3] + q) >> 51;
+        q = (h[4] + q) >> 51;
+
+        h[0] += 19 * q;
+        h[1] += h[0] >> 51;
+        h[0] &= MASK;
+        h[2] += h[1] >> 51;
+        h[1] &= MASK;
+        h[3] += h[2] >> 51;
+        h[2] &= MASK;
+        h[4] += h[3] >> 51;
+        h[3] &= MASK;
+        h[4] &= MASK;
+
+        let w = [h[0] | (h[1] << 51),
+                 (h[1] >> 13) | (h[2] << 38),
+                 (h[2] >> 26) | (h[3] << 25),
+                 (h[3] >> 39) | (h[4] << 12)];
+        let mut buf = [0u8; 32];
+        for i in 0..4 {
+            for j in 0..8 {
+                buf[8 * i + j] = (w[i] >> (8 * j)) as u8;
+            }
+        }
+        buf
+    }
+
+    fn carry(&self) -> FieldElement {
+        let mut h = self.0;
+        h[1] += h[0] >> 51;
+        h[0] &= MASK;
+        h[2] += h[1] >> 51;
+        h[1] &= MASK;
+        h[3] += h[2] >> 51;
+        h[2] &= MASK;
+        h[4] += h[3] >> 51;
+        h[3] &= MASK;
+        h[0] += 19 * (h[4] >> 51);
+        h[4] &= MASK;
+        FieldElement(h)
+    }
+
+    pub fn square(&self) -> FieldElement {
+        *self * *self
+    }
+
+    /// Returns `a` if the lowest bit of `choice` is 0 and `b` otherwise in
+    /// constant time.
+    pub fn conditional_select(a: &FieldElement, b: &FieldElement, choice: u8) -> FieldElement {
+        let m = mask(choice);
+        let mut r = a.0;
+        for i in 0..5 {
+            r[i] ^= m & (a.0[i] ^ b.0[i]);
+        }
+        FieldElement(r)
+    }
+
+    /// Swaps `a` and `b` if the lowest bit of `choice` is set in constant
+    /// time.
+    pub fn conditional_swap(a: &mut FieldElement, b: &mut FieldElement, choice: u8) {
+        let m = mask(choice);
+        for i in 0..5 {
+            let t = m & (a.0[i] ^ b.0[i]);
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+
+    // returns self^(2^k)
+    fn pow2k(&self, k: usize) -> FieldElement {
+        let mut r = *self;
+        for _ in 0..k {
+            r = r.square();
+        }
+        r
+    }
+
+    /// Returns the multiplicative inverse - computed as self^(p-2) with the
+    /// addition chain of ref10, which takes the same steps for every value.
+    pub fn invert(&self) -> FieldElement {
+        // p - 2 = 2^255 - 21
+        let z2 = self.square();
+        let z9 = z2.pow2k(2) * *self;
+        let z11 = z9 * z2;
+        let z_5_0 = z11.square() * z9;
+        let z_10_0 = z_5_0.pow2k(5) * z_5_0;
+        let z_20_0 = z_10_0.pow2k(10) * z_10_0;
+        let z_40_0 = z_20_0.pow2k(20) * z_20_0;
+        let z_50_0 = z_40_0.pow2k(10) * z_10_0;
+        let z_100_0 = z_50_0.pow2k(50) * z_50_0;
+        let z_200_0 = z_100_0.pow2k(100) * z_100_0;
+        let z_250_0 = z_200_0.pow2k(50) * z_50_0;
+        z_250_0.pow2k(5) * z11
+    }
+}
+
+impl PartialEq for FieldElement {
+    fn eq(&self, other: &FieldElement) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for FieldElement {}
+
+impl Add for FieldElement {
+    type Output = FieldElement;
+
+    fn add(self, rhs: FieldElement) -> FieldElement {
+        let (a, b) = (self.0, rhs.0);
+        FieldElement([a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3], a[4] + b[4]]).carry()
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = FieldElement;
+
+    fn sub(self, rhs: FieldElement) -> FieldElement {
+        // add 4p before subtracting to avoid an underflow
+        let (a, b) = (self.carry().0, rhs.carry().0);
+        FieldElement([(a[0] + 0x1fffffffffffb4) - b[0],
+                      (a[1] + 0x1ffffffffffffc) - b[1],
+                      (a[2] + 0x1ffffffffffffc) - b[2],
+                      (a[3] + 0x1ffffffffffffc) - b[3],
+                      (a[4] + 0x1ffffffffffffc) - b[4]])
+            .carry()
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = FieldElement;
+
+    fn neg(self) -> FieldElement {
+        FieldElement::zero() - self
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = FieldElement;
+
+    fn mul(self, rhs: FieldElement) -> FieldElement {
+        let (a, b) = (self.0, rhs.0);
+        let m = |x: u64, y: u64| (x as u128) * (y as u128);
+        let (b1, b2, b3, b4) = (19 * b[1], 19 * b[2], 19 * b[3], 19 * b[4]);
+
+        let r0 = m(a[0], b[0]) + m(a[1], b4) + m(a[2], b3) + m(a[3], b2) + m(a[4], b1);
+        let mut r1 = m(a[0], b[1]) + m(a[1], b[0]) + m(a[2], b4) + m(a[3], b3) + m(a[4], b2);
+        let mut r2 = m(a[0], b[2]) + m(a[1], b[1]) + m(a[2], b[0]) + m(a[3], b4) + m(a[4], b3);
+        let mut r3 = m(a[0], b[3]) + m(a[1], b[2]) + m(a[2], b[1]) + m(a[3], b[0]) + m(a[4], b4);
+        let mut r4 = m(a[0], b[4]) + m(a[1], b[3]) + m(a[2], b[2]) + m(a[3], b[1]) +
+                     m(a[4], b[0]);
+
+        let mask = MASK as u128;
+        r1 += r0 >> 51;
+        r2 += r1 >> 51;
+        r3 += r2 >> 51;
+        r4 += r3 >> 51;
+        let h0 = (r0 & mask) + 19 * (r4 >> 51);
+        let h1 = (r1 & mask) + (h0 >> 51);
+        FieldElement([(h0 & mask) as u64,
+                      h1 as u64,
+                      (r2 & mask) as u64,
+                      (r3 & mask) as u64,
+                      (r4 & mask) as u64])
+            .carry()
+    }
+}
+
+/// EdwardsPoint is a point on the twisted Edwards curve
+/// -x^2 + y^2 = 1 + d x^2 y^2 (edwards25519) in extended coordinates.
+/// The curve is used as prime order group (after multiplying by the cofactor)
+/// for Diffie-Hellman style protocols like the base oblivious transfer.
+///
+#[derive(Debug, Copy, Clone)]
+pub struct EdwardsPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+}
+
+impl EdwardsPoint {
+    pub fn identity() -> EdwardsPoint {
+        EdwardsPoint {
+            x: FieldElement::zero(),
+            y: FieldElement::one(),
+            z: FieldElement::one(),
+            t: FieldElement::zero(),
+        }
+    }
+
+    /// Returns the standard base point of edwards25519.
+    pub fn basepoint() -> EdwardsPoint {
+        EdwardsPoint {
+            x: BASE_X,
+            y: BASE_Y,
+            z: FieldElement::one(),
+            t: BASE_T,
+        }
+    }
+
+    /// Decodes an affine point (x || y). Returns None if the coordinates
+    /// are not canonical or the point is not on the curve.
+    pub fn from_bytes(buf: &[u8; POINT_SIZE]) -> Option<EdwardsPoint> {
+        let (mut bx, mut by) = ([0u8; 32], [0u8; 32]);
+        bx.copy_from_slice(&buf[..32]);
+        by.copy_from_slice(&buf[32..]);
+        let (x, y) = match (FieldElement::from_bytes(&bx), FieldElement::from_bytes(&by)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return None,
+        };
+        let (xx, yy) = (x.square(), y.square());
+        if yy - xx != FieldElement::one() + D * xx * yy {
+            return None;
+        }
+        Some(EdwardsPoint {
+            x: x,
+            y: y,
+            z: FieldElement::one(),
+            t: x * y,
+        })
+    }
+
+    /// Encodes the point in affine coordinates (x || y).
+    pub fn to_bytes(&self) -> [u8; POINT_SIZE] {
+        let zinv = self.z.invert();
+        let mut buf = [0u8; POINT_SIZE];
+        buf[..32].copy_from_slice(&(self.x * zinv).to_bytes());
+        buf[32..].copy_from_slice(&(self.y * zinv).to_bytes());
+        buf
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == EdwardsPoint::identity()
+    }
+
+    pub fn double(&self) -> EdwardsPoint {
+        // dbl-2008-hwcd with a = -1
+        let a = self.x.square();
+        let b = self.y.square();
+        let zz = self.z.square();
+        let c = zz + zz;
+        let e = (self.x + self.y).square() - a - b;
+        let g = b - a;
+        let f = g - c;
+        let h = -a - b;
+        EdwardsPoint {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+
+    /// Returns `a` if the lowest bit of `choice` is 0 and `b` otherwise in
+    /// constant time.
+    pub fn conditional_select(a: &EdwardsPoint, b: &EdwardsPoint, choice: u8) -> EdwardsPoint {
+        EdwardsPoint {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+            t: FieldElement::conditional_select(&a.t, &b.t, choice),
+        }
+    }
+
+    /// Swaps `a` and `b` if the lowest bit of `choice` is set in constant
+    /// time.
+    pub fn conditional_swap(a: &mut EdwardsPoint, b: &mut EdwardsPoint, choice: u8) {
+        FieldElement::conditional_swap(&mut a.x, &mut b.x, choice);
+        FieldElement::conditional_swap(&mut a.y, &mut b.y, choice);
+        FieldElement::conditional_swap(&mut a.z, &mut b.z, choice);
+        FieldElement::conditional_swap(&mut a.t, &mut b.t, choice);
+    }
+
+    /// Multiplies the point by a little-endian scalar. The Montgomery
+    /// ladder does one addition and one doubling for each of the 256 bits
+    /// and swaps the points without branching, so that the running time
+    /// does not depend on the scalar.
+    pub fn mul_scalar(&self, scalar: &[u8; SCALAR_SIZE]) -> EdwardsPoint {
+        // r1 = r0 + self is kept for every prefix of the scalar
+        let mut r0 = EdwardsPoint::identity();
+        let mut r1 = *self;
+        let mut swap = 0u8;
+        for i in (0..256).rev() {
+            let bit = (scalar[i / 8] >> (i % 8)) & 1;
+            EdwardsPoint::conditional_swap(&mut r0, &mut r1, swap ^ bit);
+            swap = bit;
+            r1 = r0 + r1;
+            r0 = r0.double();
+        }
+        EdwardsPoint::conditional_swap(&mut r0, &mut r1, swap);
+        r0
+    }
+
+    /// Multiplies the base point by a little-endian scalar.
+    pub fn mul_base(scalar: &[u8; SCALAR_SIZE]) -> EdwardsPoint {
+        EdwardsPoint::basepoint().mul_scalar(scalar)
+    }
+}
+
+/// PointTable holds the multiples `k * 16^w * P` (k < 16) of a point P for
+/// all 64 windows w of a scalar, so that multiplying P by many scalars
+/// costs one addition per window. The table entries are looked up in
+/// constant time.
+///
+pub struct PointTable {
+    windows: Vec<[EdwardsPoint; 16]>,
+}
+
+impl PointTable {
+    pub fn new(point: &EdwardsPoint) -> PointTable {
+        let mut windows = Vec::with_capacity(64);
+        let mut base = *point;
+        for _ in 0..64 {
+            let mut window = [EdwardsPoint::identity(); 16];
+            for k in 1..16 {
+                window[k] = window[k - 1] + base;
+            }
+            base = window[8].double();
+            windows.push(window);
+        }
+        PointTable { windows: windows }
+    }
+
+    /// Multiplies the point by a little-endian scalar in constant time.
+    pub fn mul_scalar(&self, scalar: &[u8; SCALAR_SIZE]) -> EdwardsPoint {
+        let mut r = EdwardsPoint::identity();
+        for (w, window) in self.windows.iter().enumerate() {
+            let nibble = (scalar[w / 2] >> (4 * (w % 2))) & 0xf;
+            let mut entry = EdwardsPoint::identity();
+            for (k, point) in window.iter().enumerate() {
+                // choice is 1 if k == nibble
+                let diff = (k as u8) ^ nibble;
+                let choice = 1 ^ ((diff | diff.wrapping_neg()) >> 7);
+                entry = EdwardsPoint::conditional_select(&entry, point, choice);
+            }
+            r = r + entry;
+        }
+        r
+    }
+}
+
+/// Returns a random scalar which is a multiple of the cofactor 8, so that
+/// multiplying any point by it clears small order components.
+pub fn random_scalar<R: Rng>(rng: &mut R) -> [u8; SCALAR_SIZE] {
+    let mut s = [0u8; SCALAR_SIZE];
+    rng.fill_bytes(&mut s);
+    s[0] &= 248;
+    s[31] &= 127;
+    s[31] |= 64;
+    s
+}
+
+impl PartialEq for EdwardsPoint {
+    fn eq(&self, other: &EdwardsPoint) -> bool {
+        self.x * other.z == other.x * self.z && self.y * other.z == other.y * self.z
+    }
+}
+
+impl Eq for EdwardsPoint {}
+
+impl Add for EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn add(self, rhs: EdwardsPoint) -> EdwardsPoint {
+        let a = (self.y - self.x) * (rhs.y - rhs.x);
+        let b = (self.y + self.x) * (rhs.y + rhs.x);
+        let c = self.t * D2 * rhs.t;
+        let d = (self.z + self.z) * rhs.z;
+        let (e, f, g, h) = (b - a, d - c, d + c, b + a);
+        EdwardsPoint {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+}
+
+impl Neg for EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn neg(self) -> EdwardsPoint {
+        EdwardsPoint {
+            x: -self.x,
+            y: self.y,
+            z: self.z,
+            t: -self.t,
+        }
+    }
+}
+
+impl Sub for EdwardsPoint {
+    type Output = EdwardsPoint;
+
+    fn sub(self, rhs: EdwardsPoint) -> EdwardsPoint {
+        self + (-rhs)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod aes;
+pub mod sha256;
+pub mod curve25519;
@@ -1,4 +1,11 @@
 
+extern crate rand;
+
+pub mod bristol;
 pub mod cbmc;
+pub mod channel;
+pub mod crypto;
+pub mod ot;
 pub mod parser;
+pub mod protocol;
 pub mod circuit;
\ No newline at end of file
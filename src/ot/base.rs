@@ -0,0 +1,133 @@
+use rand::Rng;
+
+use super::{Sender, Receiver, check_choices};
+use super::error::OTError;
+use super::super::channel::Channel;
+use super::super::circuit::garbled::{Label, LABEL_SIZE};
+use super::super::crypto::curve25519::{self, EdwardsPoint, PointTable, POINT_SIZE};
+use super::super::crypto::sha256::Sha256;
+
+/// ChouOrlandiSender is the sender of the "simplest OT" protocol by
+/// Chou and Orlandi over the edwards25519 group. All OTs of one call
+/// share the sender's public key, so a batch of n OTs costs one round.
+///
+pub struct ChouOrlandiSender {}
+
+/// ChouOrlandiReceiver is the receiver of the "simplest OT" protocol.
+///
+pub struct ChouOrlandiReceiver {}
+
+impl ChouOrlandiSender {
+    pub fn new() -> ChouOrlandiSender {
+        ChouOrlandiSender {}
+    }
+}
+
+impl ChouOrlandiReceiver {
+    pub fn new() -> ChouOrlandiReceiver {
+        ChouOrlandiReceiver {}
+    }
+}
+
+// a and b are the encoded public points - encoding needs an inversion, so
+// they are only encoded once
+fn derive_key(index: u64,
+              a: &[u8; POINT_SIZE],
+              b: &[u8; POINT_SIZE],
+              k: &EdwardsPoint)
+              -> Label {
+    let mut h = Sha256::new();
+    let mut buf = [0u8; 8];
+    for (i, byte) in buf.iter_mut().enumerate() {
+        *byte = (index >> (56 - 8 * i)) as u8;
+    }
+    h.update(&buf);
+    h.update(a);
+    h.update(b);
+    h.update(&k.to_bytes());
+    let digest = h.finish();
+
+    let mut key = [0u8; LABEL_SIZE];
+    key.copy_from_slice(&digest[..LABEL_SIZE]);
+    Label::from_bytes(&key)
+}
+
+// returns the received point and its encoding
+fn recv_point<C: Channel>(ch: &mut C) -> Result<(EdwardsPoint, [u8; POINT_SIZE]), OTError> {
+    let mut buf = [0u8; POINT_SIZE];
+    try!(ch.read_exact(&mut buf));
+    match EdwardsPoint::from_bytes(&buf) {
+        Some(ref p) if p.is_identity() => Err(OTError::from("received the identity point")),
+        Some(p) => Ok((p, buf)),
+        None => Err(OTError::from("received an invalid point")),
+    }
+}
+
+impl Sender for ChouOrlandiSender {
+    fn send<C: Channel, R: Rng>(&mut self,
+                                ch: &mut C,
+                                rng: &mut R,
+                                messages: &[(Label, Label)])
+                                -> Result<(), OTError> {
+        let a = curve25519::random_scalar(rng);
+        let pub_a = EdwardsPoint::mul_base(&a);
+        let aa = pub_a.mul_scalar(&a);
+        let enc_a = pub_a.to_bytes();
+        try!(ch.send_bytes(&enc_a));
+        try!(ch.flush());
+
+        let n = try!(ch.recv_u64());
+        if n != messages.len() as u64 {
+            return Err(OTError::from(format!("receiver expects {} OTs - sender has {}",
+                                             n,
+                                             messages.len())));
+        }
+        let mut points = Vec::with_capacity(messages.len());
+        for _ in 0..n {
+            points.push(try!(recv_point(ch)));
+        }
+        for (i, (b, m)) in points.iter().zip(messages.iter()).enumerate() {
+            let ab = b.0.mul_scalar(&a);
+            let k0 = derive_key(i as u64, &enc_a, &b.1, &ab);
+            let k1 = derive_key(i as u64, &enc_a, &b.1, &(ab - aa));
+            try!(ch.send_label(m.0 ^ k0));
+            try!(ch.send_label(m.1 ^ k1));
+        }
+        try!(ch.flush());
+        Ok(())
+    }
+}
+
+impl Receiver for ChouOrlandiReceiver {
+    fn receive<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   choices: &[u8])
+                                   -> Result<Vec<Label>, OTError> {
+        try!(check_choices(choices));
+        let (pub_a, enc_a) = try!(recv_point(ch));
+        let base = PointTable::new(&EdwardsPoint::basepoint());
+        let table_a = PointTable::new(&pub_a);
+
+        try!(ch.send_u64(choices.len() as u64));
+        let mut keys = Vec::with_capacity(choices.len());
+        for (i, c) in choices.iter().enumerate() {
+            let b = curve25519::random_scalar(rng);
+            let pub_b = base.mul_scalar(&b);
+            let pub_b = EdwardsPoint::conditional_select(&pub_b, &(pub_b + pub_a), *c);
+            let enc_b = pub_b.to_bytes();
+            try!(ch.send_bytes(&enc_b));
+            keys.push(derive_key(i as u64, &enc_a, &enc_b, &table_a.mul_scalar(&b)));
+        }
+        try!(ch.flush());
+
+        let mut messages = Vec::with_capacity(choices.len());
+        for (c, k) in choices.iter().zip(keys.into_iter()) {
+            let e0 = try!(ch.recv_label());
+            let e1 = try!(ch.recv_label());
+            let mask = 0u128.wrapping_sub(*c as u128);
+            messages.push(e0 ^ k ^ Label::new((e0 ^ e1).value() & mask));
+        }
+        Ok(messages)
+    }
+}
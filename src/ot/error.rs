@@ -0,0 +1,39 @@
+use std::io;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct OTError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for OTError {
+    fn from(s: &'a str) -> OTError {
+        OTError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for OTError {
+    fn from(s: String) -> OTError {
+        OTError { msg: s }
+    }
+}
+
+impl error::Error for OTError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+impl From<io::Error> for OTError {
+    fn from(err: io::Error) -> OTError {
+        OTError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for OTError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on oblivious transfer: {}", self.msg)
+    }
+}
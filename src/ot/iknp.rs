@@ -0,0 +1,143 @@
+use rand::Rng;
+
+use super::{Sender, Receiver, check_choices};
+use super::error::OTError;
+use super::base::{ChouOrlandiSender, ChouOrlandiReceiver};
+use super::super::channel::Channel;
+use super::super::circuit::garbled::{Label, TweakableHash, FixedKeyAes};
+use super::super::crypto::aes::{Aes128, BLOCK_SIZE};
+
+/// The security parameter - the number of base OTs.
+const KAPPA: usize = 128;
+
+/// IknpSender is the sender of the IKNP OT extension. It runs 128 base OTs
+/// (as receiver) and extends them to an arbitrary number of OTs using only
+/// symmetric cryptography.
+///
+pub struct IknpSender {}
+
+/// IknpReceiver is the receiver of the IKNP OT extension.
+///
+pub struct IknpReceiver {}
+
+impl IknpSender {
+    pub fn new() -> IknpSender {
+        IknpSender {}
+    }
+}
+
+impl IknpReceiver {
+    pub fn new() -> IknpReceiver {
+        IknpReceiver {}
+    }
+}
+
+/// Expands a seed to `len` pseudo-random bytes using AES in counter mode.
+fn prg(seed: Label, len: usize) -> Vec<u8> {
+    let aes = Aes128::new(&seed.to_bytes());
+    let mut out = Vec::with_capacity(len + BLOCK_SIZE);
+    let mut ctr: u128 = 0;
+    while out.len() < len {
+        out.extend_from_slice(&aes.encrypt_block(&Label::new(ctr).to_bytes()));
+        ctr += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+/// Transposes the KAPPA columns of m bits into m rows of KAPPA bits.
+fn transpose(columns: &[Vec<u8>], m: usize) -> Vec<Label> {
+    let mut rows = vec![0u128; m];
+    for (i, column) in columns.iter().enumerate() {
+        for (j, row) in rows.iter_mut().enumerate() {
+            let bit = (column[j / 8] >> (j % 8)) & 1;
+            *row |= (bit as u128) << i;
+        }
+    }
+    rows.into_iter().map(Label::new).collect()
+}
+
+impl Sender for IknpSender {
+    fn send<C: Channel, R: Rng>(&mut self,
+                                ch: &mut C,
+                                rng: &mut R,
+                                messages: &[(Label, Label)])
+                                -> Result<(), OTError> {
+        let m = try!(ch.recv_u64()) as usize;
+        if m != messages.len() {
+            return Err(OTError::from(format!("receiver expects {} OTs - sender has {}",
+                                             m,
+                                             messages.len())));
+        }
+        let s = Label::random(rng);
+        let choices: Vec<u8> = (0..KAPPA).map(|i| ((s.value() >> i) & 1) as u8).collect();
+        let seeds = try!(ChouOrlandiReceiver::new().receive(ch, rng, &choices));
+
+        let len = (m + 7) / 8;
+        let mut columns = Vec::with_capacity(KAPPA);
+        for (i, seed) in seeds.iter().enumerate() {
+            let u = try!(ch.recv_bytes(len));
+            let mut q = prg(*seed, len);
+            if choices[i] == 1 {
+                for (qb, ub) in q.iter_mut().zip(u.iter()) {
+                    *qb ^= *ub;
+                }
+            }
+            columns.push(q);
+        }
+
+        let hash = FixedKeyAes::default();
+        for (j, (q, msg)) in transpose(&columns, m).into_iter().zip(messages.iter()).enumerate() {
+            try!(ch.send_label(msg.0 ^ hash.hash(q, j as u128)));
+            try!(ch.send_label(msg.1 ^ hash.hash(q ^ s, j as u128)));
+        }
+        try!(ch.flush());
+        Ok(())
+    }
+}
+
+impl Receiver for IknpReceiver {
+    fn receive<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   choices: &[u8])
+                                   -> Result<Vec<Label>, OTError> {
+        try!(check_choices(choices));
+        let m = choices.len();
+        try!(ch.send_u64(m as u64));
+        try!(ch.flush());
+
+        let mut seeds = Vec::with_capacity(KAPPA);
+        for _ in 0..KAPPA {
+            seeds.push((Label::random(rng), Label::random(rng)));
+        }
+        try!(ChouOrlandiSender::new().send(ch, rng, &seeds));
+
+        let len = (m + 7) / 8;
+        let mut r = vec![0u8; len];
+        for (j, c) in choices.iter().enumerate() {
+            r[j / 8] |= *c << (j % 8);
+        }
+        let mut columns = Vec::with_capacity(KAPPA);
+        for seed in &seeds {
+            let t = prg(seed.0, len);
+            let mut u = prg(seed.1, len);
+            for ((ub, tb), rb) in u.iter_mut().zip(t.iter()).zip(r.iter()) {
+                *ub ^= *tb ^ *rb;
+            }
+            try!(ch.send_bytes(&u));
+            columns.push(t);
+        }
+        try!(ch.flush());
+
+        let hash = FixedKeyAes::default();
+        let mut messages = Vec::with_capacity(m);
+        for (j, t) in transpose(&columns, m).into_iter().enumerate() {
+            let y0 = try!(ch.recv_label());
+            let y1 = try!(ch.recv_label());
+            let y = if choices[j] == 1 { y1 } else { y0 };
+            messages.push(y ^ hash.hash(t, j as u128));
+        }
+        Ok(messages)
+    }
+}
@@ -0,0 +1,98 @@
+mod error;
+mod base;
+mod iknp;
+
+pub use self::error::OTError;
+pub use self::base::{ChouOrlandiSender, ChouOrlandiReceiver};
+pub use self::iknp::{IknpSender, IknpReceiver};
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::channel::Channel;
+use super::circuit::garbled::{Label, LabelPair};
+
+/// Sender is the sending party of a 1-out-of-2 oblivious transfer.
+/// For every pair of messages the receiver learns exactly one message
+/// and the sender doesn't learn which one.
+///
+pub trait Sender {
+    fn send<C: Channel, R: Rng>(&mut self,
+                                ch: &mut C,
+                                rng: &mut R,
+                                messages: &[(Label, Label)])
+                                -> Result<(), OTError>;
+}
+
+/// Receiver is the receiving party of a 1-out-of-2 oblivious transfer.
+/// It obtains the first message of a pair if its choice bit is 0 and
+/// the second one if it is 1 - any other choice is rejected.
+///
+pub trait Receiver {
+    fn receive<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   choices: &[u8])
+                                   -> Result<Vec<Label>, OTError>;
+}
+
+// returns an error if one of the choices is not a bit
+fn check_choices(choices: &[u8]) -> Result<(), OTError> {
+    match choices.iter().position(|c| *c > 1) {
+        Some(i) => {
+            Err(OTError::from(format!("choice {} is {} - expected 0 or 1", i, choices[i])))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Transfers the labels of the circuit inputs `ids` (all inputs of the
+/// other party) to the evaluator - one label per input. The `inputs` are
+/// the input label pairs of a garbling.
+pub fn send_inputs<C: Channel, R: Rng>(ch: &mut C,
+                                       rng: &mut R,
+                                       inputs: &HashMap<u64, LabelPair>,
+                                       ids: &[u64])
+                                       -> Result<(), OTError> {
+    let mut ids = ids.to_vec();
+    ids.sort();
+
+    let n = try!(ch.recv_u64()) as usize;
+    if n != ids.len() {
+        return Err(OTError::from(format!("receiver requests {} inputs - expected {}",
+                                         n,
+                                         ids.len())));
+    }
+    let mut messages = Vec::with_capacity(ids.len());
+    for id in &ids {
+        if try!(ch.recv_u64()) != *id {
+            return Err(OTError::from("receiver requests unexpected inputs"));
+        }
+        match inputs.get(id) {
+            Some(pair) => messages.push(*pair),
+            None => return Err(OTError::from(format!("no labels for input {}", id))),
+        };
+    }
+    IknpSender::new().send(ch, rng, &messages)
+}
+
+/// Receives the labels of the circuit inputs in `bits` (input ID -> value)
+/// from the garbler.
+pub fn receive_inputs<C: Channel, R: Rng>(ch: &mut C,
+                                          rng: &mut R,
+                                          bits: &HashMap<u64, u8>)
+                                          -> Result<HashMap<u64, Label>, OTError> {
+    let mut ids: Vec<u64> = bits.keys().cloned().collect();
+    ids.sort();
+
+    try!(ch.send_u64(ids.len() as u64));
+    for id in &ids {
+        try!(ch.send_u64(*id));
+    }
+    try!(ch.flush());
+
+    let choices: Vec<u8> = ids.iter().map(|id| bits[id]).collect();
+    let labels = try!(IknpReceiver::new().receive(ch, rng, &choices));
+    Ok(ids.into_iter().zip(labels.into_iter()).collect())
+}
@@ -0,0 +1,252 @@
+use std::io::{Read, Write, ErrorKind};
+use std::collections::HashMap;
+
+use super::error::ParseError;
+use super::error::ErrorType::InvalidEncoding;
+use super::types::*;
+
+const MAGIC: &'static [u8] = b"LGCB";
+const VERSION: u8 = 1;
+
+/// A CompactCircuit iterates over the nodes of a compact circuit - the
+/// binary form of `circuit.txt`:
+///
+/// ```text
+/// circuit := "LGCB" version:u8 names:varint name* node*
+/// name    := len:varint utf8-bytes
+/// node    := (id << 3 | kind):varint [circuit:varint] edges:varint edge*
+/// edge    := (id << 2 | kind):varint [circuit:varint]
+/// ```
+///
+/// Sub circuit names are stored once in the name table and referenced by
+/// their index. The node kinds are: input (0), output (1), output of a sub
+/// circuit (2) and the gates AND (3), XOR (4), OR (5) and NOT (6). The edge
+/// kinds are: output (0), left pin (1) and right pin (2) of a gate and input
+/// of a sub circuit (3).
+///
+pub struct CompactCircuit<R> {
+    reader: R,
+    names: Vec<String>,
+    node: u64,
+}
+
+impl<R: Read> Iterator for CompactCircuit<R> {
+    type Item = Result<Node, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Node, ParseError>> {
+        let first = match read_byte(&mut self.reader) {
+            Ok(Some(val)) => val,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err)),
+        };
+        let result = self.read_node(first);
+        self.node += 1;
+        Some(result)
+    }
+}
+
+impl<R: Read> CompactCircuit<R> {
+    /// Reads the header and the name table of the compact circuit.
+    pub fn new(mut reader: R) -> Result<CompactCircuit<R>, ParseError> {
+        let mut magic = [0u8; 5];
+        try!(read_exact(&mut reader, &mut magic));
+        if &magic[..4] != MAGIC {
+            return Err(ParseError::new(InvalidEncoding, "not a compact libgc circuit"));
+        }
+        if magic[4] != VERSION {
+            return Err(ParseError::new(InvalidEncoding,
+                                       &format!("unsupported version {}", magic[4])));
+        }
+        let n = try!(read_varint(&mut reader));
+        let mut names = Vec::new();
+        for _ in 0..n {
+            // the length is untrusted - only the bytes actually read are allocated
+            let len = try!(read_varint(&mut reader));
+            let mut buf = Vec::new();
+            if try!((&mut reader).take(len).read_to_end(&mut buf)) as u64 != len {
+                return Err(ParseError::new(InvalidEncoding, "unexpected end of circuit"));
+            }
+            names.push(try!(match String::from_utf8(buf) {
+                Ok(val) => Ok(val),
+                Err(_) => Err(ParseError::new(InvalidEncoding, "sub circuit name is not UTF-8")),
+            }));
+        }
+        Ok(CompactCircuit {
+            reader: reader,
+            names: names,
+            node: 1,
+        })
+    }
+
+    fn fail(&self, msg: &str) -> ParseError {
+        ParseError::new(InvalidEncoding, &format!("Node: {} - {}", self.node, msg))
+    }
+
+    fn name(&mut self) -> Result<String, ParseError> {
+        let i = try!(read_varint(&mut self.reader)) as usize;
+        match self.names.get(i) {
+            Some(val) => Ok(val.clone()),
+            None => Err(self.fail(&format!("unknown sub circuit name {}", i))),
+        }
+    }
+
+    fn read_node(&mut self, first: u8) -> Result<Node, ParseError> {
+        let header = try!(read_varint_from(&mut self.reader, first));
+        let id = header >> 3;
+        let (id, gate_type, circuit) = match header & 0x07 {
+            0 => (ID::Input(id), None, None),
+            1 => (ID::Output(id), None, None),
+            2 => (ID::Output(id), None, Some(try!(self.name()))),
+            3 => (ID::Gate(id), Some(GateType::AND), None),
+            4 => (ID::Gate(id), Some(GateType::XOR), None),
+            5 => (ID::Gate(id), Some(GateType::OR), None),
+            6 => (ID::Gate(id), Some(GateType::NOT), None),
+            _ => return Err(self.fail("invalid node kind")),
+        };
+
+        let n = try!(read_varint(&mut self.reader));
+        let mut edges = Vec::new();
+        for _ in 0..n {
+            let edge = try!(read_varint(&mut self.reader));
+            let id = edge >> 2;
+            edges.push(match edge & 0x03 {
+                0 => Edge::new(ID::Output(id), None, None),
+                1 => Edge::new(ID::Gate(id), Some(Pin::Left), None),
+                2 => Edge::new(ID::Gate(id), Some(Pin::Right), None),
+                _ => Edge::new(ID::Input(id), None, Some(try!(self.name()))),
+            });
+        }
+        Ok(Node::new(id, gate_type, circuit, edges))
+    }
+}
+
+/// Writes the `nodes` as compact circuit. Only nodes and edges, which can
+/// be expressed by the text format, can be written.
+pub fn write_compact<W: Write>(writer: &mut W, nodes: &[Node]) -> Result<(), ParseError> {
+    let mut names: Vec<String> = Vec::new();
+    let mut index: HashMap<String, u64> = HashMap::new();
+    {
+        let mut add = |name: Option<String>| {
+            if let Some(name) = name {
+                if !index.contains_key(&name) {
+                    index.insert(name.clone(), names.len() as u64);
+                    names.push(name);
+                }
+            }
+        };
+        for node in nodes {
+            add(node.circuit());
+            for edge in node.edges() {
+                add(edge.circuit());
+            }
+        }
+    }
+
+    let mut buf = Vec::with_capacity(64 * 1024);
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+    write_varint(&mut buf, names.len() as u64);
+    for name in &names {
+        write_varint(&mut buf, name.len() as u64);
+        buf.extend_from_slice(name.as_bytes());
+    }
+    for node in nodes {
+        let kind = match (node.id(), node.gate_type(), node.circuit()) {
+            (ID::Input(_), None, None) => 0,
+            (ID::Output(_), None, None) => 1,
+            (ID::Output(_), None, Some(_)) => 2,
+            (ID::Gate(_), Some(GateType::AND), None) => 3,
+            (ID::Gate(_), Some(GateType::XOR), None) => 4,
+            (ID::Gate(_), Some(GateType::OR), None) => 5,
+            (ID::Gate(_), Some(GateType::NOT), None) => 6,
+            _ => {
+                return Err(ParseError::new(InvalidEncoding,
+                                           &format!("cannot encode node {}", node)))
+            }
+        };
+        let id: u64 = node.id().into();
+        write_varint(&mut buf, id << 3 | kind);
+        if let Some(name) = node.circuit() {
+            write_varint(&mut buf, index[&name]);
+        }
+
+        write_varint(&mut buf, node.edges().len() as u64);
+        for edge in node.edges() {
+            let kind = match (edge.id(), edge.pin(), edge.circuit()) {
+                (ID::Output(_), None, None) => 0,
+                (ID::Gate(_), Some(Pin::Left), None) => 1,
+                (ID::Gate(_), Some(Pin::Right), None) => 2,
+                (ID::Input(_), None, Some(_)) => 3,
+                _ => {
+                    return Err(ParseError::new(InvalidEncoding,
+                                               &format!("cannot encode edge {}", edge)))
+                }
+            };
+            let id: u64 = edge.id().into();
+            write_varint(&mut buf, id << 2 | kind);
+            if let Some(name) = edge.circuit() {
+                write_varint(&mut buf, index[&name]);
+            }
+        }
+        if buf.len() >= 64 * 1024 {
+            try!(writer.write_all(&buf));
+            buf.clear();
+        }
+    }
+    try!(writer.write_all(&buf));
+    try!(writer.flush());
+    Ok(())
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut val: u64) {
+    while val >= 0x80 {
+        buf.push((val as u8) | 0x80);
+        val >>= 7;
+    }
+    buf.push(val as u8);
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> Result<Option<u8>, ParseError> {
+    let mut b = [0u8; 1];
+    loop {
+        return match reader.read(&mut b) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(b[0])),
+            Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => Err(ParseError::from(err)),
+        };
+    }
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), ParseError> {
+    match reader.read_exact(buf) {
+        Ok(_) => Ok(()),
+        Err(ref err) if err.kind() == ErrorKind::UnexpectedEof => {
+            Err(ParseError::new(InvalidEncoding, "unexpected end of circuit"))
+        }
+        Err(err) => Err(ParseError::from(err)),
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, ParseError> {
+    match try!(read_byte(reader)) {
+        Some(first) => read_varint_from(reader, first),
+        None => Err(ParseError::new(InvalidEncoding, "unexpected end of circuit")),
+    }
+}
+
+fn read_varint_from<R: Read>(reader: &mut R, first: u8) -> Result<u64, ParseError> {
+    let (mut val, mut shift, mut b) = ((first & 0x7f) as u64, 7, first);
+    while b & 0x80 != 0 {
+        b = match try!(read_byte(reader)) {
+            Some(val) => val,
+            None => return Err(ParseError::new(InvalidEncoding, "unexpected end of circuit")),
+        };
+        if shift > 63 || (shift == 63 && b & 0x7e != 0) {
+            return Err(ParseError::new(InvalidEncoding, "varint overflows 64 bit"));
+        }
+        val |= ((b & 0x7f) as u64) << shift;
+        shift += 7;
+    }
+    Ok(val)
+}
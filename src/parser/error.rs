@@ -15,6 +15,7 @@ pub enum ErrorType {
     InvalidGate,
     InvalidWire,
     InvalidMetaInfo,
+    InvalidEncoding,
     Unknown,
 }
 
@@ -32,6 +33,7 @@ impl Display for ErrorType {
             ErrorType::InvalidGate => write!(f, "invalid gate"),
             ErrorType::InvalidWire => write!(f, "invalid wire"),
             ErrorType::InvalidMetaInfo => write!(f, "invalid meta info"),
+            ErrorType::InvalidEncoding => write!(f, "invalid compact encoding"),
             ErrorType::Unknown => write!(f, "unknown"),
         }
     }
@@ -1,9 +1,14 @@
 
 pub mod error;
 pub mod types;
+mod compact;
+mod writer;
+
+pub use self::compact::{CompactCircuit, write_compact};
+pub use self::writer::{write_libgc, write_circuit, write_meta_info};
 
 use std::path::{Path, PathBuf};
-use std::io::{BufReader, BufRead, Lines};
+use std::io::{BufReader, BufRead, BufWriter, Lines, Write};
 use std::fs::File;
 use std::default::Default;
 use std::collections::{HashSet, HashMap};
@@ -38,6 +43,7 @@ pub struct MetaInfo {
     pub gates: u64,
     pub one: Option<Node>,
     pub sub_circuits: HashMap<String, String>,
+    pub interface: Interface,
 }
 
 impl Default for MetaInfo {
@@ -49,6 +55,7 @@ impl Default for MetaInfo {
             gates: 0,
             one: None,
             sub_circuits: HashMap::new(),
+            interface: Interface::default(),
         }
     }
 }
@@ -85,14 +92,73 @@ pub fn open_circuit(path: &Path) -> Result<Circuit<BufReader<File>>, ParseError>
     })
 }
 
+pub fn open_compact_circuit(path: &Path) -> Result<CompactCircuit<BufReader<File>>, ParseError> {
+    let p = PathBuf::from(path).join("circuit.bin");
+    CompactCircuit::new(BufReader::new(try!(File::open(p.as_path()))))
+}
+
+/// Nodes iterates over the nodes of a circuit in text or compact form.
+///
+pub enum Nodes<B> {
+    Text(Circuit<B>),
+    Compact(CompactCircuit<B>),
+}
+
+impl<B: BufRead> Iterator for Nodes<B> {
+    type Item = Result<Node, ParseError>;
+
+    fn next(&mut self) -> Option<Result<Node, ParseError>> {
+        match *self {
+            Nodes::Text(ref mut circuit) => circuit.next(),
+            Nodes::Compact(ref mut circuit) => circuit.next(),
+        }
+    }
+}
+
+/// Opens the circuit at `path` - the `circuit.txt` if it exists and the
+/// compact `circuit.bin` otherwise. A `circuit.bin` next to a `circuit.txt`
+/// may be stale, so it is never preferred.
+pub fn read_circuit(path: &Path) -> Result<Nodes<BufReader<File>>, ParseError> {
+    if PathBuf::from(path).join("circuit.txt").is_file() {
+        Ok(Nodes::Text(try!(open_circuit(path))))
+    } else {
+        Ok(Nodes::Compact(try!(open_compact_circuit(path))))
+    }
+}
+
 pub fn parse_circuit(path: &Path) -> Result<Vec<Node>, ParseError> {
     let mut nodes = Vec::new();
-    for node in try!(open_circuit(path)) {
+    for node in try!(read_circuit(path)) {
         nodes.push(try!(node));
     }
     Ok(nodes)
 }
 
+/// Converts the `circuit.txt` at `path` into the compact `circuit.bin`.
+pub fn pack_circuit(path: &Path) -> Result<(), ParseError> {
+    let mut nodes = Vec::new();
+    for node in try!(open_circuit(path)) {
+        nodes.push(try!(node));
+    }
+    let p = PathBuf::from(path).join("circuit.bin");
+    let mut writer = BufWriter::new(try!(File::create(p.as_path())));
+    write_compact(&mut writer, &nodes)
+}
+
+/// Converts the compact `circuit.bin` at `path` into the `circuit.txt`.
+pub fn unpack_circuit(path: &Path) -> Result<(), ParseError> {
+    let circuit = try!(open_compact_circuit(path));
+    let p = PathBuf::from(path).join("circuit.txt");
+    let mut writer = BufWriter::new(try!(File::create(p.as_path())));
+    for node in circuit {
+        try!(writer.write_fmt(format_args!("{}\n", try!(node))));
+    }
+    try!(writer.flush());
+    Ok(())
+}
+
+/// Parses the `meta_info.txt` of the circuit at `path`. Relative sub circuit
+/// paths are resolved against `path` - absolute ones are kept as they are.
 pub fn parse_meta_info(path: &Path) -> Result<MetaInfo, ParseError> {
     let p = PathBuf::from(path).join("meta_info.txt");
     let reader = BufReader::new(try!(File::open(p.as_path())));
@@ -140,14 +206,100 @@ pub fn parse_meta_info(path: &Path) -> Result<MetaInfo, ParseError> {
                 if token == "A" || token == "X" || token == "O" || token == "N" {
                     return Err(ctx.fail(Unknown, ""));
                 }
-                info.sub_circuits.insert(token.to_string(), tokens[1].trim().to_string());
+                // relative sub circuit paths are resolved against the directory
+                // of the meta info file
+                let sub_path = PathBuf::from(path).join(tokens[1].trim());
+                info.sub_circuits.insert(token.to_string(),
+                                         sub_path.to_string_lossy().into_owned());
             }
         }
         ctx.next_line();
     }
+    if PathBuf::from(path).join("interface.txt").is_file() {
+        info.interface = try!(parse_interface(path, &info));
+    }
     Ok(info)
 }
 
+/// Parses the named input and output variables of the circuit at `path`.
+/// Every variable must refer to the inputs and outputs of `info` only.
+pub fn parse_interface(path: &Path, info: &MetaInfo) -> Result<Interface, ParseError> {
+    let p = PathBuf::from(path).join("interface.txt");
+    let reader = BufReader::new(try!(File::open(p.as_path())));
+
+    let mut ctx = Context::new();
+    let mut interface = Interface::default();
+    for line in reader.lines() {
+        let line = try!(line);
+        let line = line.trim();
+        if !line.is_empty() {
+            let var = try!(parse_variable(line, info, &mut ctx));
+            let known = interface.input(var.name()).is_some() ||
+                        interface.output(var.name()).is_some();
+            if known {
+                return Err(ctx.fail(Unknown, &format!("duplicate variable {}", var.name())));
+            }
+            match var.var_type() {
+                Some(_) => interface.outputs.push(var),
+                None => interface.inputs.push(var),
+            };
+        }
+        ctx.next_line();
+    }
+    Ok(interface)
+}
+
+fn parse_variable(line: &str, info: &MetaInfo, ctx: &mut Context) -> Result<Variable, ParseError> {
+    let mut chars = line.chars();
+    let sign = chars.next();
+    let tokens: Vec<&str> = chars.as_str().split_whitespace().collect();
+    if tokens.is_empty() {
+        return Err(ctx.fail(Unknown, "variable without name"));
+    }
+    match sign {
+        Some('+') => {
+            if tokens.len() != 3 {
+                return Err(ctx.fail(Unknown, "expected +name first len"));
+            }
+            let first = try!(match tokens[1].parse::<u64>() {
+                Ok(val) if val > 0 => Ok(val),
+                _ => Err(ctx.fail(InvalidInputID, "input ID is not a number")),
+            });
+            let len = try!(match tokens[2].parse::<u64>() {
+                Ok(val) => Ok(val),
+                Err(_) => Err(ctx.fail(Unknown, "length is not a number")),
+            });
+            match first.checked_add(len) {
+                Some(end) if end - 1 <= info.inputs => Ok(Variable::input(tokens[0], first, len)),
+                _ => {
+                    Err(ctx.fail(InvalidInputID,
+                                 &format!("variable exceeds the {} inputs", info.inputs)))
+                }
+            }
+        }
+        Some('-') => {
+            if tokens.len() < 2 {
+                return Err(ctx.fail(Unknown, "expected -name type id..."));
+            }
+            let mut ids = Vec::with_capacity(tokens.len() - 2);
+            for token in &tokens[2..] {
+                ids.push(try!(match token.parse::<u64>() {
+                    Ok(val) if val > 0 && val <= info.outputs => Ok(val),
+                    Ok(_) => {
+                        Err(ctx.fail(InvalidOutputID,
+                                     &format!("output ID {} is not within 1 and {}",
+                                              token,
+                                              info.outputs)))
+                    }
+                    Err(_) => Err(ctx.fail(InvalidOutputID, "output ID is not a number")),
+                }));
+            }
+            Ok(Variable::output(tokens[0], tokens[1], &ids))
+        }
+        _ => Err(ctx.fail(Unknown, "variable must start with + or -")),
+    }
+}
+
 fn parse_node(line: String, ctx: &mut Context) -> Result<Node, ParseError> {
     let tokens: Vec<&str> = line.split("->").collect();
     if tokens.len() != 2 {
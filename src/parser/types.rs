@@ -285,3 +285,171 @@ impl Node {
         self.edges.extend_from_slice(edges);
     }
 }
+
+/// A Variable is a named group of input or output pins of a circuit - the
+/// `ids` hold the bits of the variable (least significant bit first).
+/// Input variables are consecutive input pins and have no type, output
+/// variables carry the type of the cbmc-gc output mapping, e.g. `INT32`.
+///
+///   - `+INPUT_A_x 1 32`
+///   - `-OUTPUT_sum INT32 1 2 ... 32`
+///
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Variable {
+    name: String,
+    var_type: Option<String>,
+    ids: Vec<ID>,
+}
+
+impl fmt::Display for Variable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.var_type {
+            None => {
+                let first: u64 = match self.ids.first() {
+                    Some(id) => (*id).into(),
+                    None => 1,
+                };
+                write!(f, "+{} {} {}", self.name, first, self.ids.len())
+            }
+            Some(ref var_type) => {
+                try!(write!(f, "-{} {}", self.name, var_type));
+                for id in &self.ids {
+                    let id: u64 = (*id).into();
+                    try!(write!(f, " {}", id));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Variable {
+    /// Returns the input variable of the `len` input pins starting at `first`.
+    pub fn input(name: &str, first: u64, len: u64) -> Variable {
+        Variable {
+            name: name.to_string(),
+            var_type: None,
+            ids: (first..first + len).map(|id| ID::Input(id)).collect(),
+        }
+    }
+
+    pub fn output(name: &str, var_type: &str, ids: &[u64]) -> Variable {
+        Variable {
+            name: name.to_string(),
+            var_type: Some(var_type.to_string()),
+            ids: ids.iter().map(|id| ID::Output(*id)).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    #[inline]
+    pub fn var_type(&self) -> Option<&str> {
+        self.var_type.as_ref().map(|t| t.as_str())
+    }
+
+    #[inline]
+    pub fn ids(&self) -> &[ID] {
+        self.ids.as_slice()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+/// The Interface of a circuit are its named input and output variables.
+/// Circuits without interface file have an empty interface.
+///
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Interface {
+    pub inputs: Vec<Variable>,
+    pub outputs: Vec<Variable>,
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for var in self.inputs.iter().chain(self.outputs.iter()) {
+            try!(write!(f, "{}\n", var));
+        }
+        Ok(())
+    }
+}
+
+impl Interface {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
+    }
+
+    pub fn input(&self, name: &str) -> Option<&Variable> {
+        self.inputs.iter().find(|var| var.name() == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&Variable> {
+        self.outputs.iter().find(|var| var.name() == name)
+    }
+}
+
+/// Value is a primitive integer type (or bool) which can be assigned to
+/// a variable and read back from it. The bits are ordered from the least
+/// to the most significant bit - like the IDs of a variable.
+pub trait Value: Sized {
+    /// Returns the `len` least significant bits of the value. Signed values
+    /// are sign extended if `len` exceeds the width of the type.
+    fn to_bits(&self, len: usize) -> Vec<u8>;
+
+    /// Returns the value of the `bits`. Signed values are sign extended if
+    /// there are less bits than the width of the type.
+    fn from_bits(bits: &[u8]) -> Self;
+}
+
+macro_rules! impl_value {
+    ($t:ty, $signed:expr) => {
+        impl Value for $t {
+            fn to_bits(&self, len: usize) -> Vec<u8> {
+                let val = *self as i128;
+                (0..len).map(|i| ((val >> i.min(127)) & 1) as u8).collect()
+            }
+
+            fn from_bits(bits: &[u8]) -> $t {
+                let mut val: u128 = 0;
+                for (i, bit) in bits.iter().take(128).enumerate() {
+                    val |= ((bit & 0x01) as u128) << i;
+                }
+                let len = bits.len();
+                if $signed && len > 0 && len < 128 && bits[len - 1] & 0x01 == 1 {
+                    val |= !0u128 << len;
+                }
+                val as $t
+            }
+        }
+    };
+}
+
+impl_value!(u8, false);
+impl_value!(u16, false);
+impl_value!(u32, false);
+impl_value!(u64, false);
+impl_value!(i8, true);
+impl_value!(i16, true);
+impl_value!(i32, true);
+impl_value!(i64, true);
+
+impl Value for bool {
+    fn to_bits(&self, len: usize) -> Vec<u8> {
+        let mut bits = vec![0; len];
+        if len > 0 && *self {
+            bits[0] = 1;
+        }
+        bits
+    }
+
+    fn from_bits(bits: &[u8]) -> bool {
+        bits.iter().any(|bit| bit & 0x01 == 1)
+    }
+}
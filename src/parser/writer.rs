@@ -0,0 +1,173 @@
+use std::env;
+use std::fs::{self, File};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::io::{BufWriter, Write};
+
+use super::MetaInfo;
+use super::error::ParseError;
+use super::error::ErrorType::*;
+use super::types::*;
+
+impl fmt::Display for MetaInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dst = MetaInfoAt {
+            info: self,
+            dst: self.path.as_path(),
+            cwd: None,
+        };
+        write!(f, "{}", dst)
+    }
+}
+
+/// MetaInfoAt formats a meta info, which is written to the directory `dst`.
+/// Sub circuits within `dst` are written relative to it, so that the
+/// directory can be moved - all others are written as absolute paths if
+/// `dst` is not the directory of the circuit. Relative paths are resolved
+/// against `cwd` then.
+///
+struct MetaInfoAt<'a> {
+    info: &'a MetaInfo,
+    dst: &'a Path,
+    cwd: Option<PathBuf>,
+}
+
+impl<'a> fmt::Display for MetaInfoAt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let info = self.info;
+        try!(write!(f,
+                    "INPUTS = {}\nGATES = {}\nOUTPUTS = {}",
+                    info.inputs,
+                    info.gates,
+                    info.outputs));
+        if let Some(ref one) = info.one {
+            let edges: Vec<String> = one.edges().iter().map(|edge| format!("{}", edge)).collect();
+            try!(write!(f, "\nONE = {}", edges.join(" ")));
+        }
+        let mut keys: Vec<&String> = info.sub_circuits.keys().collect();
+        keys.sort();
+        for key in keys {
+            let sub = Path::new(&info.sub_circuits[key]);
+            let sub = match sub.strip_prefix(self.dst) {
+                Ok(rel) if !self.dst.as_os_str().is_empty() => rel.to_path_buf(),
+                _ if self.dst == info.path.as_path() || sub.is_absolute() => sub.to_path_buf(),
+                _ => {
+                    match self.cwd {
+                        Some(ref cwd) => cwd.join(sub),
+                        None => sub.to_path_buf(),
+                    }
+                }
+            };
+            try!(write!(f, "\n{} = {}", key, sub.display()));
+        }
+        Ok(())
+    }
+}
+
+/// Writes the libgc circuit `nodes` described by `info` into the directory
+/// `path` - it is created if it doesn't exist. The nodes and the meta info
+/// are verified before anything is written. The `interface.txt` is only
+/// written if the circuit has named variables. Sub circuits are referenced
+/// by `info.sub_circuits`, but not written.
+pub fn write_libgc(path: &Path, info: &MetaInfo, nodes: &[Node]) -> Result<(), ParseError> {
+    try!(verify(info, nodes));
+    if !path.is_dir() {
+        try!(fs::create_dir_all(path));
+    }
+    try!(write_circuit(path, nodes));
+    write_meta_info(path, info)
+}
+
+/// Writes the `nodes` into the `circuit.txt` at `path`.
+pub fn write_circuit(path: &Path, nodes: &[Node]) -> Result<(), ParseError> {
+    let p = PathBuf::from(path).join("circuit.txt");
+    let mut writer = BufWriter::new(try!(File::create(p.as_path())));
+    for node in nodes {
+        try!(writer.write_fmt(format_args!("{}\n", node)));
+    }
+    try!(writer.flush());
+    Ok(())
+}
+
+/// Writes the `meta_info.txt` and the `interface.txt` (if there are named
+/// variables) of `info` into `path`. The sub circuits keep referring to the
+/// same directories - relative to `path` if they are within it and absolute
+/// otherwise.
+pub fn write_meta_info(path: &Path, info: &MetaInfo) -> Result<(), ParseError> {
+    let dst = MetaInfoAt {
+        info: info,
+        dst: path,
+        cwd: Some(try!(env::current_dir())),
+    };
+    let p = PathBuf::from(path).join("meta_info.txt");
+    let mut writer = BufWriter::new(try!(File::create(p.as_path())));
+    try!(writer.write_fmt(format_args!("{}", dst)));
+    try!(writer.flush());
+
+    let p = PathBuf::from(path).join("interface.txt");
+    if !info.interface.is_empty() {
+        let mut writer = BufWriter::new(try!(File::create(p.as_path())));
+        try!(writer.write_fmt(format_args!("{}", info.interface)));
+        try!(writer.flush());
+    } else if p.is_file() {
+        try!(fs::remove_file(p.as_path()));
+    }
+    Ok(())
+}
+
+/// Checks that the `nodes` and `info` can be expressed by the libgc format
+/// and be parsed again.
+fn verify(info: &MetaInfo, nodes: &[Node]) -> Result<(), ParseError> {
+    for key in info.sub_circuits.keys() {
+        let reserved = ["INPUTS", "OUTPUTS", "GATES", "ONE", "A", "X", "O", "N"];
+        let invalid = key.is_empty() || reserved.contains(&key.as_str()) ||
+                      key.starts_with(|c: char| c.is_digit(10)) ||
+                      key.contains(|c: char| c.is_whitespace() || c == '=' || c == ':');
+        if invalid {
+            return Err(ParseError::new(InvalidMetaInfo,
+                                       &format!("invalid sub circuit name: {}", key)));
+        }
+    }
+    let check_circuit = |name: Option<String>| match name {
+        Some(ref key) if !info.sub_circuits.contains_key(key) => {
+            Err(ParseError::new(InvalidMetaInfo, &format!("unknown sub circuit: {}", key)))
+        }
+        _ => Ok(()),
+    };
+
+    if let Some(ref one) = info.one {
+        if one.edges().is_empty() {
+            return Err(ParseError::new(InvalidMetaInfo, "ONE must have at least one edge"));
+        }
+        for edge in one.edges() {
+            try!(verify_edge(edge));
+            try!(check_circuit(edge.circuit()));
+        }
+    }
+    for node in nodes {
+        let valid = match (node.id(), node.gate_type(), node.circuit()) {
+            (ID::Input(id), None, None) => id > 0 && id <= info.inputs,
+            (ID::Output(id), None, _) => id > 0,
+            (ID::Gate(_), Some(_), None) => true,
+            _ => false,
+        };
+        if !valid || node.edges().is_empty() {
+            return Err(ParseError::new(InvalidGate, &format!("cannot write node: {}", node)));
+        }
+        try!(check_circuit(node.circuit()));
+        for edge in node.edges() {
+            try!(verify_edge(edge));
+            try!(check_circuit(edge.circuit()));
+        }
+    }
+    Ok(())
+}
+
+fn verify_edge(edge: &Edge) -> Result<(), ParseError> {
+    match (edge.id(), edge.pin(), edge.circuit()) {
+        (ID::Output(id), None, None) if id > 0 => Ok(()),
+        (ID::Gate(_), Some(_), None) => Ok(()),
+        (ID::Input(id), None, Some(_)) if id > 0 => Ok(()),
+        _ => Err(ParseError::new(InvalidWire, &format!("cannot write edge: {}", edge))),
+    }
+}
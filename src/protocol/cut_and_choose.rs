@@ -0,0 +1,683 @@
+use std::fs;
+use std::env;
+use std::io::{self, Read};
+use std::path::Path;
+use std::collections::{HashMap, HashSet};
+
+use rand::{Rng, ChaChaRng, SeedableRng};
+
+use super::{Assignment, check_inputs, send_ids, recv_ids};
+use super::error::ProtocolError;
+use super::super::channel::Channel;
+use super::super::circuit::builder::{CircuitBuilder, Wire};
+use super::super::circuit::garbled::{Garbler, Evaluator, Encoding, Label, LabelPair, Scheme,
+                                     FixedKeyAes};
+use super::super::circuit::garbled::format::{Header, Reader, Writer};
+use super::super::crypto::sha256::{Sha256, DIGEST_SIZE};
+use super::super::ot::{Sender, Receiver, IknpSender, IknpReceiver};
+use super::super::parser;
+use super::super::parser::MetaInfo;
+
+/// The size of the seed of a garbling in bytes.
+const SEED_SIZE: usize = 32;
+
+/// The number of random shares of every evaluator input. A selective
+/// failure attack on the OT reveals an input bit with a probability of at
+/// most 2^-(SHARES - 1).
+const SHARES: usize = 40;
+
+/// The number of bits of the hash of the garbler's inputs. Garblings with
+/// different garbler inputs have the same hash with a probability of
+/// 2^-HASH_BITS.
+const HASH_BITS: usize = 40;
+
+type Seed = [u8; SEED_SIZE];
+type Commitment = [u8; DIGEST_SIZE];
+
+fn seeded_rng(seed: &Seed) -> ChaChaRng {
+    let mut key = [0u32; SEED_SIZE / 4];
+    for (i, word) in key.iter_mut().enumerate() {
+        *word = ((seed[4 * i] as u32) << 24) | ((seed[4 * i + 1] as u32) << 16) |
+                ((seed[4 * i + 2] as u32) << 8) | (seed[4 * i + 3] as u32);
+    }
+    ChaChaRng::from_seed(&key[..])
+}
+
+/// Returns the seed of the randomness of the input commitments of the
+/// garbling with the seed `seed` - independent of the garbling itself.
+fn commitment_seed(seed: &Seed) -> Seed {
+    let mut h = Sha256::new();
+    h.update(b"input commitments");
+    h.update(seed);
+    h.finish()
+}
+
+/// Garbles the circuit with the randomness derived from `seed` and returns
+/// the encoding and the commitment - the SHA-256 digest of the encoded
+/// garbled circuit followed by the hashes of its output labels.
+fn commit(garbler: &mut Garbler, seed: &Seed) -> Result<(Encoding, Commitment), ProtocolError> {
+    let header = Header::new(garbler.scheme(), garbler.info());
+    let mut writer = try!(Writer::new(Sha256::new(), &header));
+    let encoding = try!(garbler.garble_into(&mut seeded_rng(seed),
+                                            &FixedKeyAes::default(),
+                                            &mut writer));
+    let mut hash = try!(writer.finish(&encoding.decoding()));
+    hash.update(&output_hashes(&encoding.outputs));
+    Ok((encoding, hash.finish()))
+}
+
+/// Returns the hashes of the 0- and the 1-label of every output in the
+/// order of the output IDs. The evaluator rejects an output label which
+/// doesn't match them before anything is sent back to the garbler.
+fn output_hashes(outputs: &HashMap<u64, LabelPair>) -> Vec<u8> {
+    let mut hashes = Vec::with_capacity(2 * DIGEST_SIZE * outputs.len());
+    for id in 1..outputs.len() as u64 + 1 {
+        let pair = outputs[&id];
+        hashes.extend_from_slice(&Sha256::digest(&pair.0.to_bytes()));
+        hashes.extend_from_slice(&Sha256::digest(&pair.1.to_bytes()));
+    }
+    hashes
+}
+
+/// Returns the commitment to an input label - the random nonce hides the
+/// label from the evaluator.
+fn commit_label(nonce: Label, label: Label) -> Commitment {
+    let mut h = Sha256::new();
+    h.update(&nonce.to_bytes());
+    h.update(&label.to_bytes());
+    h.finish()
+}
+
+/// InputCommitment commits to both labels of an input of the garbler in a
+/// random order - the label of the value `b` is committed at the position
+/// `b ^ flip`. The order and the nonces are derived from the seed of the
+/// garbling, so the evaluator can recompute the commitments of opened
+/// garblings.
+///
+struct InputCommitment {
+    flip: u8,
+    nonces: [Label; 2],
+    commitments: [Commitment; 2],
+}
+
+/// Returns the commitments to the first `own` inputs of the garbling with
+/// the seed `seed` and the input labels `inputs`.
+fn commit_inputs(seed: &Seed,
+                 inputs: &HashMap<u64, LabelPair>,
+                 own: usize)
+                 -> Result<Vec<InputCommitment>, ProtocolError> {
+    let mut rng = seeded_rng(&commitment_seed(seed));
+    let mut commitments = Vec::with_capacity(own);
+    for id in 1..own as u64 + 1 {
+        let pair = match inputs.get(&id) {
+            Some(pair) => *pair,
+            None => return Err(ProtocolError::from(format!("missing input {}", id))),
+        };
+        let flip = rng.gen::<u8>() & 1;
+        let nonces = [Label::random(&mut rng), Label::random(&mut rng)];
+        let labels = if flip == 0 { [pair.0, pair.1] } else { [pair.1, pair.0] };
+        commitments.push(InputCommitment {
+            flip: flip,
+            nonces: nonces,
+            commitments: [commit_label(nonces[0], labels[0]), commit_label(nonces[1], labels[1])],
+        });
+    }
+    Ok(commitments)
+}
+
+/// HashReader computes the SHA-256 digest of all bytes read from `r`.
+struct HashReader<R: Read> {
+    r: R,
+    hash: Sha256,
+}
+
+impl<R: Read> Read for HashReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = try!(self.r.read(buf));
+        self.hash.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Layout describes the inputs of the circuit which is actually garbled by
+/// the cut-and-choose protocol:
+///
+///   1. the garbler's inputs x (in the order of the assignment)
+///   2. a random pad r of HASH_BITS bits chosen by the garbler
+///   3. SHARES random shares for every evaluator input - their XOR is the
+///      input (in the order of the assignment)
+///
+/// Its outputs are the ones of the original circuit - used as sub circuit -
+/// followed by the hash A * x + r of the garbler's inputs for a random
+/// binary matrix A chosen by the evaluator. The garbler has no pad and no
+/// hash if it has no inputs.
+///
+struct Layout {
+    garbler: usize,
+    pad: usize,
+    shares: usize,
+}
+
+impl Layout {
+    fn new(assignment: &Assignment) -> Layout {
+        let garbler = assignment.garbler().len();
+        Layout {
+            garbler: garbler,
+            pad: if garbler > 0 { HASH_BITS } else { 0 },
+            shares: SHARES * assignment.evaluator().len(),
+        }
+    }
+
+    /// Returns the number of inputs of the garbler - its inputs and the pad.
+    fn own(&self) -> usize {
+        self.garbler + self.pad
+    }
+
+    fn inputs(&self) -> usize {
+        self.own() + self.shares
+    }
+
+    /// Writes the circuit for the original circuit `info` and the matrix
+    /// derived from `matrix` to `path` and returns its meta info.
+    fn write(&self,
+             path: &Path,
+             info: &MetaInfo,
+             assignment: &Assignment,
+             matrix: &Seed)
+             -> Result<MetaInfo, ProtocolError> {
+        let mut b = CircuitBuilder::new();
+        let x = b.inputs(self.garbler);
+        let r = b.inputs(self.pad);
+        let shares = b.inputs(self.shares);
+
+        let mut inputs: Vec<Option<Wire>> = vec![None; info.inputs as usize];
+        for (id, wire) in assignment.garbler().iter().zip(x.iter()) {
+            inputs[*id as usize - 1] = Some(*wire);
+        }
+        for (id, shares) in assignment.evaluator().iter().zip(shares.chunks(SHARES)) {
+            let mut y = shares[0];
+            for share in &shares[1..] {
+                y = b.xor(y, *share);
+            }
+            inputs[*id as usize - 1] = Some(y);
+        }
+        let mut wires = Vec::with_capacity(inputs.len());
+        for (i, wire) in inputs.into_iter().enumerate() {
+            match wire {
+                Some(wire) => wires.push(wire),
+                None => return Err(ProtocolError::from(format!("input {} is not assigned", i + 1))),
+            }
+        }
+        for output in try!(b.sub_circuit(info.path.as_path(), &wires)) {
+            b.output(output);
+        }
+
+        let mut rng = seeded_rng(matrix);
+        for pad in &r {
+            let mut h = *pad;
+            for xi in &x {
+                if rng.gen::<bool>() {
+                    h = b.xor(h, *xi);
+                }
+            }
+            b.output(h);
+        }
+        let (meta, nodes) = b.build();
+        try!(parser::write_libgc(path, &meta, &nodes));
+        Ok(try!(parser::parse_meta_info(path)))
+    }
+}
+
+/// Returns a new temporary directory path for the garbled circuit.
+fn temp_path<R: Rng>(rng: &mut R) -> ::std::path::PathBuf {
+    env::temp_dir().join(format!("libgc-cut-and-choose-{:016x}", rng.gen::<u64>()))
+}
+
+/// CutAndChooseGarbler runs the garbler's side of a two-party computation
+/// which is secure against a malicious garbler (cut-and-choose):
+///
+///   1. commit to both labels of the own inputs and of a random pad in every
+///      garbling - every garbling with its own random seed
+///   2. transfer the labels of the shares of the evaluator's inputs of all
+///      garblings using OT
+///   3. receive the hash matrix, garble the circuit `circuits` times and
+///      commit to the garbled circuits and the hashes of their output labels
+///   4. open the garblings chosen by the evaluator by revealing their seeds
+///   5. open the labels of the own inputs for all other garblings and send
+///      these garbled circuits
+///   6. receive and decode the output labels of the first evaluated garbling
+///
+/// The evaluator checks that the opened garblings, their input commitments,
+/// their committed garbled circuits and the labels it obtained by OT match
+/// the seeds. Its inputs are split into random shares, so that a garbler who
+/// corrupts the OT learns nothing but random bits. The garbler's inputs are
+/// bound by the label commitments before the hash is chosen, so evaluated
+/// garblings with different garbler inputs yield different hashes. The
+/// evaluator aborts if an output label doesn't match its committed hash or
+/// if the evaluated garblings disagree - it never reveals which garbling an
+/// output came from. A cheating garbler remains undetected only if none of
+/// the garblings it cheated in is opened - the number of garblings is the
+/// statistical security parameter.
+///
+/// The garbled circuits are regarbled from their seeds when they are sent,
+/// so only the input and output labels of every garbling are kept in memory.
+/// The circuit, which is actually garbled, is written to a temporary
+/// directory.
+///
+pub struct CutAndChooseGarbler {
+    info: MetaInfo,
+    scheme: Scheme,
+    circuits: usize,
+    assignment: Assignment,
+}
+
+impl CutAndChooseGarbler {
+    pub fn new(info: MetaInfo,
+               assignment: Assignment,
+               scheme: Scheme,
+               circuits: usize)
+               -> Result<CutAndChooseGarbler, ProtocolError> {
+        try!(assignment.check(info.inputs));
+        if circuits < 2 {
+            return Err(ProtocolError::from("cut-and-choose requires at least two garblings"));
+        }
+        try!(Garbler::with_scheme(try!(parser::parse_meta_info(info.path.as_path())), scheme));
+        Ok(CutAndChooseGarbler {
+            info: info,
+            scheme: scheme,
+            circuits: circuits,
+            assignment: assignment,
+        })
+    }
+
+    /// Runs the protocol with the given garbler inputs (input ID -> value)
+    /// and returns the output bits in the order of the output IDs.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, ProtocolError> {
+        try!(check_inputs(self.assignment.garbler(), inputs));
+        try!(ch.send_u64(self.info.inputs));
+        try!(send_ids(ch, self.assignment.garbler()));
+        try!(ch.send_u64(self.circuits as u64));
+        try!(ch.send_u8(self.scheme.id()));
+        try!(ch.flush());
+        if try!(ch.recv_u8()) != 1 {
+            return Err(ProtocolError::from("evaluator rejected the protocol parameters"));
+        }
+
+        let layout = Layout::new(&self.assignment);
+        let mut own: Vec<u8> = self.assignment.garbler().iter().map(|id| inputs[id]).collect();
+        for _ in 0..layout.pad {
+            own.push(rng.gen::<u8>() & 1);
+        }
+
+        let mut seeds = Vec::with_capacity(self.circuits);
+        let mut commitments = Vec::with_capacity(self.circuits);
+        let mut messages = Vec::with_capacity(self.circuits * layout.shares);
+        for _ in 0..self.circuits {
+            let mut seed = [0u8; SEED_SIZE];
+            rng.fill_bytes(&mut seed);
+            let labels = Garbler::input_labels(self.scheme,
+                                               layout.inputs() as u64,
+                                               &mut seeded_rng(&seed));
+            let c = try!(commit_inputs(&seed, &labels, layout.own()));
+            for input in &c {
+                try!(ch.send_bytes(&input.commitments[0]));
+                try!(ch.send_bytes(&input.commitments[1]));
+            }
+            for id in layout.own()..layout.inputs() {
+                messages.push(labels[&(id as u64 + 1)]);
+            }
+            seeds.push(seed);
+            commitments.push(c);
+        }
+        try!(ch.flush());
+        try!(IknpSender::new().send(ch, rng, &messages));
+
+        let mut matrix = [0u8; SEED_SIZE];
+        try!(ch.read_exact(&mut matrix));
+        let path = temp_path(rng);
+        let result = self.garble(ch, &layout, path.as_path(), &matrix, &seeds, &commitments, &own);
+        let _ = fs::remove_dir_all(path.as_path());
+        result
+    }
+
+    fn garble<C: Channel>(&self,
+                          ch: &mut C,
+                          layout: &Layout,
+                          path: &Path,
+                          matrix: &Seed,
+                          seeds: &[Seed],
+                          commitments: &[Vec<InputCommitment>],
+                          own: &[u8])
+                          -> Result<Vec<u8>, ProtocolError> {
+        let info = try!(layout.write(path, &self.info, &self.assignment, matrix));
+        let mut garbler = try!(Garbler::with_scheme(info, self.scheme));
+        let mut encodings = Vec::with_capacity(self.circuits);
+        for seed in seeds {
+            let (encoding, commitment) = try!(commit(&mut garbler, seed));
+            try!(ch.send_bytes(&commitment));
+            encodings.push(encoding);
+        }
+        try!(ch.flush());
+
+        let checks = try!(recv_ids(ch, self.circuits as u64));
+        let mut opened = HashSet::with_capacity(checks.len());
+        for i in &checks {
+            if *i >= self.circuits as u64 || !opened.insert(*i as usize) {
+                return Err(ProtocolError::from(format!("invalid garbling to open: {}", i)));
+            }
+        }
+        if opened.len() == self.circuits {
+            return Err(ProtocolError::from("evaluator wants to open all garblings"));
+        }
+        for i in &checks {
+            try!(ch.send_bytes(&seeds[*i as usize]));
+        }
+        let evaluated: Vec<usize> = (0..self.circuits).filter(|i| !opened.contains(i)).collect();
+
+        for i in &evaluated {
+            for (j, bit) in own.iter().enumerate() {
+                let input = &commitments[*i][j];
+                let position = bit ^ input.flip;
+                try!(ch.send_u8(position));
+                try!(ch.send_label(input.nonces[position as usize]));
+                try!(ch.send_label(encodings[*i].encode(j as u64 + 1, *bit).unwrap()));
+            }
+        }
+        for i in &evaluated {
+            let header = Header::new(garbler.scheme(), garbler.info());
+            let mut writer = try!(Writer::new(&mut *ch, &header));
+            let encoding = try!(garbler.garble_into(&mut seeded_rng(&seeds[*i]),
+                                                    &FixedKeyAes::default(),
+                                                    &mut writer));
+            try!(try!(writer.finish(&encoding.decoding()))
+                .send_bytes(&output_hashes(&encoding.outputs)));
+        }
+        try!(ch.flush());
+
+        // the evaluator returns the output labels of the first evaluated
+        // garbling - all evaluated garblings have the same output
+        let encoding = &encodings[evaluated[0]];
+        let outputs = encoding.outputs.len() - layout.pad;
+        let n = try!(ch.recv_u64());
+        if n != outputs as u64 {
+            return Err(ProtocolError::from(format!("expected {} outputs - got {}", outputs, n)));
+        }
+        let mut output = Vec::with_capacity(outputs);
+        for id in 1..n + 1 {
+            let label = try!(ch.recv_label());
+            let pair = match encoding.outputs.get(&id) {
+                Some(pair) => *pair,
+                None => return Err(ProtocolError::from(format!("missing output {}", id))),
+            };
+            if label == pair.0 {
+                output.push(0);
+            } else if label == pair.1 {
+                output.push(1);
+            } else {
+                return Err(ProtocolError::from(format!("invalid label for output {}", id)));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// CutAndChooseEvaluator runs the evaluator's side of the cut-and-choose
+/// protocol. It checks `checks` randomly chosen garblings, their input
+/// commitments and the labels it obtained for them by OT against a
+/// regarbling from their seeds, evaluates all others and returns their
+/// common output. The garbler is caught cheating if an opened garbling, an
+/// evaluated garbled circuit, an opened input label or an output label
+/// doesn't match its commitment, if the OT of an opened garbling doesn't
+/// match its seed or if the outputs - including the hashes of the garbler's
+/// inputs - of the evaluated garblings differ.
+///
+pub struct CutAndChooseEvaluator {
+    info: MetaInfo,
+    circuits: usize,
+    checks: usize,
+    assignment: Assignment,
+}
+
+impl CutAndChooseEvaluator {
+    pub fn new(info: MetaInfo,
+               assignment: Assignment,
+               circuits: usize,
+               checks: usize)
+               -> Result<CutAndChooseEvaluator, ProtocolError> {
+        try!(assignment.check(info.inputs));
+        if checks == 0 || checks >= circuits {
+            return Err(ProtocolError::from(format!("cannot open {} of {} garblings",
+                                                   checks,
+                                                   circuits)));
+        }
+        try!(Evaluator::new(try!(parser::parse_meta_info(info.path.as_path()))));
+        Ok(CutAndChooseEvaluator {
+            info: info,
+            circuits: circuits,
+            checks: checks,
+            assignment: assignment,
+        })
+    }
+
+    /// Runs the protocol with the given evaluator inputs (input ID -> value)
+    /// and returns the output bits in the order of the output IDs.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, ProtocolError> {
+        try!(check_inputs(self.assignment.evaluator(), inputs));
+        let n = try!(ch.recv_u64());
+        let ids = try!(recv_ids(ch, self.info.inputs));
+        let circuits = try!(ch.recv_u64());
+        let scheme = Scheme::from_id(try!(ch.recv_u8()));
+        if n != self.info.inputs || ids.as_slice() != self.assignment.garbler() ||
+           circuits != self.circuits as u64 || scheme.is_none() {
+            try!(ch.send_u8(0));
+            try!(ch.flush());
+            return Err(ProtocolError::from("garbler uses different protocol parameters"));
+        }
+        let scheme = scheme.unwrap();
+        try!(ch.send_u8(1));
+        try!(ch.flush());
+
+        let layout = Layout::new(&self.assignment);
+        let mut labels = Vec::with_capacity(self.circuits);
+        for _ in 0..self.circuits {
+            let mut l = Vec::with_capacity(layout.own());
+            for _ in 0..layout.own() {
+                let mut commitments = [[0u8; DIGEST_SIZE]; 2];
+                try!(ch.read_exact(&mut commitments[0]));
+                try!(ch.read_exact(&mut commitments[1]));
+                l.push(commitments);
+            }
+            labels.push(l);
+        }
+
+        // the same random shares are used for all garblings
+        let mut shares = Vec::with_capacity(layout.shares);
+        for id in self.assignment.evaluator() {
+            let mut last = inputs[id] & 1;
+            for _ in 1..SHARES {
+                let share = rng.gen::<u8>() & 1;
+                last ^= share;
+                shares.push(share);
+            }
+            shares.push(last);
+        }
+        let mut choices = Vec::with_capacity(self.circuits * shares.len());
+        for _ in 0..self.circuits {
+            choices.extend_from_slice(&shares);
+        }
+        let received = try!(IknpReceiver::new().receive(ch, rng, &choices));
+
+        let mut matrix = [0u8; SEED_SIZE];
+        rng.fill_bytes(&mut matrix);
+        try!(ch.send_bytes(&matrix));
+        try!(ch.flush());
+        let path = temp_path(rng);
+        let result = self.evaluate(ch,
+                                   rng,
+                                   scheme,
+                                   &layout,
+                                   path.as_path(),
+                                   &matrix,
+                                   &labels,
+                                   &shares,
+                                   &received);
+        let _ = fs::remove_dir_all(path.as_path());
+        result
+    }
+
+    fn evaluate<C: Channel, R: Rng>(&self,
+                                    ch: &mut C,
+                                    rng: &mut R,
+                                    scheme: Scheme,
+                                    layout: &Layout,
+                                    path: &Path,
+                                    matrix: &Seed,
+                                    commitments: &[Vec<[Commitment; 2]>],
+                                    shares: &[u8],
+                                    received: &[Label])
+                                    -> Result<Vec<u8>, ProtocolError> {
+        let info = try!(layout.write(path, &self.info, &self.assignment, matrix));
+        let mut evaluator = try!(Evaluator::new(info));
+        let mut garbler = try!(Garbler::with_scheme(try!(parser::parse_meta_info(path)), scheme));
+
+        let mut garblings = Vec::with_capacity(self.circuits);
+        for _ in 0..self.circuits {
+            let mut commitment = [0u8; DIGEST_SIZE];
+            try!(ch.read_exact(&mut commitment));
+            garblings.push(commitment);
+        }
+
+        let mut indices: Vec<usize> = (0..self.circuits).collect();
+        rng.shuffle(&mut indices);
+        let mut checks: Vec<u64> = indices[..self.checks].iter().map(|i| *i as u64).collect();
+        checks.sort();
+        try!(send_ids(ch, &checks));
+        try!(ch.flush());
+
+        for i in &checks {
+            let i = *i as usize;
+            let mut seed = [0u8; SEED_SIZE];
+            try!(ch.read_exact(&mut seed));
+            let (encoding, commitment) = try!(commit(&mut garbler, &seed));
+            if commitment != garblings[i] {
+                return Err(ProtocolError::from(format!("garbler cheated: garbling {} doesn't \
+                                                        match its commitment",
+                                                       i)));
+            }
+            let inputs = try!(commit_inputs(&seed, &encoding.inputs, layout.own()));
+            for (j, input) in inputs.iter().enumerate() {
+                if input.commitments != commitments[i][j] {
+                    return Err(ProtocolError::from(format!("garbler cheated: the input \
+                                                            commitments of garbling {} don't \
+                                                            match its seed",
+                                                           i)));
+                }
+            }
+            for (j, share) in shares.iter().enumerate() {
+                let id = (layout.own() + j) as u64 + 1;
+                if encoding.encode(id, *share) != Some(received[i * shares.len() + j]) {
+                    return Err(ProtocolError::from(format!("garbler cheated: the OT of \
+                                                            garbling {} doesn't match its seed",
+                                                           i)));
+                }
+            }
+        }
+        let evaluated: Vec<usize> = (0..self.circuits)
+            .filter(|i| checks.binary_search(&(*i as u64)).is_err())
+            .collect();
+
+        let mut labels: Vec<HashMap<u64, Label>> = Vec::with_capacity(evaluated.len());
+        for i in &evaluated {
+            let mut l = HashMap::with_capacity(layout.inputs());
+            for j in 0..layout.own() {
+                let position = try!(ch.recv_u8());
+                let nonce = try!(ch.recv_label());
+                let label = try!(ch.recv_label());
+                if position > 1 ||
+                   commit_label(nonce, label) != commitments[*i][j][position as usize] {
+                    return Err(ProtocolError::from(format!("garbler cheated: input label {} \
+                                                            of garbling {} doesn't match its \
+                                                            commitment",
+                                                           j + 1,
+                                                           i)));
+                }
+                l.insert(j as u64 + 1, label);
+            }
+            for j in 0..shares.len() {
+                l.insert((layout.own() + j) as u64 + 1, received[*i * shares.len() + j]);
+            }
+            labels.push(l);
+        }
+
+        // all evaluated garblings must have the same output - the output
+        // labels of the first one are sent back
+        let mut result: Option<Vec<u8>> = None;
+        let mut output_labels = Vec::new();
+        for (j, i) in evaluated.iter().enumerate() {
+            let mut reader = try!(Reader::open(HashReader {
+                                                   r: &mut *ch,
+                                                   hash: Sha256::new(),
+                                               },
+                                               evaluator.info()));
+            if reader.header().scheme != scheme {
+                return Err(ProtocolError::from(format!("garbling {} uses a different scheme",
+                                                       i)));
+            }
+            try!(evaluator.evaluate_from(scheme, &mut reader, &labels[j], &FixedKeyAes::default()));
+            let decoding = try!(reader.read_decoding());
+            let bits = try!(evaluator.decode(&decoding));
+            let mut hashes = vec![0u8; 2 * DIGEST_SIZE * bits.len()];
+            let mut r = reader.into_inner();
+            try!(r.read_exact(&mut hashes));
+            if r.hash.finish() != garblings[*i] {
+                return Err(ProtocolError::from(format!("garbler cheated: garbling {} doesn't \
+                                                        match its commitment",
+                                                       i)));
+            }
+            for (k, bit) in bits.iter().enumerate() {
+                let label = try!(evaluator.get_output(k as u64 + 1));
+                let offset = (2 * k + *bit as usize) * DIGEST_SIZE;
+                if Sha256::digest(&label.to_bytes())[..] != hashes[offset..offset + DIGEST_SIZE] {
+                    return Err(ProtocolError::from(format!("garbler cheated: invalid label for \
+                                                            output {} of garbling {}",
+                                                           k + 1,
+                                                           i)));
+                }
+            }
+            if bits.len() < layout.pad {
+                return Err(ProtocolError::from(format!("garbling {} has too few outputs", i)));
+            }
+            match result {
+                Some(ref result) if *result != bits => {
+                    return Err(ProtocolError::from("garbler cheated: the outputs of the \
+                                                    evaluated garblings differ"));
+                }
+                Some(_) => (),
+                None => {
+                    for id in 1..(bits.len() - layout.pad) as u64 + 1 {
+                        output_labels.push(try!(evaluator.get_output(id)));
+                    }
+                    result = Some(bits);
+                }
+            }
+        }
+
+        let mut output = result.unwrap();
+        let outputs = output.len() - layout.pad;
+        output.truncate(outputs);
+        try!(ch.send_u64(outputs as u64));
+        for label in &output_labels {
+            try!(ch.send_label(*label));
+        }
+        try!(ch.flush());
+        Ok(output)
+    }
+}
@@ -0,0 +1,231 @@
+use std::fs;
+use std::path::Path;
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::{Assignment, GarblerParty, EvaluatorParty};
+use super::error::ProtocolError;
+use super::super::cbmc::Party;
+use super::super::channel::Channel;
+use super::super::circuit::garbled::Scheme;
+use super::super::parser;
+use super::super::parser::MetaInfo;
+use super::super::parser::types::{Node, Edge, ID, Pin, GateType};
+
+/// Writes a libgc circuit which compares two `bits`-bit numbers to the
+/// directory `path` (it is created if it doesn't exist). The inputs 1 to
+/// `bits` are the first number, the inputs `bits + 1` to `2 * bits` the
+/// second one. The only output is 1 if both numbers are equal and 0
+/// otherwise:
+///
+///   NOT ((x1 XOR y1) OR (x2 XOR y2) OR ... OR (xn XOR yn))
+///
+pub fn write_equality_circuit(path: &Path, bits: u64) -> Result<(), ProtocolError> {
+    if bits == 0 {
+        return Err(ProtocolError::from("cannot compare numbers of 0 bits"));
+    }
+
+    // gates 1..n: XOR of the input bits, n+1..2n-1: OR chain, 2n: NOT
+    let (xor, or, not) = (0, bits, 2 * bits);
+    let mut nodes = Vec::with_capacity(4 * bits as usize);
+    for i in 1..bits + 1 {
+        let edges = vec![Edge::new(ID::Gate(xor + i), Some(Pin::Left), None)];
+        nodes.push(Node::new(ID::Input(i), None, None, edges));
+    }
+    for i in 1..bits + 1 {
+        let edges = vec![Edge::new(ID::Gate(xor + i), Some(Pin::Right), None)];
+        nodes.push(Node::new(ID::Input(bits + i), None, None, edges));
+    }
+    for i in 1..bits + 1 {
+        // the first XOR is the left operand of the first OR (or the NOT)
+        // and every other XOR the right operand of an OR.
+        let edge = match i {
+            1 if bits == 1 => Edge::new(ID::Gate(not), Some(Pin::Left), None),
+            1 => Edge::new(ID::Gate(or + 1), Some(Pin::Left), None),
+            _ => Edge::new(ID::Gate(or + i - 1), Some(Pin::Right), None),
+        };
+        nodes.push(Node::new(ID::Gate(xor + i), Some(GateType::XOR), None, vec![edge]));
+    }
+    for i in 1..bits {
+        let edge = if i == bits - 1 {
+            Edge::new(ID::Gate(not), Some(Pin::Left), None)
+        } else {
+            Edge::new(ID::Gate(or + i + 1), Some(Pin::Left), None)
+        };
+        nodes.push(Node::new(ID::Gate(or + i), Some(GateType::OR), None, vec![edge]));
+    }
+    nodes.push(Node::new(ID::Gate(not),
+                         Some(GateType::NOT),
+                         None,
+                         vec![Edge::new(ID::Output(1), None, None)]));
+
+    // like the converter, GATES and OUTPUTS are the number of gates and
+    // outputs plus one
+    let mut info = MetaInfo::default();
+    info.inputs = 2 * bits;
+    info.gates = 2 * bits + 1;
+    info.outputs = 2;
+    try!(parser::write_libgc(path, &info, &nodes));
+    Ok(())
+}
+
+/// DualExecutionParty runs one side of a two-party computation in the
+/// dual-execution mode. Every party garbles the circuit once and evaluates
+/// the garbling of the other party:
+///
+///   1. party A garbles and party B evaluates
+///   2. party B garbles and party A evaluates
+///   3. both parties compare the outputs of both executions with a garbled
+///      equality circuit - once garbled by party A and once by party B -
+///      and only accept them if both comparisons are equal
+///   4. both parties send the output labels back to the garbler, who
+///      decodes them
+///
+/// The decoding bits are withheld until the comparison succeeded, so the
+/// evaluators only know the point-and-permute bits of their output labels.
+/// Every party masks them with the decoding bits of its own garbling - the
+/// masked bits of both parties are equal if and only if both executions
+/// have the same output, but they reveal nothing about it on their own.
+/// Since every party garbles one of the equality circuits itself, a
+/// malicious party can't fake the result of the comparison. It may learn
+/// one bit about the input of the other party from the result though. The
+/// garbler inputs of the assignment are the ones of party A.
+///
+pub struct DualExecutionParty {
+    party: Party,
+    garbler: GarblerParty,
+    evaluator: EvaluatorParty,
+}
+
+impl DualExecutionParty {
+    pub fn new(info: MetaInfo,
+               assignment: Assignment,
+               party: Party)
+               -> Result<DualExecutionParty, ProtocolError> {
+        DualExecutionParty::with_scheme(info, assignment, party, Scheme::default())
+    }
+
+    pub fn with_scheme(info: MetaInfo,
+                       assignment: Assignment,
+                       party: Party,
+                       scheme: Scheme)
+                       -> Result<DualExecutionParty, ProtocolError> {
+        let other = try!(parser::parse_meta_info(info.path.as_path()));
+        let swapped = Assignment {
+            garbler: assignment.evaluator.clone(),
+            evaluator: assignment.garbler.clone(),
+        };
+        let (garbler, evaluator) = match party {
+            Party::A => {
+                (try!(GarblerParty::with_scheme(info, assignment, scheme)),
+                 try!(EvaluatorParty::new(other, swapped)))
+            }
+            Party::B => {
+                (try!(GarblerParty::with_scheme(info, swapped, scheme)),
+                 try!(EvaluatorParty::new(other, assignment)))
+            }
+        };
+        Ok(DualExecutionParty {
+            party: party,
+            garbler: garbler,
+            evaluator: evaluator,
+        })
+    }
+
+    /// Runs the protocol with the given inputs of the party (input ID ->
+    /// value) and returns the output bits in the order of the output IDs.
+    /// The equality circuit is written to a temporary directory.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, ProtocolError> {
+        let (garbling, points) = match self.party {
+            Party::A => {
+                let garbling = try!(self.garbler.garble(ch, rng, inputs, false));
+                (garbling, try!(self.evaluator.evaluate(ch, rng, inputs)))
+            }
+            Party::B => {
+                let points = try!(self.evaluator.evaluate(ch, rng, inputs));
+                (try!(self.garbler.garble(ch, rng, inputs, false)), points)
+            }
+        };
+        let decoding = garbling.decoding();
+        let mut masked = Vec::with_capacity(points.len());
+        for (i, bit) in points.iter().enumerate() {
+            match decoding.get(&(i as u64 + 1)) {
+                Some(d) => masked.push(bit ^ d),
+                None => return Err(ProtocolError::from(format!("missing output {}", i + 1))),
+            }
+        }
+
+        let bits = masked.len() as u64;
+        let path = ::std::env::temp_dir().join(format!("libgc-equality-{}-{:016x}",
+                                                       self.party,
+                                                       rng.gen::<u64>()));
+        let equal = self.compare(ch, rng, &masked, path.as_path());
+        let _ = fs::remove_dir_all(path.as_path());
+        if !try!(equal) {
+            return Err(ProtocolError::from(format!("the {} output bits of both executions differ",
+                                                   bits)));
+        }
+        match self.party {
+            Party::A => {
+                try!(self.evaluator.send_outputs(ch, bits));
+                self.garbler.decode(ch, &garbling)
+            }
+            Party::B => {
+                let output = try!(self.garbler.decode(ch, &garbling));
+                try!(self.evaluator.send_outputs(ch, bits));
+                Ok(output)
+            }
+        }
+    }
+
+    /// Compares the masked bits of both parties with two garbled equality
+    /// circuits - the first one garbled by party A, the second one by party
+    /// B - and returns whether both of them are equal.
+    fn compare<C: Channel, R: Rng>(&self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   masked: &[u8],
+                                   path: &Path)
+                                   -> Result<bool, ProtocolError> {
+        let bits = masked.len() as u64;
+        try!(ch.send_u64(bits));
+        try!(ch.flush());
+        if try!(ch.recv_u64()) != bits {
+            return Err(ProtocolError::from("the outputs of both executions differ in length"));
+        }
+        try!(write_equality_circuit(path, bits));
+        let assignment = Assignment::split(2 * bits, bits);
+
+        // the garbler always provides the inputs 1 to `bits`
+        let mut own = HashMap::with_capacity(masked.len());
+        let mut other = HashMap::with_capacity(masked.len());
+        for (i, bit) in masked.iter().enumerate() {
+            own.insert(i as u64 + 1, *bit);
+            other.insert(bits + i as u64 + 1, *bit);
+        }
+        let (first, second) = match self.party {
+            Party::A => {
+                let mut garbler = try!(GarblerParty::new(try!(parser::parse_meta_info(path)),
+                                                         assignment.clone()));
+                let mut evaluator = try!(EvaluatorParty::new(try!(parser::parse_meta_info(path)),
+                                                             assignment));
+                let first = try!(garbler.run(ch, rng, &own));
+                (first, try!(evaluator.run(ch, rng, &other)))
+            }
+            Party::B => {
+                let mut evaluator = try!(EvaluatorParty::new(try!(parser::parse_meta_info(path)),
+                                                             assignment.clone()));
+                let mut garbler = try!(GarblerParty::new(try!(parser::parse_meta_info(path)),
+                                                         assignment));
+                let first = try!(evaluator.run(ch, rng, &other));
+                (first, try!(garbler.run(ch, rng, &own)))
+            }
+        };
+        Ok(first[0] == 1 && second[0] == 1)
+    }
+}
@@ -0,0 +1,67 @@
+use std::io;
+use super::super::parser::error::ParseError;
+use super::super::circuit::garbled::GarbleError;
+use super::super::ot::OTError;
+use super::super::circuit::builder::BuildError;
+use std::convert::From;
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ProtocolError {
+    msg: String,
+}
+
+impl<'a> From<&'a str> for ProtocolError {
+    fn from(s: &'a str) -> ProtocolError {
+        ProtocolError { msg: String::from(s) }
+    }
+}
+
+impl From<String> for ProtocolError {
+    fn from(s: String) -> ProtocolError {
+        ProtocolError { msg: s }
+    }
+}
+
+impl error::Error for ProtocolError {
+    fn description(&self) -> &str {
+        self.msg.as_ref()
+    }
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(err: io::Error) -> ProtocolError {
+        ProtocolError::from(format!("{}", err))
+    }
+}
+
+impl From<ParseError> for ProtocolError {
+    fn from(err: ParseError) -> ProtocolError {
+        ProtocolError::from(format!("{}", err))
+    }
+}
+
+impl From<GarbleError> for ProtocolError {
+    fn from(err: GarbleError) -> ProtocolError {
+        ProtocolError::from(format!("{}", err))
+    }
+}
+
+impl From<OTError> for ProtocolError {
+    fn from(err: OTError) -> ProtocolError {
+        ProtocolError::from(format!("{}", err))
+    }
+}
+
+impl From<BuildError> for ProtocolError {
+    fn from(err: BuildError) -> ProtocolError {
+        ProtocolError::from(format!("{}", err))
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error on protocol execution: {}", self.msg)
+    }
+}
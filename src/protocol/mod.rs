@@ -0,0 +1,320 @@
+mod error;
+mod cut_and_choose;
+mod dual;
+
+pub use self::error::ProtocolError;
+pub use self::cut_and_choose::{CutAndChooseGarbler, CutAndChooseEvaluator};
+pub use self::dual::{DualExecutionParty, write_equality_circuit};
+
+use std::collections::{HashMap, HashSet};
+
+use rand::{Rng, ChaChaRng};
+
+use super::channel::Channel;
+use super::circuit::garbled::{Garbler, Encoding, Evaluator, Label, Scheme, FixedKeyAes};
+use super::circuit::garbled::format::{Header, Writer, Reader};
+use super::ot;
+use super::parser::MetaInfo;
+use super::parser::types::Variable;
+
+/// Assignment maps every input of a circuit either to the garbler or to
+/// the evaluator of a two-party computation.
+///
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Assignment {
+    garbler: Vec<u64>,
+    evaluator: Vec<u64>,
+}
+
+impl Assignment {
+    /// Returns an assignment which maps the input variables of one party
+    /// (e.g. the ones of `output.inputs.partyA.txt`) to the garbler and the
+    /// variables of the other party to the evaluator.
+    pub fn new(garbler: &[Variable], evaluator: &[Variable]) -> Result<Assignment, ProtocolError> {
+        let ids = |vars: &[Variable]| -> Vec<u64> {
+            vars.iter().flat_map(|v| v.ids().iter().map(|id| (*id).into())).collect()
+        };
+        let mut a = Assignment {
+            garbler: ids(garbler),
+            evaluator: ids(evaluator),
+        };
+        a.garbler.sort();
+        a.evaluator.sort();
+
+        let mut ids = HashSet::with_capacity(a.garbler.len() + a.evaluator.len());
+        for id in a.garbler.iter().chain(a.evaluator.iter()) {
+            if !ids.insert(*id) {
+                return Err(ProtocolError::from(format!("input {} is assigned twice", id)));
+            }
+        }
+        Ok(a)
+    }
+
+    /// Returns an assignment of `inputs` inputs which maps the first
+    /// `garbler` inputs to the garbler and all others to the evaluator.
+    pub fn split(inputs: u64, garbler: u64) -> Assignment {
+        Assignment {
+            garbler: (1..garbler + 1).collect(),
+            evaluator: (garbler + 1..inputs + 1).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn garbler(&self) -> &[u64] {
+        self.garbler.as_slice()
+    }
+
+    #[inline]
+    pub fn evaluator(&self) -> &[u64] {
+        self.evaluator.as_slice()
+    }
+
+    fn check(&self, inputs: u64) -> Result<(), ProtocolError> {
+        let n = (self.garbler.len() + self.evaluator.len()) as u64;
+        if n != inputs {
+            return Err(ProtocolError::from(format!("assignment covers {} inputs - circuit has \
+                                                    {}",
+                                                   n,
+                                                   inputs)));
+        }
+        for id in self.garbler.iter().chain(self.evaluator.iter()) {
+            if *id == 0 || *id > inputs {
+                return Err(ProtocolError::from(format!("unknown input {}", id)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_inputs(ids: &[u64], inputs: &HashMap<u64, u8>) -> Result<(), ProtocolError> {
+    if ids.len() != inputs.len() {
+        return Err(ProtocolError::from(format!("expected {} input bits - got {}",
+                                               ids.len(),
+                                               inputs.len())));
+    }
+    for id in ids {
+        if !inputs.contains_key(id) {
+            return Err(ProtocolError::from(format!("missing value for input {}", id)));
+        }
+    }
+    Ok(())
+}
+
+fn send_ids<C: Channel>(ch: &mut C, ids: &[u64]) -> Result<(), ProtocolError> {
+    try!(ch.send_u64(ids.len() as u64));
+    for id in ids {
+        try!(ch.send_u64(*id));
+    }
+    Ok(())
+}
+
+/// Receives at most `max` IDs - the count is checked before allocating.
+fn recv_ids<C: Channel>(ch: &mut C, max: u64) -> Result<Vec<u64>, ProtocolError> {
+    let n = try!(ch.recv_u64());
+    if n > max {
+        return Err(ProtocolError::from(format!("expected at most {} IDs - got {}", max, n)));
+    }
+    let mut ids = Vec::with_capacity(n as usize);
+    for _ in 0..n {
+        ids.push(try!(ch.recv_u64()));
+    }
+    Ok(ids)
+}
+
+/// GarblerParty runs the garbler's side of a semi-honest two-party
+/// computation (Yao's protocol):
+///
+///   1. send the labels of the own inputs
+///   2. transfer the labels of the evaluator's inputs using OT
+///   3. garble the circuit and stream it (including the output decoding
+///      information) to the evaluator
+///   4. receive and decode the output labels of the evaluator
+///
+pub struct GarblerParty {
+    garbler: Garbler,
+    inputs: u64,
+    assignment: Assignment,
+}
+
+impl GarblerParty {
+    pub fn new(info: MetaInfo, assignment: Assignment) -> Result<GarblerParty, ProtocolError> {
+        GarblerParty::with_scheme(info, assignment, Scheme::default())
+    }
+
+    pub fn with_scheme(info: MetaInfo,
+                       assignment: Assignment,
+                       scheme: Scheme)
+                       -> Result<GarblerParty, ProtocolError> {
+        let inputs = info.inputs;
+        try!(assignment.check(inputs));
+        Ok(GarblerParty {
+            garbler: try!(Garbler::with_scheme(info, scheme)),
+            inputs: inputs,
+            assignment: assignment,
+        })
+    }
+
+    /// Runs the protocol with the given garbler inputs (input ID -> value)
+    /// and returns the output bits in the order of the output IDs.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, ProtocolError> {
+        let garbling = try!(self.garble(ch, rng, inputs, true));
+        self.decode(ch, &garbling)
+    }
+
+    /// Transfers the input labels to the evaluator and garbles the circuit
+    /// while streaming it (steps 1 to 3). If `decoding` is false, the
+    /// decoding bits are withheld and zero bits are sent instead - the
+    /// evaluator only learns the point-and-permute bits of its output labels
+    /// then.
+    fn garble<C: Channel, R: Rng>(&mut self,
+                                  ch: &mut C,
+                                  rng: &mut R,
+                                  inputs: &HashMap<u64, u8>,
+                                  decoding: bool)
+                                  -> Result<Encoding, ProtocolError> {
+        try!(check_inputs(self.assignment.garbler(), inputs));
+        try!(ch.send_u64(self.inputs));
+        try!(send_ids(ch, self.assignment.garbler()));
+        try!(ch.flush());
+        if try!(ch.recv_u8()) != 1 {
+            return Err(ProtocolError::from("evaluator rejected the input assignment"));
+        }
+
+        // the input labels only depend on the randomness of the garbling,
+        // so they are sent before the circuit is garbled
+        let mut garbling_rng = rng.gen::<ChaChaRng>();
+        let labels = Garbler::input_labels(self.garbler.scheme(),
+                                           self.inputs,
+                                           &mut garbling_rng.clone());
+        for id in self.assignment.garbler() {
+            let pair = labels[id];
+            try!(ch.send_label(if inputs[id] == 0 { pair.0 } else { pair.1 }));
+        }
+        try!(ch.flush());
+        try!(ot::send_inputs(ch, rng, &labels, self.assignment.evaluator()));
+
+        let header = Header::new(self.garbler.scheme(), self.garbler.info());
+        let mut writer = try!(Writer::new(&mut *ch, &header));
+        let garbling = try!(self.garbler.garble_into(&mut garbling_rng,
+                                                     &FixedKeyAes::default(),
+                                                     &mut writer));
+        let mut bits = garbling.decoding();
+        if !decoding {
+            for bit in bits.values_mut() {
+                *bit = 0;
+            }
+        }
+        try!(writer.finish(&bits));
+        Ok(garbling)
+    }
+
+    /// Receives and decodes the output labels of the evaluator (step 4).
+    fn decode<C: Channel>(&mut self,
+                          ch: &mut C,
+                          garbling: &Encoding)
+                          -> Result<Vec<u8>, ProtocolError> {
+        let n = try!(ch.recv_u64());
+        if n != garbling.outputs.len() as u64 {
+            return Err(ProtocolError::from(format!("expected {} output labels - got {}",
+                                                   garbling.outputs.len(),
+                                                   n)));
+        }
+        let mut output = Vec::with_capacity(n as usize);
+        for id in 1..n + 1 {
+            let label = try!(ch.recv_label());
+            let pair = match garbling.outputs.get(&id) {
+                Some(pair) => *pair,
+                None => return Err(ProtocolError::from(format!("missing output {}", id))),
+            };
+            if label == pair.0 {
+                output.push(0);
+            } else if label == pair.1 {
+                output.push(1);
+            } else {
+                return Err(ProtocolError::from(format!("invalid label for output {}", id)));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// EvaluatorParty runs the evaluator's side of a semi-honest two-party
+/// computation (Yao's protocol). It obtains the labels of its inputs via
+/// OT, evaluates the garbled circuit while receiving it and sends the output
+/// labels back to the garbler.
+///
+pub struct EvaluatorParty {
+    evaluator: Evaluator,
+    inputs: u64,
+    assignment: Assignment,
+}
+
+impl EvaluatorParty {
+    pub fn new(info: MetaInfo, assignment: Assignment) -> Result<EvaluatorParty, ProtocolError> {
+        let inputs = info.inputs;
+        try!(assignment.check(inputs));
+        Ok(EvaluatorParty {
+            evaluator: try!(Evaluator::new(info)),
+            inputs: inputs,
+            assignment: assignment,
+        })
+    }
+
+    /// Runs the protocol with the given evaluator inputs (input ID -> value)
+    /// and returns the output bits in the order of the output IDs.
+    pub fn run<C: Channel, R: Rng>(&mut self,
+                                   ch: &mut C,
+                                   rng: &mut R,
+                                   inputs: &HashMap<u64, u8>)
+                                   -> Result<Vec<u8>, ProtocolError> {
+        let output = try!(self.evaluate(ch, rng, inputs));
+        try!(self.send_outputs(ch, output.len() as u64));
+        Ok(output)
+    }
+
+    /// Sends the `n` output labels of the last evaluation back to the
+    /// garbler (step 4 of the garbler).
+    fn send_outputs<C: Channel>(&self, ch: &mut C, n: u64) -> Result<(), ProtocolError> {
+        try!(ch.send_u64(n));
+        for id in 1..n + 1 {
+            try!(ch.send_label(try!(self.evaluator.get_output(id))));
+        }
+        try!(ch.flush());
+        Ok(())
+    }
+
+    /// Receives the garbled circuit and the input labels and evaluates the
+    /// circuit - the output labels are not sent back to the garbler.
+    fn evaluate<C: Channel, R: Rng>(&mut self,
+                                    ch: &mut C,
+                                    rng: &mut R,
+                                    inputs: &HashMap<u64, u8>)
+                                    -> Result<Vec<u8>, ProtocolError> {
+        try!(check_inputs(self.assignment.evaluator(), inputs));
+        let n = try!(ch.recv_u64());
+        let ids = try!(recv_ids(ch, self.inputs));
+        if n != self.inputs || ids.as_slice() != self.assignment.garbler() {
+            try!(ch.send_u8(0));
+            try!(ch.flush());
+            return Err(ProtocolError::from("garbler uses a different input assignment"));
+        }
+        try!(ch.send_u8(1));
+        try!(ch.flush());
+
+        let mut labels: HashMap<u64, Label> = HashMap::with_capacity(self.inputs as usize);
+        for id in self.assignment.garbler() {
+            labels.insert(*id, try!(ch.recv_label()));
+        }
+        labels.extend(try!(ot::receive_inputs(ch, rng, inputs)));
+
+        let mut reader = try!(Reader::open(&mut *ch, self.evaluator.info()));
+        let scheme = reader.header().scheme;
+        try!(self.evaluator.evaluate_from(scheme, &mut reader, &labels, &FixedKeyAes::default()));
+        let decoding = try!(reader.read_decoding());
+        Ok(try!(self.evaluator.decode(&decoding)))
+    }
+}
@@ -0,0 +1,81 @@
+extern crate libgc;
+extern crate rand;
+
+mod common;
+
+use std::env;
+use std::thread;
+use std::ops::Range;
+use std::path::Path;
+use std::collections::HashMap;
+
+use rand::Rng;
+use libgc::channel;
+use libgc::parser;
+use libgc::circuit::bmr::Party;
+use common::{execute, to_bits};
+
+/// Runs the circuit at `path` with one party per input range - party `j`
+/// owns the inputs `ranges[j]` - and returns the output of every party.
+fn run(path: &Path, ranges: &[Range<u64>], bits: &[u8]) -> Vec<Vec<u8>> {
+    let n = ranges.len();
+    let mut handles = Vec::with_capacity(n);
+    for (mut net, id) in channel::network(n).into_iter().zip(0..n) {
+        let mut inputs = HashMap::new();
+        for i in ranges[id].clone() {
+            inputs.insert(i, bits[i as usize - 1]);
+        }
+        let ranges = ranges.to_vec();
+        let mut party = Party::new(parser::parse_meta_info(path).unwrap()).unwrap();
+        handles.push(thread::spawn(move || {
+            party.run(&mut net, &mut rand::thread_rng(), &ranges, &inputs).unwrap()
+        }));
+    }
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+
+#[test]
+fn bmr_three_party_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut rng = rand::thread_rng();
+    let bits = to_bits(rng.gen(), rng.gen());
+    let expected = execute(path.as_path(), &bits);
+    for output in run(path.as_path(), &[1..21, 21..41, 41..65], &bits) {
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn bmr_multi_party_sum2() {
+    // sum2 uses sum as sub circuit - the last party has no inputs
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let bits = to_bits(0xDEADBEEF, 0x12345678);
+    let expected = execute(path.as_path(), &bits);
+    for output in run(path.as_path(), &[1..17, 17..33, 33..65, 65..65], &bits) {
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn bmr_gates() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    for x in 0..4u8 {
+        let bits = [x & 1, x >> 1];
+        let expected = execute(path.as_path(), &bits);
+        for output in run(path.as_path(), &[1..2, 2..3], &bits) {
+            assert_eq!(output, expected);
+        }
+    }
+}
+
+#[test]
+fn bmr_invalid_ranges() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    let invalid = [vec![1..2], vec![1..2, 1..3], vec![1..2, 3..4], vec![0..1, 1..3]];
+    for ranges in invalid.iter() {
+        let mut net = channel::network(ranges.len()).remove(0);
+        let mut party = Party::new(parser::parse_meta_info(path.as_path()).unwrap()).unwrap();
+        assert!(party.run(&mut net, &mut rand::thread_rng(), ranges, &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+extern crate libgc;
+
+use std::fs;
+use std::env;
+use std::process;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use libgc::bristol;
+use libgc::parser;
+use libgc::circuit::binary;
+
+static DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// out = (!(a & b), a, 0, 1 ^ b)
+const FASHION: &'static str = "6 9
+2 1 1
+
+1 4
+
+2 1 0 1 2 AND
+1 1 2 5 INV
+1 1 0 6 EQW
+1 1 0 7 EQ
+1 1 1 3 EQ
+2 1 3 1 8 XOR
+";
+
+// out = a ^ b, a & b
+const BRISTOL: &'static str = "2 4
+1 1 2
+
+2 1 0 1 2 XOR
+2 1 0 1 3 AND
+";
+
+#[test]
+fn parse_bristol_fashion() {
+    let circuit = bristol::Circuit::parse(Cursor::new(FASHION)).unwrap();
+    assert_eq!(circuit.inputs(), &[1, 1]);
+    assert_eq!(circuit.outputs(), &[4]);
+    assert_eq!(circuit.gates()[1], bristol::Gate::INV(2, 5));
+    assert_eq!(bristol::Circuit::parse(Cursor::new(format!("{}", circuit))).unwrap(),
+               circuit);
+
+    for keep_not in &[true, false] {
+        let mut c = convert(&circuit, *keep_not);
+        for x in 0..4u8 {
+            let (a, b) = (x & 1, x >> 1);
+            c.set_input(1, a);
+            c.set_input(2, b);
+            c.execute().unwrap();
+            assert_eq!(c.collect_output(), vec![1 ^ (a & b), a, 0, 1 ^ b]);
+            assert_eq!(c.get::<u8>("OUTPUT_1").unwrap(),
+                       (1 ^ (a & b)) | a << 1 | (1 ^ b) << 3);
+        }
+    }
+}
+
+#[test]
+fn parse_bristol() {
+    let circuit = bristol::Circuit::parse(Cursor::new(BRISTOL)).unwrap();
+    assert_eq!(circuit.inputs(), &[1, 1]);
+    assert_eq!(circuit.outputs(), &[2]);
+
+    let mut c = convert(&circuit, false);
+    for x in 0..4u8 {
+        c.set("INPUT_1", x & 1).unwrap();
+        c.set("INPUT_2", x >> 1).unwrap();
+        c.execute().unwrap();
+        assert_eq!(c.collect_output(), vec![(x & 1) ^ (x >> 1), (x & 1) & (x >> 1)]);
+    }
+}
+
+#[test]
+fn parse_invalid_bristol() {
+    // wire 2 is read before it is written
+    let circuit = "2 4\n1 1 2\n\n2 1 0 2 3 XOR\n2 1 0 1 2 AND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+    // wire 2 is written twice
+    let circuit = "2 4\n1 1 2\n\n2 1 0 1 2 XOR\n2 1 0 1 2 AND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+    // MAND is not supported
+    let circuit = "1 6\n2 2 2\n1 2\n\n4 2 0 1 2 3 4 5 MAND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+    // the header claims far more gates and wires than the circuit has
+    let circuit = "18446744073709551615 4\n1 1 2\n\n2 1 0 1 2 XOR\n2 1 0 1 3 AND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+    let circuit = "2 18446744073709551615\n1 1 2\n\n2 1 0 1 2 XOR\n2 1 0 1 3 AND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+    // the input lengths overflow
+    let circuit = "2 4\n18446744073709551615 1 2\n\n2 1 0 1 2 XOR\n2 1 0 1 3 AND\n";
+    assert!(bristol::Circuit::parse(Cursor::new(circuit)).is_err());
+}
+
+#[test]
+fn export_libgc_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let circuit = bristol::export(&parser::parse_meta_info(path.as_path()).unwrap()).unwrap();
+    assert_eq!(circuit.inputs(), &[32, 32]);
+    assert_eq!(circuit.outputs(), &[32]);
+    let circuit = bristol::Circuit::parse(Cursor::new(format!("{}", circuit))).unwrap();
+
+    let mut c = convert(&circuit, false);
+    for &(x, y) in &[(2u32, 2u32), (1 << 31, 7), (u32::max_value(), 5), (12345, 67890)] {
+        c.set("INPUT_1", x).unwrap();
+        c.set("INPUT_2", y).unwrap();
+        c.execute().unwrap();
+        assert_eq!(c.get::<u32>("OUTPUT_1").unwrap(), x.wrapping_add(y));
+    }
+}
+
+#[test]
+fn export_libgc_gates() {
+    // gates uses OR and NOT gates and the ONE constant
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    let mut expected = binary::Circuit::new(parser::parse_meta_info(path.as_path()).unwrap())
+        .unwrap();
+    let circuit = bristol::export(expected.info()).unwrap();
+    assert_eq!(circuit.inputs(), &[2]);
+    assert_eq!(circuit.outputs(), &[5]);
+
+    let mut c = convert(&circuit, true);
+    for x in 0..4u8 {
+        for id in 1..3 {
+            expected.set_input(id, (x >> (id - 1)) & 1);
+            c.set_input(id, (x >> (id - 1)) & 1);
+        }
+        expected.execute().unwrap();
+        c.execute().unwrap();
+        assert_eq!(c.collect_output(), expected.collect_output());
+    }
+}
+
+fn convert(circuit: &bristol::Circuit, keep_not: bool) -> binary::Circuit {
+    let dir: PathBuf = env::temp_dir()
+        .join(format!("libgc-bristol-test-{}-{}",
+                      process::id(),
+                      DIRS.fetch_add(1, Ordering::SeqCst)));
+    fs::create_dir_all(&dir).unwrap();
+    bristol::Converter::new(dir.as_path()).unwrap().convert(circuit, keep_not).unwrap();
+    let info = parser::parse_meta_info(dir.as_path()).unwrap();
+    let c = binary::Circuit::new(info).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    c
+}
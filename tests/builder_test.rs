@@ -0,0 +1,140 @@
+extern crate libgc;
+extern crate rand;
+
+use std::fs;
+use std::env;
+use std::process;
+use std::path::Path;
+use rand::Rng;
+use libgc::parser;
+use libgc::parser::types::{GateType, ID};
+use libgc::circuit::binary;
+use libgc::circuit::builder::{CircuitBuilder, Wire};
+
+fn add(builder: &mut CircuitBuilder, x: &[Wire], y: &[Wire]) -> Vec<Wire> {
+    let mut carry = builder.zero();
+    let mut sum = Vec::with_capacity(x.len());
+    for (a, b) in x.iter().zip(y.iter()) {
+        let t = builder.xor(*a, *b);
+        sum.push(builder.xor(t, carry));
+        let c = builder.and(*a, *b);
+        let d = builder.and(t, carry);
+        carry = builder.or(c, d);
+    }
+    sum
+}
+
+#[test]
+fn build_adder() {
+    let mut builder = CircuitBuilder::new();
+    let x = builder.input_variable("x", 16).unwrap();
+    let y = builder.input_variable("y", 16).unwrap();
+    let sum = add(&mut builder, &x, &y);
+    builder.output_variable("sum", "INT16", &sum).unwrap();
+    assert!(builder.output_variable("x", "INT16", &sum).is_err());
+    let (info, nodes) = builder.build();
+
+    // gate IDs are assigned in topological order
+    let mut last = 0;
+    for node in &nodes {
+        if let ID::Gate(id) = node.id() {
+            assert!(id > last);
+            last = id;
+            assert!(node.gate_type() != Some(GateType::NOT));
+            for edge in node.edges() {
+                if let ID::Gate(to) = edge.id() {
+                    assert!(to > id);
+                }
+            }
+        }
+    }
+    assert_eq!(last, info.gates);
+
+    let dir = env::temp_dir().join(format!("libgc-builder-test-{}", process::id()));
+    parser::write_libgc(dir.as_path(), &info, &nodes).unwrap();
+    let mut written = binary::Circuit::new(parser::parse_meta_info(dir.as_path()).unwrap())
+        .unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let mut circuit = binary::Circuit::from_nodes(info, nodes).unwrap();
+    let mut rng = rand::thread_rng();
+    for _ in 0..32 {
+        let (a, b): (i16, i16) = (rng.gen(), rng.gen());
+        for c in vec![&mut circuit, &mut written] {
+            c.set("x", a).unwrap();
+            c.set("y", b).unwrap();
+            c.execute().unwrap();
+            assert_eq!(c.get::<i16>("sum").unwrap(), a.wrapping_add(b));
+        }
+    }
+}
+
+#[test]
+fn build_constants() {
+    let mut builder = CircuitBuilder::new();
+    let a = builder.input();
+    let b = builder.input();
+    let _unused = builder.and(a, b);
+    let one = builder.constant(true);
+    let zero = builder.constant(false);
+    let not = builder.not(b);
+    builder.output(one);
+    builder.output(zero);
+    builder.output(not);
+    builder.output(a);
+    let (info, nodes) = builder.build();
+    assert_eq!(info.gates, 2);
+    assert_eq!(info.outputs, 4);
+    assert!(info.one.is_some());
+
+    let mut circuit = binary::Circuit::from_nodes(info, nodes).unwrap();
+    for x in 0..4u8 {
+        circuit.set_input(1, x & 1);
+        circuit.set_input(2, x >> 1);
+        circuit.execute().unwrap();
+        assert_eq!(circuit.collect_output(), vec![1, 0, 1 ^ (x >> 1), x & 1]);
+    }
+}
+
+#[test]
+fn build_sub_circuits() {
+    // x + y + z with two instances of the 32 bit adder sum
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut builder = CircuitBuilder::new();
+    let x = builder.input_variable("x", 32).unwrap();
+    let y = builder.input_variable("y", 32).unwrap();
+    let z = builder.input_variable("z", 32).unwrap();
+    assert!(builder.sub_circuit(path.as_path(), &x).is_err());
+
+    let inputs: Vec<Wire> = x.iter().chain(y.iter()).cloned().collect();
+    let t = builder.sub_circuit(path.as_path(), &inputs).unwrap();
+    assert_eq!(t.len(), 32);
+    let inputs: Vec<Wire> = t.iter().chain(z.iter()).cloned().collect();
+    let sum = builder.sub_circuit(path.as_path(), &inputs).unwrap();
+    builder.output_variable("sum", "INT32", &sum).unwrap();
+    let (info, nodes) = builder.build();
+    assert_eq!(info.sub_circuits.len(), 2);
+    assert_eq!(info.gates, 0);
+
+    // relative paths are stored as canonical paths
+    let mut b = CircuitBuilder::new();
+    let inputs = b.inputs(64);
+    for wire in b.sub_circuit(Path::new("tests/libgc_output/../libgc_output/sum"), &inputs)
+        .unwrap() {
+        b.output(wire);
+    }
+    let (sub, _) = b.build();
+    let canonical = fs::canonicalize(path.as_path()).unwrap();
+    assert_eq!(sub.sub_circuits["c0"], canonical.to_string_lossy());
+
+    let mut circuit = binary::Circuit::from_nodes(info, nodes).unwrap();
+    let mut rng = rand::thread_rng();
+    for _ in 0..8 {
+        let (a, b, c): (i32, i32, i32) = (rng.gen(), rng.gen(), rng.gen());
+        circuit.set("x", a).unwrap();
+        circuit.set("y", b).unwrap();
+        circuit.set("z", c).unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(circuit.get::<i32>("sum").unwrap(), a.wrapping_add(b).wrapping_add(c));
+    }
+}
@@ -2,6 +2,7 @@ extern crate libgc;
 
 use std::env;
 use libgc::cbmc;
+use libgc::parser::types::ID;
 
 #[test]
 fn parse_cbmc_sum() {
@@ -11,3 +12,15 @@ fn parse_cbmc_sum() {
     parser.parse_gates().unwrap();
     parser.parse_constant().unwrap();
 }
+
+#[test]
+fn parse_cbmc_sum_outputs() {
+    let path = env::current_dir().unwrap().join("tests").join("cbmc_output").join("sum");
+    let parser: cbmc::Parser = cbmc::Parser::new(path.as_path()).unwrap();
+    let outputs = parser.parse_outputs().unwrap();
+    assert_eq!(outputs.len(), 1);
+    assert_eq!(outputs[0].name(), "OUTPUT_sum");
+    assert_eq!(outputs[0].var_type(), Some("INT32"));
+    assert_eq!(outputs[0].ids().to_vec(),
+               (1..33).map(|id| ID::Output(id)).collect::<Vec<ID>>());
+}
@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+use std::path::Path;
+
+use libgc::parser;
+use libgc::circuit::binary;
+
+/// Executes the circuit at `path` on the plaintext input bits `bits[id - 1]`.
+pub fn execute(path: &Path, bits: &[u8]) -> Vec<u8> {
+    let mut circuit = binary::Circuit::new(parser::parse_meta_info(path).unwrap()).unwrap();
+    for (i, bit) in bits.iter().enumerate() {
+        circuit.set_input(i as u64 + 1, *bit);
+    }
+    circuit.execute().unwrap();
+    circuit.collect_output()
+}
+
+/// Returns the input bits of the sum circuits - the bits of `x` followed by
+/// the bits of `y`, least significant bit first.
+pub fn to_bits(x: u32, y: u32) -> Vec<u8> {
+    let mut bits = Vec::with_capacity(64);
+    for v in &[x, y] {
+        for i in 0..32 {
+            bits.push(((v >> i) & 1) as u8);
+        }
+    }
+    bits
+}
+
+pub fn from_bits(bits: &[u8]) -> u32 {
+    let mut v = 0;
+    for (i, bit) in bits.iter().enumerate() {
+        v |= (*bit as u32) << i;
+    }
+    v
+}
@@ -0,0 +1,92 @@
+extern crate libgc;
+
+use std::fs;
+use std::env;
+use std::process;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use libgc::parser;
+use libgc::circuit::binary;
+
+#[test]
+fn compact_round_trip() {
+    for name in &["sum", "sum2", "gates"] {
+        let path = env::current_dir().unwrap().join("tests").join("libgc_output").join(name);
+        let nodes = parser::parse_circuit(path.as_path()).unwrap();
+        let mut buf = Vec::new();
+        parser::write_compact(&mut buf, &nodes).unwrap();
+        assert!(buf.len() < fs::metadata(path.join("circuit.txt")).unwrap().len() as usize);
+
+        let circuit = parser::CompactCircuit::new(Cursor::new(buf)).unwrap();
+        let decoded: Vec<String> = circuit.map(|node| format!("{}", node.unwrap())).collect();
+        let expected: Vec<String> = nodes.iter().map(|node| format!("{}", node)).collect();
+        assert_eq!(decoded, expected);
+    }
+}
+
+#[test]
+fn compact_invalid() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let nodes = parser::parse_circuit(path.as_path()).unwrap();
+    let mut buf = Vec::new();
+    parser::write_compact(&mut buf, &nodes).unwrap();
+
+    let mut magic = buf.clone();
+    magic[0] = b'X';
+    assert!(parser::CompactCircuit::new(Cursor::new(magic)).is_err());
+
+    // a truncated node is an error - not the end of the circuit
+    buf.pop();
+    let circuit = parser::CompactCircuit::new(Cursor::new(buf)).unwrap();
+    assert!(circuit.last().unwrap().is_err());
+
+    // a name length beyond the end of the circuit is not allocated
+    let name = b"LGCB\x01\x01\xff\xff\xff\xff\xff\xff\xff\x7fsub".to_vec();
+    assert!(parser::CompactCircuit::new(Cursor::new(name)).is_err());
+    // neither is an edge count beyond the end of the circuit
+    let edges = b"LGCB\x01\x00\x08\xff\xff\xff\xff\xff\xff\xff\x7f\x05".to_vec();
+    let circuit = parser::CompactCircuit::new(Cursor::new(edges)).unwrap();
+    assert!(circuit.last().unwrap().is_err());
+}
+
+#[test]
+fn execute_compact_sum() {
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let dir = env::temp_dir().join(format!("libgc-compact-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in &["circuit.txt", "meta_info.txt", "interface.txt"] {
+        fs::copy(src.join(name), dir.join(name)).unwrap();
+    }
+    parser::pack_circuit(dir.as_path()).unwrap();
+    fs::remove_file(dir.join("circuit.txt")).unwrap();
+    assert_eq!(execute_sum(dir.as_path(), 17, 25), 42);
+
+    parser::unpack_circuit(dir.as_path()).unwrap();
+    fs::remove_file(dir.join("circuit.bin")).unwrap();
+    assert_eq!(execute_sum(dir.as_path(), -17, 25), 8);
+    assert_eq!(parser::parse_circuit(dir.as_path()).unwrap().len(),
+               parser::parse_circuit(src.as_path()).unwrap().len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compact_stale() {
+    // a circuit.bin next to a circuit.txt is ignored
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let dir = env::temp_dir().join(format!("libgc-compact-stale-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in &["circuit.txt", "meta_info.txt", "interface.txt"] {
+        fs::copy(src.join(name), dir.join(name)).unwrap();
+    }
+    fs::File::create(dir.join("circuit.bin")).unwrap().write_all(b"LGCB\x01\x00\x07").unwrap();
+    assert_eq!(execute_sum(dir.as_path(), 17, 25), 42);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+fn execute_sum(path: &Path, x: i32, y: i32) -> i32 {
+    let mut circuit = binary::Circuit::new(parser::parse_meta_info(path).unwrap()).unwrap();
+    circuit.set("INPUT_A_x", x).unwrap();
+    circuit.set("INPUT_B_y", y).unwrap();
+    circuit.execute().unwrap();
+    circuit.get("OUTPUT_sum").unwrap()
+}
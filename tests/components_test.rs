@@ -0,0 +1,185 @@
+extern crate libgc;
+extern crate rand;
+
+use rand::Rng;
+use libgc::parser::MetaInfo;
+use libgc::parser::types::{GateType, Node};
+use libgc::circuit::binary::Circuit;
+use libgc::circuit::builder::components;
+
+fn and_gates(nodes: &[Node]) -> usize {
+    nodes.iter()
+        .filter(|node| match node.gate_type() {
+            Some(GateType::AND) | Some(GateType::OR) => true,
+            _ => false,
+        })
+        .count()
+}
+
+fn compile(circuit: (MetaInfo, Vec<Node>)) -> Circuit {
+    let (info, nodes) = circuit;
+    Circuit::from_nodes(info, nodes).unwrap()
+}
+
+// runs the operation with the inputs x and y on all 4 bit numbers
+fn exhaustive<F>(circuit: (MetaInfo, Vec<Node>), op: F)
+    where F: Fn(u8, u8) -> u8
+{
+    let mut circuit = compile(circuit);
+    for x in 0..16u8 {
+        for y in 0..16u8 {
+            circuit.set("x", x).unwrap();
+            circuit.set("y", y).unwrap();
+            circuit.execute().unwrap();
+            assert_eq!(circuit.get::<u8>("z").unwrap(), op(x, y) & 0xf);
+        }
+    }
+}
+
+// runs the operation with the inputs x and y on random 32 bit numbers
+fn randomized<F>(circuit: (MetaInfo, Vec<Node>), op: F)
+    where F: Fn(u32, u32) -> u32
+{
+    let mut circuit = compile(circuit);
+    let mut rng = rand::thread_rng();
+    for _ in 0..64 {
+        let (x, y): (u32, u32) = (rng.gen(), rng.gen());
+        circuit.set("x", x).unwrap();
+        circuit.set("y", y).unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(circuit.get::<u32>("z").unwrap(), op(x, y));
+    }
+}
+
+#[test]
+fn adders() {
+    exhaustive(components::ripple_carry_adder(4).unwrap(), |x, y| x.wrapping_add(y));
+    exhaustive(components::carry_lookahead_adder(4).unwrap(), |x, y| x.wrapping_add(y));
+    randomized(components::ripple_carry_adder(32).unwrap(), |x, y| x.wrapping_add(y));
+    randomized(components::carry_lookahead_adder(32).unwrap(), |x, y| x.wrapping_add(y));
+    for bits in 1..8 {
+        exhaustive_bits(components::carry_lookahead_adder(bits).unwrap(), bits);
+    }
+    assert_eq!(and_gates(&components::ripple_carry_adder(32).unwrap().1), 31);
+    assert!(components::ripple_carry_adder(0).is_err());
+}
+
+fn exhaustive_bits(circuit: (MetaInfo, Vec<Node>), bits: usize) {
+    let mask = (1u16 << bits) - 1;
+    let mut circuit = compile(circuit);
+    for x in 0..mask + 1 {
+        for y in 0..mask + 1 {
+            circuit.set("x", x).unwrap();
+            circuit.set("y", y).unwrap();
+            circuit.execute().unwrap();
+            assert_eq!(circuit.get::<u16>("z").unwrap(), (x + y) & mask);
+        }
+    }
+}
+
+#[test]
+fn subtractor() {
+    exhaustive(components::subtractor(4).unwrap(), |x, y| x.wrapping_sub(y));
+    randomized(components::subtractor(32).unwrap(), |x, y| x.wrapping_sub(y));
+    assert_eq!(and_gates(&components::subtractor(32).unwrap().1), 31);
+}
+
+#[test]
+fn multiplier() {
+    exhaustive(components::multiplier(4).unwrap(), |x, y| x.wrapping_mul(y));
+    randomized(components::multiplier(32).unwrap(), |x, y| x.wrapping_mul(y));
+    assert_eq!(and_gates(&components::multiplier(8).unwrap().1), 36 + 21);
+}
+
+#[test]
+fn comparators() {
+    exhaustive(components::equality(4).unwrap(), |x, y| (x == y) as u8);
+    randomized(components::equality(32).unwrap(), |x, y| (x == y) as u32);
+    assert_eq!(and_gates(&components::equality(32).unwrap().1), 31);
+
+    for &signed in &[false, true] {
+        let (info, nodes) = components::comparator(4, signed).unwrap();
+        assert_eq!(and_gates(&nodes), 4 + 3);
+        let mut circuit = Circuit::from_nodes(info, nodes).unwrap();
+        for x in -8..8i8 {
+            for y in -8..8i8 {
+                circuit.set("x", x).unwrap();
+                circuit.set("y", y).unwrap();
+                circuit.execute().unwrap();
+                let lt = if signed { x < y } else { (x as u8 & 0xf) < (y as u8 & 0xf) };
+                assert_eq!(circuit.get::<bool>("lt").unwrap(), lt);
+                assert_eq!(circuit.get::<bool>("eq").unwrap(), x == y);
+            }
+        }
+    }
+
+    let mut circuit = compile(components::comparator(32, true).unwrap());
+    let mut rng = rand::thread_rng();
+    for _ in 0..64 {
+        let (x, y): (i32, i32) = (rng.gen(), rng.gen());
+        circuit.set("x", x).unwrap();
+        circuit.set("y", y).unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(circuit.get::<bool>("lt").unwrap(), x < y);
+    }
+}
+
+#[test]
+fn multiplexer() {
+    let (info, nodes) = components::multiplexer(16).unwrap();
+    assert_eq!(and_gates(&nodes), 16);
+    let mut circuit = Circuit::from_nodes(info, nodes).unwrap();
+    let mut rng = rand::thread_rng();
+    for i in 0..32 {
+        let (x, y): (u16, u16) = (rng.gen(), rng.gen());
+        circuit.set("s", i % 2 == 1).unwrap();
+        circuit.set("x", x).unwrap();
+        circuit.set("y", y).unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(circuit.get::<u16>("z").unwrap(), if i % 2 == 1 { y } else { x });
+    }
+}
+
+#[test]
+fn shifts() {
+    // the shift amount has 4 bits - 8 to 15 shift out all bits
+    for &(right, arithmetic) in &[(false, false), (true, false), (true, true)] {
+        let mut circuit = compile(components::barrel_shifter(8, right, arithmetic).unwrap());
+        for x in 0..256u16 {
+            for k in 0..16u8 {
+                circuit.set("x", x as u8).unwrap();
+                circuit.set("k", k).unwrap();
+                circuit.execute().unwrap();
+                let z = match (right, arithmetic) {
+                    (false, _) => (x << k) as u8,
+                    (true, false) => (x >> k) as u8,
+                    (true, true) => ((x as u8 as i8) >> k.min(7)) as u8,
+                };
+                assert_eq!(circuit.get::<u8>("z").unwrap(), z);
+            }
+        }
+    }
+}
+
+#[test]
+fn hamming_weight() {
+    for bits in 1..12 {
+        let (info, nodes) = components::hamming_weight_counter(bits).unwrap();
+        assert_eq!(and_gates(&nodes), bits - bits.count_ones() as usize);
+        let mut circuit = Circuit::from_nodes(info, nodes).unwrap();
+        for x in 0..1u16 << bits {
+            circuit.set("x", x).unwrap();
+            circuit.execute().unwrap();
+            assert_eq!(circuit.get::<u16>("z").unwrap(), x.count_ones() as u16);
+        }
+    }
+
+    let mut circuit = compile(components::hamming_weight_counter(64).unwrap());
+    let mut rng = rand::thread_rng();
+    for _ in 0..64 {
+        let x: u64 = rng.gen();
+        circuit.set("x", x).unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(circuit.get::<u8>("z").unwrap(), x.count_ones() as u8);
+    }
+}
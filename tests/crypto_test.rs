@@ -0,0 +1,98 @@
+extern crate libgc;
+extern crate rand;
+
+use rand::Rng;
+use libgc::crypto::aes::Aes128;
+use libgc::crypto::sha256::Sha256;
+use libgc::crypto::curve25519::{self, EdwardsPoint, PointTable};
+
+fn from_hex(s: &str) -> [u8; 16] {
+    let mut buf = [0u8; 16];
+    for i in 0..16 {
+        buf[i] = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    buf
+}
+
+#[test]
+fn aes128_test_vectors() {
+    let vectors = [("000102030405060708090a0b0c0d0e0f",
+                    "00112233445566778899aabbccddeeff",
+                    "69c4e0d86a7b0430d8cdb78070b4c55a"),
+                   ("2b7e151628aed2a6abf7158809cf4f3c",
+                    "3243f6a8885a308d313198a2e0370734",
+                    "3925841d02dc09fbdc118597196a0b32")];
+    for &(key, plaintext, ciphertext) in vectors.iter() {
+        let (key, plaintext) = (from_hex(key), from_hex(plaintext));
+        assert_eq!(Aes128::portable(&key).encrypt_block(&plaintext),
+                   from_hex(ciphertext));
+        assert_eq!(Aes128::new(&key).encrypt_block(&plaintext), from_hex(ciphertext));
+    }
+}
+
+#[test]
+fn aes128_portable_matches_aes_ni() {
+    let mut rng = rand::thread_rng();
+    let mut key = [0u8; 16];
+    rng.fill_bytes(&mut key);
+    let (aes, portable) = (Aes128::new(&key), Aes128::portable(&key));
+    for _ in 0..64 {
+        let mut block = [0u8; 16];
+        rng.fill_bytes(&mut block);
+        assert_eq!(aes.encrypt_block(&block), portable.encrypt_block(&block));
+    }
+}
+
+#[test]
+fn sha256_test_vectors() {
+    let vectors = [("", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+                   ("abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+                   ("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                    "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")];
+    for &(msg, digest) in vectors.iter() {
+        let mut expected = [0u8; 32];
+        expected[..16].copy_from_slice(&from_hex(&digest[..32]));
+        expected[16..].copy_from_slice(&from_hex(&digest[32..]));
+        assert_eq!(Sha256::digest(msg.as_bytes()), expected);
+
+        let mut h = Sha256::new();
+        for b in msg.as_bytes() {
+            h.update(&[*b]);
+        }
+        assert_eq!(h.finish(), expected);
+    }
+}
+
+#[test]
+fn edwards25519_group() {
+    let mut rng = rand::thread_rng();
+    let g = EdwardsPoint::basepoint();
+    assert_eq!(EdwardsPoint::from_bytes(&g.to_bytes()), Some(g));
+
+    // the base point has order 2^252 + 27742317777372353535851937790883648493
+    let order = [0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde,
+                 0xf9, 0xde, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                 0x00, 0x00, 0x00, 0x00, 0x00, 0x10];
+    assert!(g.mul_scalar(&order).is_identity());
+
+    let (a, b) = (curve25519::random_scalar(&mut rng), curve25519::random_scalar(&mut rng));
+    let (pub_a, pub_b) = (EdwardsPoint::mul_base(&a), EdwardsPoint::mul_base(&b));
+    assert_eq!(pub_a.mul_scalar(&b), pub_b.mul_scalar(&a));
+    assert_eq!((pub_a + pub_b) - pub_b, pub_a);
+    assert!(EdwardsPoint::from_bytes(&(pub_a + pub_b).to_bytes()).is_some());
+
+    let mut invalid = g.to_bytes();
+    invalid[0] ^= 1;
+    assert!(EdwardsPoint::from_bytes(&invalid).is_none());
+
+    // the doubling and the precomputed table agree with the addition
+    assert_eq!(pub_a.double(), pub_a + pub_a);
+    let table = PointTable::new(&pub_a);
+    assert_eq!(table.mul_scalar(&b), pub_a.mul_scalar(&b));
+    assert!(PointTable::new(&g).mul_scalar(&order).is_identity());
+    let mut two = [0u8; 32];
+    two[0] = 2;
+    assert_eq!(table.mul_scalar(&two), pub_a.double());
+    assert_eq!(EdwardsPoint::conditional_select(&pub_a, &pub_b, 0), pub_a);
+    assert_eq!(EdwardsPoint::conditional_select(&pub_a, &pub_b, 1), pub_b);
+}
@@ -0,0 +1,294 @@
+extern crate libgc;
+extern crate rand;
+
+mod common;
+
+use std::env;
+use std::path::Path;
+use std::collections::HashMap;
+
+use rand::Rng;
+use libgc::parser;
+use libgc::parser::types::GateType;
+use libgc::circuit::binary;
+use libgc::circuit::garbled::{Garbler, Evaluator, Label, Scheme, FixedKeyAes};
+#[cfg(feature = "parallel")]
+use libgc::circuit::garbled::ParallelGarbler;
+use libgc::circuit::garbled::format;
+use common::{to_bits, from_bits};
+
+const SCHEMES: [Scheme; 4] = [Scheme::PointAndPermute,
+                              Scheme::FreeXOR,
+                              Scheme::RowReduction,
+                              Scheme::HalfGates];
+
+#[test]
+fn garble_libgc_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    for scheme in &SCHEMES {
+        let info = parser::parse_meta_info(path.as_path()).unwrap();
+        let mut garbler = Garbler::with_scheme(info, *scheme).unwrap();
+        let garbling = garbler.garble(&mut rand::thread_rng()).unwrap();
+
+        let mut rows = 0;
+        for node in parser::parse_circuit(path.as_path()).unwrap() {
+            if let Some(gate_type) = node.gate_type() {
+                rows += scheme.rows(gate_type);
+            }
+        }
+        assert_eq!(garbling.circuit.scheme(), *scheme);
+        assert_eq!(garbling.circuit.len(), rows);
+        assert_eq!(garbling.inputs.len(), 64);
+        assert_eq!(garbling.outputs.len(), 32);
+
+        let delta = garbling.inputs[&1].0 ^ garbling.inputs[&1].1;
+        for (_, pair) in garbling.inputs.iter().chain(garbling.outputs.iter()) {
+            assert!(pair.0 != pair.1);
+            assert!(pair.0.point() != pair.1.point());
+            if scheme.is_free_xor() {
+                assert_eq!(pair.0 ^ pair.1, delta);
+            }
+        }
+    }
+}
+
+#[test]
+fn free_xor_garbles_and_gates_only() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut and_gates = 0;
+    for node in parser::parse_circuit(path.as_path()).unwrap() {
+        match node.gate_type() {
+            Some(GateType::AND) | Some(GateType::OR) => and_gates += 1,
+            _ => (),
+        }
+    }
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let garbling = Garbler::new(info).unwrap().garble(&mut rand::thread_rng()).unwrap();
+    assert_eq!(garbling.circuit.len(), 4 * and_gates);
+}
+
+#[test]
+fn compare_table_sizes() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut sizes = Vec::new();
+    for scheme in &SCHEMES {
+        let info = parser::parse_meta_info(path.as_path()).unwrap();
+        let garbling = Garbler::with_scheme(info, *scheme)
+            .unwrap()
+            .garble(&mut rand::thread_rng())
+            .unwrap();
+        sizes.push(garbling.circuit.size());
+    }
+    // point-and-permute > free-XOR > GRR3 > half-gates
+    assert!(sizes[0] > sizes[1]);
+    assert_eq!(sizes[1] / 4, sizes[2] / 3);
+    assert_eq!(sizes[1] / 4, sizes[3] / 2);
+}
+
+#[test]
+fn evaluate_libgc_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    for scheme in &SCHEMES {
+        evaluate_libgc(path.as_path(), *scheme);
+    }
+}
+
+#[test]
+fn evaluate_libgc_sum2() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    for scheme in &SCHEMES {
+        evaluate_libgc(path.as_path(), *scheme);
+    }
+}
+
+#[test]
+fn evaluate_libgc_gates() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    for scheme in &SCHEMES {
+        let info = parser::parse_meta_info(path.as_path()).unwrap();
+        let mut garbler = Garbler::with_scheme(info, *scheme).unwrap();
+        let mut evaluator = Evaluator::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        for a in 0..2u8 {
+            for b in 0..2u8 {
+                let garbling = garbler.garble(&mut rand::thread_rng()).unwrap();
+                let mut inputs = HashMap::new();
+                inputs.insert(1, garbling.encode(1, a).unwrap());
+                inputs.insert(2, garbling.encode(2, b).unwrap());
+                let output = evaluator.evaluate(&garbling.circuit, &inputs, &garbling.decoding())
+                    .unwrap();
+                assert_eq!(output, vec![a & b, a | b, a ^ b, a ^ 1, a ^ 1]);
+            }
+        }
+    }
+}
+
+#[test]
+fn encode_garbled_circuit() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut rng = rand::thread_rng();
+    let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+    for scheme in &SCHEMES {
+        let mut garbler = Garbler::with_scheme(parser::parse_meta_info(path.as_path()).unwrap(),
+                                               *scheme)
+            .unwrap();
+        let garbling = garbler.garble(&mut rng).unwrap();
+        let buf = format::write_circuit(Vec::new(), &info, &garbling.circuit, &garbling.decoding())
+            .unwrap();
+        assert!(buf.len() > garbling.circuit.size());
+
+        let mut reader = format::Reader::open(buf.as_slice(), &info).unwrap();
+        assert_eq!(reader.header().scheme, *scheme);
+        assert_eq!(reader.header().inputs, 64);
+        let (gc, decoding) = reader.read_circuit().unwrap();
+        assert_eq!(gc.tables(), garbling.circuit.tables());
+        assert_eq!(decoding, garbling.decoding());
+
+        let mut inputs = HashMap::new();
+        for (i, bit) in to_bits(x, y).iter().enumerate() {
+            let id = i as u64 + 1;
+            inputs.insert(id, garbling.encode(id, *bit).unwrap());
+        }
+        let mut evaluator = Evaluator::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        let output = evaluator.evaluate(&gc, &inputs, &decoding).unwrap();
+        assert_eq!(from_bits(&output), x.wrapping_add(y));
+    }
+}
+
+#[test]
+fn encoded_garbled_circuit_must_match_circuit() {
+    let sum = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let gates = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    let info = parser::parse_meta_info(sum.as_path()).unwrap();
+    let garbling = Garbler::new(parser::parse_meta_info(sum.as_path()).unwrap())
+        .unwrap()
+        .garble(&mut rand::thread_rng())
+        .unwrap();
+    let buf = format::write_circuit(Vec::new(), &info, &garbling.circuit, &garbling.decoding())
+        .unwrap();
+
+    let other = parser::parse_meta_info(gates.as_path()).unwrap();
+    assert!(format::Reader::open(buf.as_slice(), &other).is_err());
+    assert!(format::Reader::open(&buf[1..], &info).is_err());
+
+    let mut reader = format::Reader::open(buf.as_slice(), &info).unwrap();
+    assert!(reader.read_decoding().is_err());
+
+    // the number of decoding bits must match the OUTPUTS of the header
+    let mut invalid = buf.clone();
+    let n = invalid.len() - 4 - 8;
+    for byte in &mut invalid[n..n + 8] {
+        *byte = 0xff;
+    }
+    let mut reader = format::Reader::open(invalid.as_slice(), &info).unwrap();
+    assert!(reader.read_circuit().is_err());
+    let mut reader = format::Reader::open(buf.as_slice(), &info).unwrap();
+    assert!(reader.read_circuit().is_ok());
+
+    // the garbled tables must not exceed the ones of GATES
+    let header = format::Header::new(garbling.circuit.scheme(), &info);
+    let mut writer = format::Writer::new(Vec::new(), &header).unwrap();
+    for _ in 0..4 * info.gates + 1 {
+        writer.write_label(Label::default()).unwrap();
+    }
+    let invalid = writer.finish(&garbling.decoding()).unwrap();
+    let mut reader = format::Reader::open(invalid.as_slice(), &info).unwrap();
+    assert!(reader.read_circuit().is_err());
+}
+
+#[test]
+fn stream_garbled_circuit() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let hash = FixedKeyAes::default();
+    let mut rng = rand::thread_rng();
+    for scheme in &SCHEMES {
+        let mut garbler = Garbler::with_scheme(parser::parse_meta_info(path.as_path()).unwrap(),
+                                               *scheme)
+            .unwrap();
+        let mut evaluator = Evaluator::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+
+        let mut writer = format::Writer::new(Vec::new(), &format::Header::new(*scheme, &info))
+            .unwrap();
+        let encoding = garbler.garble_into(&mut rng, &hash, &mut writer).unwrap();
+        let buf = writer.finish(&encoding.decoding()).unwrap();
+
+        let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+        let mut inputs = HashMap::new();
+        for (i, bit) in to_bits(x, y).iter().enumerate() {
+            let id = i as u64 + 1;
+            inputs.insert(id, encoding.encode(id, *bit).unwrap());
+        }
+        let mut reader = format::Reader::open(buf.as_slice(), &info).unwrap();
+        evaluator.evaluate_from(*scheme, &mut reader, &inputs, &hash).unwrap();
+        let output = evaluator.decode(&reader.read_decoding().unwrap()).unwrap();
+        assert_eq!(from_bits(&output), x.wrapping_add(y));
+    }
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn garble_libgc_parallel() {
+    let mut rng = rand::thread_rng();
+    for name in &["sum", "sum2", "gates"] {
+        let path = env::current_dir().unwrap().join("tests").join("libgc_output").join(name);
+        let mut circuit = binary::Circuit::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        let mut evaluator = Evaluator::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        for (scheme, threads) in SCHEMES.iter().zip(1..5) {
+            let info = parser::parse_meta_info(path.as_path()).unwrap();
+            let garbler = ParallelGarbler::with_scheme(info, *scheme, threads).unwrap();
+            let garbling = garbler.garble(&mut rng).unwrap();
+            let sequential = Garbler::with_scheme(parser::parse_meta_info(path.as_path())
+                                                      .unwrap(),
+                                                  *scheme)
+                .unwrap()
+                .garble(&mut rng)
+                .unwrap();
+            assert_eq!(garbling.circuit.len(), sequential.circuit.len());
+
+            let n = garbling.inputs.len();
+            let bits: Vec<u8> = (0..n).map(|_| rng.gen::<u8>() & 1).collect();
+            let mut inputs = HashMap::new();
+            for (i, bit) in bits.iter().enumerate() {
+                let id = i as u64 + 1;
+                inputs.insert(id, garbling.encode(id, *bit).unwrap());
+                circuit.set_input(id, *bit);
+            }
+            let output = evaluator.evaluate(&garbling.circuit, &inputs, &garbling.decoding())
+                .unwrap();
+            circuit.execute().unwrap();
+            assert_eq!(output, circuit.collect_output());
+        }
+    }
+}
+
+fn evaluate_libgc(path: &Path, scheme: Scheme) {
+    let mut circuit = binary::Circuit::new(parser::parse_meta_info(path).unwrap()).unwrap();
+    let mut garbler = Garbler::with_scheme(parser::parse_meta_info(path).unwrap(), scheme)
+        .unwrap();
+    let mut evaluator = Evaluator::new(parser::parse_meta_info(path).unwrap()).unwrap();
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..8 {
+        let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+        let bits = to_bits(x, y);
+
+        let garbling = garbler.garble(&mut rng).unwrap();
+        let mut inputs = HashMap::new();
+        for (i, bit) in bits.iter().enumerate() {
+            let id = i as u64 + 1;
+            inputs.insert(id, garbling.encode(id, *bit).unwrap());
+            circuit.set_input(id, *bit);
+        }
+        let output = evaluator.evaluate(&garbling.circuit, &inputs, &garbling.decoding())
+            .unwrap();
+        circuit.execute().unwrap();
+        assert_eq!(output, circuit.collect_output());
+        assert_eq!(from_bits(&output), x.wrapping_add(y));
+    }
+}
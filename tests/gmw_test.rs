@@ -0,0 +1,163 @@
+extern crate libgc;
+extern crate rand;
+
+mod common;
+
+use std::env;
+use std::thread;
+use std::path::Path;
+use std::collections::HashMap;
+
+use rand::Rng;
+use libgc::channel;
+use libgc::parser;
+use libgc::parser::types::GateType;
+use libgc::circuit::gmw::{self, Evaluator};
+use common::{execute, to_bits};
+
+/// Evaluates the circuit at `path` with `n` parties - the input `id` belongs
+/// to the party `owners[id - 1]` - and returns the output of every party.
+/// The triples are generated by a trusted dealer or with OT.
+fn run(path: &Path, n: usize, owners: &[usize], bits: &[u8], dealer: bool) -> Vec<Vec<u8>> {
+    let info = parser::parse_meta_info(path).unwrap();
+    let count = gmw::count_triples(&info).unwrap();
+    let mut dealt = gmw::deal_triples(&mut rand::thread_rng(), n, count).unwrap().into_iter();
+
+    let mut handles = Vec::with_capacity(n);
+    for (mut net, id) in channel::network(n).into_iter().zip(0..n) {
+        let mut inputs = HashMap::new();
+        for (i, bit) in bits.iter().enumerate() {
+            if owners[i] == id {
+                inputs.insert(i as u64 + 1, *bit);
+            }
+        }
+        let owners = owners.to_vec();
+        let mut evaluator = Evaluator::new(parser::parse_meta_info(path).unwrap()).unwrap();
+        let triples = dealt.next().unwrap();
+        handles.push(thread::spawn(move || {
+            let mut rng = rand::thread_rng();
+            let triples = if dealer {
+                triples
+            } else {
+                gmw::generate_triples(&mut net, &mut rng, count).unwrap()
+            };
+            assert_eq!(triples.len() as u64, count);
+            evaluator.evaluate(&mut net,
+                               &mut rng,
+                               &mut triples.into_iter(),
+                               &owners,
+                               &inputs)
+                .unwrap()
+        }));
+    }
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+
+#[test]
+fn gmw_two_party_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut rng = rand::thread_rng();
+    let owners: Vec<usize> = (0..64).map(|i| i / 32).collect();
+    for _ in 0..4 {
+        let bits = to_bits(rng.gen(), rng.gen());
+        let expected = execute(path.as_path(), &bits);
+        for output in run(path.as_path(), 2, &owners, &bits, true) {
+            assert_eq!(output, expected);
+        }
+    }
+}
+
+#[test]
+fn gmw_multi_party_sum2() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let mut rng = rand::thread_rng();
+    let owners: Vec<usize> = (0..64).map(|i| i % 3).collect();
+    let bits = to_bits(rng.gen(), rng.gen());
+    let expected = execute(path.as_path(), &bits);
+    for output in run(path.as_path(), 4, &owners, &bits, true) {
+        assert_eq!(output, expected);
+    }
+}
+
+#[test]
+fn gmw_gates() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    for n in 2..5 {
+        for x in 0..4u8 {
+            let bits = [x & 1, x >> 1];
+            let expected = execute(path.as_path(), &bits);
+            for output in run(path.as_path(), n, &[0, n - 1], &bits, true) {
+                assert_eq!(output, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn count_triples() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut count = 0;
+    for node in parser::parse_circuit(path.as_path()).unwrap() {
+        match node.gate_type() {
+            Some(GateType::AND) | Some(GateType::OR) => count += 1,
+            _ => (),
+        }
+    }
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    assert_eq!(gmw::count_triples(&info).unwrap(), count);
+
+    // sum2 uses sum as sub circuit
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let evaluator = Evaluator::new(parser::parse_meta_info(path.as_path()).unwrap()).unwrap();
+    assert!(evaluator.triples().unwrap() >= count);
+
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("gates");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    assert_eq!(gmw::count_triples(&info).unwrap(), 2);
+}
+
+#[test]
+fn deal_triples() {
+    let mut rng = rand::thread_rng();
+    assert!(gmw::deal_triples(&mut rng, 0, 10).is_err());
+
+    let shares = gmw::deal_triples(&mut rng, 1, 10).unwrap();
+    assert_eq!(shares.len(), 1);
+    assert!(shares[0].iter().all(|t| t.c == t.a & t.b));
+}
+
+#[test]
+fn generate_triples_with_ot() {
+    for n in 2..4 {
+        let mut handles = Vec::with_capacity(n);
+        for mut net in channel::network(n) {
+            handles.push(thread::spawn(move || {
+                gmw::generate_triples(&mut net, &mut rand::thread_rng(), 300).unwrap()
+            }));
+        }
+        let shares: Vec<Vec<gmw::Triple>> = handles.into_iter()
+            .map(|h| h.join().unwrap())
+            .collect();
+        for i in 0..300 {
+            let (mut a, mut b, mut c) = (0, 0, 0);
+            for s in &shares {
+                a ^= s[i].a;
+                b ^= s[i].b;
+                c ^= s[i].c;
+            }
+            assert_eq!(c, a & b);
+        }
+    }
+}
+
+#[test]
+fn gmw_with_ot_triples() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let owners: Vec<usize> = (0..64).map(|i| i / 32).collect();
+    let bits = to_bits(0xDEADBEEF, 0x12345678);
+    let expected = execute(path.as_path(), &bits);
+    for output in run(path.as_path(), 3, &owners, &bits, false) {
+        assert_eq!(output, expected);
+    }
+}
@@ -1,7 +1,13 @@
 extern crate libgc;
+extern crate rand;
 
+use std::fs;
 use std::env;
+use std::process;
+use std::io::{Read, Write};
 use std::path::Path;
+use rand::Rng;
+use libgc::cbmc;
 use libgc::parser;
 use libgc::circuit::binary;
 
@@ -14,20 +20,286 @@ fn parse_libgc() {
     parser::parse_circuit(path.as_path()).unwrap();
 }
 
+#[test]
+fn parse_libgc_sub_paths() {
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let sub = src.join("sub");
+    let info = parser::parse_meta_info(src.as_path()).unwrap();
+    assert_eq!(info.sub_circuits["c0"], sub.to_string_lossy());
+
+    // an absolute path is not resolved against the circuit directory
+    let dir = env::temp_dir().join(format!("libgc-sub-path-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(src.join("circuit.txt"), dir.join("circuit.txt")).unwrap();
+    let meta = format!("INPUTS = 64\nGATES = 0\nOUTPUTS = 33\nc0 = {}", sub.display());
+    fs::File::create(dir.join("meta_info.txt")).unwrap().write_all(meta.as_bytes()).unwrap();
+    let info = parser::parse_meta_info(dir.as_path()).unwrap();
+    assert_eq!(info.sub_circuits["c0"], sub.to_string_lossy());
+    execute_libgc(dir.as_path());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parse_libgc_invalid_interface() {
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let dir = env::temp_dir().join(format!("libgc-interface-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(src.join("meta_info.txt"), dir.join("meta_info.txt")).unwrap();
+    let lines = ["\u{e4}INPUT_A_x 1 32",
+                 "+INPUT_A_x 1 18446744073709551615",
+                 "+INPUT_A_x 60 8",
+                 "-OUTPUT_sum INT32 1 2 1000"];
+    for line in &lines {
+        fs::File::create(dir.join("interface.txt")).unwrap().write_all(line.as_bytes()).unwrap();
+        assert!(parser::parse_meta_info(dir.as_path()).is_err(), "{}", line);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn write_libgc() {
+    // copy sum2 with the writer - the copy still refers to the sub circuit
+    // of sum2 and can be executed
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let dir = env::temp_dir().join(format!("libgc-writer-test-{}", process::id()));
+    let mut info = parser::parse_meta_info(src.as_path()).unwrap();
+    let nodes = parser::parse_circuit(src.as_path()).unwrap();
+    parser::write_libgc(dir.as_path(), &info, &nodes).unwrap();
+    let copy = parser::parse_meta_info(dir.as_path()).unwrap();
+    assert_eq!(copy.sub_circuits["c0"], src.join("sub").to_string_lossy());
+    execute_libgc(dir.as_path());
+
+    // sub circuits within the destination are written relative to it
+    let sub = parser::parse_meta_info(src.join("sub").as_path()).unwrap();
+    let sub_nodes = parser::parse_circuit(src.join("sub").as_path()).unwrap();
+    parser::write_libgc(dir.join("sub").as_path(), &sub, &sub_nodes).unwrap();
+    info.sub_circuits.insert(String::from("c0"), dir.join("sub").to_string_lossy().into_owned());
+    parser::write_libgc(dir.as_path(), &info, &nodes).unwrap();
+    let mut meta = String::new();
+    fs::File::open(dir.join("meta_info.txt")).unwrap().read_to_string(&mut meta).unwrap();
+    assert!(meta.ends_with("\nc0 = sub"));
+    let copy = parser::parse_meta_info(dir.as_path()).unwrap();
+    assert_eq!(copy.sub_circuits["c0"], dir.join("sub").to_string_lossy());
+    assert_eq!(format!("{}", parser::parse_meta_info(dir.join("sub").as_path()).unwrap()),
+               format!("{}", sub));
+    let written: Vec<String> = parser::parse_circuit(dir.as_path())
+        .unwrap()
+        .iter()
+        .map(|node| format!("{}", node))
+        .collect();
+    assert_eq!(written, nodes.iter().map(|node| format!("{}", node)).collect::<Vec<String>>());
+    execute_libgc(dir.as_path());
+
+    // nodes without edges and unknown sub circuits cannot be written
+    let node = parser::types::Node::new(parser::types::ID::Input(1), None, None, Vec::new());
+    assert!(parser::write_libgc(dir.as_path(), &info, &[node]).is_err());
+    let mut nodes = nodes;
+    nodes.push(parser::types::Node::new(parser::types::ID::Output(1),
+                                        None,
+                                        Some(String::from("c1")),
+                                        nodes[0].edges().to_vec()));
+    assert!(parser::write_libgc(dir.as_path(), &info, &nodes).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
 #[test]
 fn execute_libgc_sum() {
     let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
     execute_libgc(path.as_path());
 }
 
-// to run this test, you must set the c0 path in sum2/sub/meta_info.txt to a valid path
-// change: /home/andreas/rust/libgc/tests/libgc_output/sum2/sub to a valid path
-// #[test] 
+#[test]
 fn execute_libgc_sum2() {
     let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
     execute_libgc(path.as_path());
 }
 
+#[test]
+fn execute_libgc_without_io() {
+    // the circuit files are only read while the circuit is compiled
+    let src = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let dir = env::temp_dir().join(format!("libgc-compiled-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for name in &["circuit.txt", "meta_info.txt"] {
+        fs::copy(src.join(name), dir.join(name)).unwrap();
+    }
+    let info = parser::parse_meta_info(dir.as_path()).unwrap();
+    let mut circuit = binary::Circuit::new(info).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // test 2 + 2 = 4
+    set_pin_from_to(&mut circuit, 1, 64, 0);
+    circuit.set_input(2, 1);  // Alice
+    circuit.set_input(34, 1); // Bob
+    circuit.execute().unwrap();
+    let mut expected = vec![0u8; 32];
+    expected[2] = 1;
+    assert_eq!(circuit.collect_output(), expected);
+
+    // unknown input IDs make the execution fail
+    circuit.set_input(65, 1);
+    assert!(circuit.execute().is_err());
+}
+
+#[test]
+fn execute_libgc_variables() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    assert_eq!(info.interface.inputs.len(), 2);
+    assert_eq!(info.interface.output("OUTPUT_sum").unwrap().var_type(), Some("INT32"));
+    let mut circuit = binary::Circuit::new(info).unwrap();
+
+    circuit.set("INPUT_A_x", 4i32).unwrap();
+    circuit.set("INPUT_B_y", 5i32).unwrap();
+    circuit.execute().unwrap();
+    assert_eq!(circuit.get::<i32>("OUTPUT_sum").unwrap(), 9);
+
+    circuit.set("INPUT_A_x", -7i32).unwrap();
+    circuit.set("INPUT_B_y", 3i32).unwrap();
+    circuit.execute().unwrap();
+    assert_eq!(circuit.get::<i32>("OUTPUT_sum").unwrap(), -4);
+    assert_eq!(circuit.get::<u32>("OUTPUT_sum").unwrap(), -4i32 as u32);
+    assert_eq!(circuit.get::<i64>("OUTPUT_sum").unwrap(), -4);
+
+    assert!(circuit.set("INPUT_C_z", 1u8).is_err());
+    assert!(circuit.get::<i32>("OUTPUT_diff").is_err());
+}
+
+#[test]
+fn compile_libgc_sum2() {
+    // sum2 uses sum as sub circuit - it is inlined into the graph
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let sum = binary::Graph::compile(&parser::parse_meta_info(path.as_path()).unwrap()).unwrap();
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    let graph = binary::Graph::compile(&parser::parse_meta_info(path.as_path()).unwrap()).unwrap();
+    assert_eq!(graph.inputs(), 64);
+    assert_eq!(graph.outputs().len(), 32);
+    assert!(graph.operations().len() >= sum.operations().len());
+    for op in graph.operations() {
+        assert!(op.a < op.out && op.b < op.out && op.out < graph.wires());
+    }
+
+    let mut bits = vec![0u8; 64];
+    bits[0] = 1;  // Alice
+    bits[32] = 1; // Bob
+    let mut values = Vec::new();
+    let mut expected = vec![0u8; 32];
+    expected[1] = 1;
+    assert_eq!(graph.evaluate(&bits, &mut values).unwrap(), expected);
+    assert_eq!(values.len(), graph.wires());
+    assert!(graph.evaluate(&bits[1..], &mut values).is_err());
+}
+
+#[test]
+fn execute_libgc_batch() {
+    let mut rng = rand::thread_rng();
+    for name in &["sum", "sum2", "gates"] {
+        let path = env::current_dir().unwrap().join("tests").join("libgc_output").join(name);
+        let mut circuit = binary::Circuit::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        let n = circuit.graph().inputs();
+
+        // 150 instances do not fill the last word of any lane width
+        let inputs: Vec<Vec<u8>> = (0..150)
+            .map(|_| (0..n).map(|_| rng.gen::<u8>() & 1).collect())
+            .collect();
+        let mut expected = Vec::with_capacity(inputs.len());
+        for bits in &inputs {
+            for (i, bit) in bits.iter().enumerate() {
+                circuit.set_input(i as u64 + 1, *bit);
+            }
+            circuit.execute().unwrap();
+            expected.push(circuit.collect_output());
+        }
+
+        let mut batch: binary::Batch = binary::Batch::from_graph(circuit.graph().clone());
+        assert_eq!(batch.evaluate(&inputs).unwrap(), expected);
+        let mut batch = binary::Batch::<u128>::new(parser::parse_meta_info(path.as_path())
+                .unwrap())
+            .unwrap();
+        assert_eq!(batch.evaluate(&inputs).unwrap(), expected);
+        assert!(batch.evaluate(&[vec![0u8; n + 1]]).is_err());
+        let mut batch = binary::Batch::<binary::U256>::from_graph(circuit.graph().clone());
+        assert_eq!(batch.evaluate(&inputs).unwrap(), expected);
+    }
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn execute_libgc_parallel() {
+    let mut rng = rand::thread_rng();
+    for name in &["sum", "sum2", "gates"] {
+        let path = env::current_dir().unwrap().join("tests").join("libgc_output").join(name);
+        let mut circuit = binary::Circuit::new(parser::parse_meta_info(path.as_path()).unwrap())
+            .unwrap();
+        let graph = circuit.graph().clone();
+
+        // every operation depends on operations of lower levels only
+        let levels = graph.levels().unwrap();
+        let mut level = vec![0; graph.wires()];
+        let mut count = 0;
+        for (l, ops) in levels.iter().enumerate() {
+            for i in ops {
+                let op = graph.operations()[*i];
+                assert!(level[op.a] <= l && level[op.b] <= l);
+                level[op.out] = l + 1;
+                count += 1;
+            }
+        }
+        assert_eq!(count, graph.operations().len());
+
+        for threads in 1..5 {
+            let parallel = binary::ParallelCircuit::from_graph(graph.clone(), threads).unwrap();
+            let bits: Vec<u8> = (0..graph.inputs()).map(|_| rng.gen::<u8>() & 1).collect();
+            for (i, bit) in bits.iter().enumerate() {
+                circuit.set_input(i as u64 + 1, *bit);
+            }
+            circuit.execute().unwrap();
+            assert_eq!(parallel.evaluate(&bits).unwrap(), circuit.collect_output());
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "parallel")]
+fn parallel_pool_passes_on_panics() {
+    let pool = binary::Pool::new(4);
+    let levels = vec![(0..8).collect::<Vec<usize>>(), (8..16).collect()];
+
+    let result = std::panic::catch_unwind(|| {
+        let mut values = vec![0u8; 16];
+        pool.run(&levels,
+                 &mut values,
+                 |_, op| if op == 3 { panic!("compute") } else { 1u8 },
+                 |values, op, val| values[op] = val);
+    });
+    assert!(result.is_err());
+
+    let result = std::panic::catch_unwind(|| {
+        let mut values = vec![0u8; 16];
+        pool.run(&levels,
+                 &mut values,
+                 |_, _| 1u8,
+                 |_, op, _| if op == 12 { panic!("apply") });
+    });
+    assert!(result.is_err());
+
+    let mut values = vec![0u8; 16];
+    pool.run(&levels, &mut values, |_, op| op as u8, |values, op, val| values[op] = val);
+    assert_eq!(values, (0..16).collect::<Vec<u8>>());
+}
+
+#[test]
+fn topological_levels() {
+    // 0 -> 2, 1 -> 2, 2 -> 3, 0 -> 3
+    let succ = vec![vec![2, 3], vec![2], vec![3], vec![]];
+    let levels = cbmc::levels(4, |i| succ[i].iter().cloned()).unwrap();
+    assert_eq!(levels, vec![vec![0, 1], vec![2], vec![3]]);
+
+    let cycle = vec![vec![1], vec![0]];
+    assert!(cbmc::levels(2, |i| cycle[i].iter().cloned()).is_err());
+}
+
 fn execute_libgc(path: &Path) {
     let info = parser::parse_meta_info(path).unwrap();
     let mut circuit = binary::Circuit::new(info).unwrap();
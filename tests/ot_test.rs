@@ -0,0 +1,95 @@
+extern crate libgc;
+extern crate rand;
+
+use std::env;
+use std::thread;
+use std::collections::HashMap;
+
+use rand::Rng;
+use libgc::channel;
+use libgc::parser;
+use libgc::circuit::garbled::{Garbler, Evaluator, Label};
+use libgc::ot::{self, Sender, Receiver};
+
+fn run_ot<S, R>(mut sender: S, mut receiver: R, n: usize)
+    where S: Sender + Send + 'static,
+          R: Receiver
+{
+    let mut rng = rand::thread_rng();
+    let mut messages = Vec::with_capacity(n);
+    let mut choices = Vec::with_capacity(n);
+    for _ in 0..n {
+        messages.push((Label::random(&mut rng), Label::random(&mut rng)));
+        choices.push(rng.gen::<u8>() & 1);
+    }
+
+    let (mut ch0, mut ch1) = channel::pipe();
+    let msgs = messages.clone();
+    let handle = thread::spawn(move || {
+        sender.send(&mut ch0, &mut rand::thread_rng(), &msgs).unwrap();
+    });
+    let received = receiver.receive(&mut ch1, &mut rng, &choices).unwrap();
+    handle.join().unwrap();
+
+    assert_eq!(received.len(), n);
+    for ((m, c), r) in messages.iter().zip(choices.iter()).zip(received.iter()) {
+        assert_eq!(*r, if *c == 1 { m.1 } else { m.0 });
+    }
+}
+
+#[test]
+fn chou_orlandi_ot() {
+    run_ot(ot::ChouOrlandiSender::new(), ot::ChouOrlandiReceiver::new(), 16);
+}
+
+#[test]
+fn iknp_ot_extension() {
+    run_ot(ot::IknpSender::new(), ot::IknpReceiver::new(), 5000);
+}
+
+#[test]
+fn reject_invalid_choices() {
+    let (_, mut ch) = channel::pipe();
+    let mut rng = rand::thread_rng();
+    let choices = [0, 1, 2];
+    assert!(ot::ChouOrlandiReceiver::new().receive(&mut ch, &mut rng, &choices).is_err());
+    assert!(ot::IknpReceiver::new().receive(&mut ch, &mut rng, &choices).is_err());
+}
+
+#[test]
+fn transfer_input_labels() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let garbling = Garbler::new(info).unwrap().garble(&mut rand::thread_rng()).unwrap();
+
+    // Alice (inputs 1 - 32) garbles, Bob (inputs 33 - 64) evaluates: 7 + 35
+    let (x, y) = (7u64, 35u64);
+    let mut labels = HashMap::new();
+    for id in 1..33 {
+        labels.insert(id, garbling.encode(id, ((x >> (id - 1)) & 1) as u8).unwrap());
+    }
+    let mut bits = HashMap::new();
+    for id in 33..65 {
+        bits.insert(id, ((y >> (id - 33)) & 1) as u8);
+    }
+
+    let (mut ch0, mut ch1) = channel::pipe();
+    let inputs = garbling.inputs.clone();
+    let handle = thread::spawn(move || {
+        let ids: Vec<u64> = (33..65).collect();
+        ot::send_inputs(&mut ch0, &mut rand::thread_rng(), &inputs, &ids).unwrap();
+    });
+    labels.extend(ot::receive_inputs(&mut ch1, &mut rand::thread_rng(), &bits).unwrap());
+    handle.join().unwrap();
+
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let output = Evaluator::new(info)
+        .unwrap()
+        .evaluate(&garbling.circuit, &labels, &garbling.decoding())
+        .unwrap();
+    let mut sum = 0;
+    for (i, bit) in output.iter().enumerate() {
+        sum |= (*bit as u64) << i;
+    }
+    assert_eq!(sum, x + y);
+}
@@ -0,0 +1,235 @@
+extern crate libgc;
+extern crate rand;
+
+use std::env;
+use std::thread;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::collections::HashMap;
+
+use rand::Rng;
+use libgc::cbmc;
+use libgc::channel::{self, BufStream, Channel};
+use libgc::parser;
+use libgc::circuit::garbled::Scheme;
+use libgc::protocol::{Assignment, GarblerParty, EvaluatorParty};
+use libgc::protocol::{CutAndChooseGarbler, CutAndChooseEvaluator, DualExecutionParty};
+use libgc::protocol::write_equality_circuit;
+use libgc::circuit::binary;
+
+fn assignment() -> Assignment {
+    let path = env::current_dir().unwrap().join("tests").join("cbmc_output").join("sum");
+    let parser = cbmc::Parser::new(path.as_path()).unwrap();
+    let a = parser.parse_party_inputs(cbmc::Party::A).unwrap();
+    let b = parser.parse_party_inputs(cbmc::Party::B).unwrap();
+    Assignment::new(&a, &b).unwrap()
+}
+
+fn run_sum(path: &Path, scheme: Scheme, x: u32, y: u32) {
+    let assignment = assignment();
+    let mut alice = HashMap::new();
+    for id in assignment.garbler() {
+        alice.insert(*id, ((x >> (id - 1)) & 1) as u8);
+    }
+    let mut bob = HashMap::new();
+    for id in assignment.evaluator() {
+        bob.insert(*id, ((y >> (id - 33)) & 1) as u8);
+    }
+
+    let (mut ch0, mut ch1) = channel::pipe();
+    let info = parser::parse_meta_info(path).unwrap();
+    let mut garbler = GarblerParty::with_scheme(info, assignment.clone(), scheme).unwrap();
+    let handle = thread::spawn(move || garbler.run(&mut ch0, &mut rand::thread_rng(), &alice));
+
+    let info = parser::parse_meta_info(path).unwrap();
+    let mut evaluator = EvaluatorParty::new(info, assignment).unwrap();
+    let output = evaluator.run(&mut ch1, &mut rand::thread_rng(), &bob).unwrap();
+    assert_eq!(handle.join().unwrap().unwrap(), output);
+
+    let mut sum = 0u32;
+    for (i, bit) in output.iter().enumerate() {
+        sum |= (*bit as u32) << i;
+    }
+    assert_eq!(sum, x.wrapping_add(y));
+}
+
+#[test]
+fn party_inputs_from_cbmc() {
+    let assignment = assignment();
+    assert_eq!(assignment.garbler(), (1..33).collect::<Vec<u64>>().as_slice());
+    assert_eq!(assignment.evaluator(), (33..65).collect::<Vec<u64>>().as_slice());
+    assert_eq!(assignment, Assignment::split(64, 32));
+}
+
+#[test]
+fn two_party_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut rng = rand::thread_rng();
+    for scheme in &[Scheme::PointAndPermute,
+                    Scheme::FreeXOR,
+                    Scheme::RowReduction,
+                    Scheme::HalfGates] {
+        run_sum(path.as_path(), *scheme, rng.gen(), rng.gen());
+    }
+}
+
+#[test]
+fn two_party_sum2() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum2");
+    run_sum(path.as_path(), Scheme::HalfGates, 0xFFFFFFFF, 2);
+}
+
+#[test]
+fn two_party_sum_tcp() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let (x, y) = (1234567u32, 7654321u32);
+    let mut alice = HashMap::new();
+    let mut bob = HashMap::new();
+    for i in 0..32 {
+        alice.insert(i + 1, ((x >> i) & 1) as u8);
+        bob.insert(i + 33, ((y >> i) & 1) as u8);
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut garbler = GarblerParty::new(info, Assignment::split(64, 32)).unwrap();
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut ch = BufStream::new(stream.try_clone().unwrap(), stream);
+        garbler.run(&mut ch, &mut rand::thread_rng(), &alice)
+    });
+
+    let stream = TcpStream::connect(addr).unwrap();
+    let mut ch = BufStream::new(stream.try_clone().unwrap(), stream);
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut evaluator = EvaluatorParty::new(info, Assignment::split(64, 32)).unwrap();
+    let output = evaluator.run(&mut ch, &mut rand::thread_rng(), &bob).unwrap();
+    assert_eq!(handle.join().unwrap().unwrap(), output);
+
+    let mut sum = 0u32;
+    for (i, bit) in output.iter().enumerate() {
+        sum |= (*bit as u32) << i;
+    }
+    assert_eq!(sum, x + y);
+}
+
+#[test]
+fn cut_and_choose_sum() {
+    let mut rng = rand::thread_rng();
+    for scheme in &[Scheme::FreeXOR, Scheme::HalfGates] {
+        for path in &["sum", "sum2"] {
+            let path = env::current_dir().unwrap().join("tests").join("libgc_output").join(path);
+            let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+            let mut alice = HashMap::new();
+            let mut bob = HashMap::new();
+            for i in 0..32 {
+                alice.insert(i + 1, ((x >> i) & 1) as u8);
+                bob.insert(i + 33, ((y >> i) & 1) as u8);
+            }
+
+            let (mut ch0, mut ch1) = channel::pipe();
+            let info = parser::parse_meta_info(path.as_path()).unwrap();
+            let mut garbler = CutAndChooseGarbler::new(info, Assignment::split(64, 32), *scheme, 8)
+                .unwrap();
+            let handle =
+                thread::spawn(move || garbler.run(&mut ch0, &mut rand::thread_rng(), &alice));
+
+            let info = parser::parse_meta_info(path.as_path()).unwrap();
+            let mut evaluator = CutAndChooseEvaluator::new(info, Assignment::split(64, 32), 8, 5)
+                .unwrap();
+            let output = evaluator.run(&mut ch1, &mut rng, &bob).unwrap();
+            assert_eq!(handle.join().unwrap().unwrap(), output);
+
+            let mut sum = 0u32;
+            for (i, bit) in output.iter().enumerate() {
+                sum |= (*bit as u32) << i;
+            }
+            assert_eq!(sum, x.wrapping_add(y));
+        }
+    }
+}
+
+#[test]
+fn cut_and_choose_parameters() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    assert!(CutAndChooseEvaluator::new(info, Assignment::split(64, 32), 4, 4).is_err());
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    assert!(CutAndChooseGarbler::new(info, Assignment::split(64, 32), Scheme::FreeXOR, 1)
+        .is_err());
+}
+
+#[test]
+fn evaluator_rejects_too_many_ids() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let (mut ch0, mut ch1) = channel::pipe();
+    ch0.send_u64(64).unwrap();
+    ch0.send_u64(u64::max_value()).unwrap();
+
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut evaluator = EvaluatorParty::new(info, Assignment::split(64, 32)).unwrap();
+    let mut bob = HashMap::new();
+    for id in 33..65 {
+        bob.insert(id, 0);
+    }
+    assert!(evaluator.run(&mut ch1, &mut rand::thread_rng(), &bob).is_err());
+}
+
+#[test]
+fn equality_circuit() {
+    for bits in 1..5u64 {
+        let path = env::temp_dir().join(format!("libgc-equality-test-{}", bits));
+        write_equality_circuit(path.as_path(), bits).unwrap();
+        for x in 0..1u64 << bits {
+            for y in 0..1u64 << bits {
+                let info = parser::parse_meta_info(path.as_path()).unwrap();
+                let mut circuit = binary::Circuit::new(info).unwrap();
+                for i in 0..bits {
+                    circuit.set_input(i + 1, ((x >> i) & 1) as u8);
+                    circuit.set_input(bits + i + 1, ((y >> i) & 1) as u8);
+                }
+                circuit.execute().unwrap();
+                assert_eq!(circuit.collect_output(), vec![(x == y) as u8]);
+            }
+        }
+        std::fs::remove_dir_all(path.as_path()).unwrap();
+    }
+}
+
+#[test]
+fn dual_execution_sum() {
+    let path = env::current_dir().unwrap().join("tests").join("libgc_output").join("sum");
+    let mut rng = rand::thread_rng();
+    let (x, y) = (rng.gen::<u32>(), rng.gen::<u32>());
+    let assignment = assignment();
+    let mut alice = HashMap::new();
+    for id in assignment.garbler() {
+        alice.insert(*id, ((x >> (id - 1)) & 1) as u8);
+    }
+    let mut bob = HashMap::new();
+    for id in assignment.evaluator() {
+        bob.insert(*id, ((y >> (id - 33)) & 1) as u8);
+    }
+
+    let (mut ch0, mut ch1) = channel::pipe();
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut a = DualExecutionParty::with_scheme(info,
+                                                assignment.clone(),
+                                                cbmc::Party::A,
+                                                Scheme::HalfGates)
+        .unwrap();
+    let handle = thread::spawn(move || a.run(&mut ch0, &mut rand::thread_rng(), &alice));
+
+    let info = parser::parse_meta_info(path.as_path()).unwrap();
+    let mut b = DualExecutionParty::with_scheme(info, assignment, cbmc::Party::B, Scheme::HalfGates)
+        .unwrap();
+    let output = b.run(&mut ch1, &mut rng, &bob).unwrap();
+    assert_eq!(handle.join().unwrap().unwrap(), output);
+
+    let mut sum = 0u32;
+    for (i, bit) in output.iter().enumerate() {
+        sum |= (*bit as u32) << i;
+    }
+    assert_eq!(sum, x.wrapping_add(y));
+}